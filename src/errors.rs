@@ -18,10 +18,72 @@
 #[derive(Debug)]
 pub enum CatalystError {
   /// `Tree` error is raised when plan is not integral/tree is invalid.
-  Tree(String)
+  Tree(String),
+  /// `Schema` error is raised when data type is invalid or cannot be parsed.
+  Schema(String),
+  /// `Rule` error is raised when rule or batch of rules cannot be created.
+  Rule(String),
+  /// `Eval` error is raised when expression cannot be evaluated, e.g. on overflow.
+  Eval(String),
+  /// `Analysis` error is raised when plan cannot be analyzed, e.g. column cannot be
+  /// resolved or types do not match.
+  Analysis(String)
 }
 
 macro_rules! tree_err {
   ($fmt:expr) => (Err(CatalystError::Tree($fmt.to_owned())));
   ($fmt:expr, $($args:expr), *) => (Err(CatalystError::Tree(format!($fmt, $($args), *))));
 }
+
+macro_rules! schema_err {
+  ($fmt:expr) => (Err(CatalystError::Schema($fmt.to_owned())));
+  ($fmt:expr, $($args:expr), *) => (
+    Err(CatalystError::Schema(format!($fmt, $($args), *)))
+  );
+}
+
+macro_rules! rule_err {
+  ($fmt:expr) => (Err(CatalystError::Rule($fmt.to_owned())));
+  ($fmt:expr, $($args:expr), *) => (Err(CatalystError::Rule(format!($fmt, $($args), *))));
+}
+
+macro_rules! eval_err {
+  ($fmt:expr) => (Err(CatalystError::Eval($fmt.to_owned())));
+  ($fmt:expr, $($args:expr), *) => (Err(CatalystError::Eval(format!($fmt, $($args), *))));
+}
+
+macro_rules! analysis_err {
+  ($fmt:expr) => (Err(CatalystError::Analysis($fmt.to_owned())));
+  ($fmt:expr, $($args:expr), *) => (
+    Err(CatalystError::Analysis(format!($fmt, $($args), *)))
+  );
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn analyze(column: Option<&str>) -> Result<(), CatalystError> {
+    match column {
+      Some(name) => analysis_err!("Cannot resolve column {}", name),
+      None => analysis_err!("Empty column name")
+    }
+  }
+
+  #[test]
+  fn test_analysis_err() {
+    match analyze(None) {
+      Err(CatalystError::Analysis(msg)) => assert_eq!(msg, "Empty column name"),
+      _ => panic!("Expected analysis error")
+    }
+    match analyze(Some("a")) {
+      Err(CatalystError::Analysis(msg)) => assert_eq!(msg, "Cannot resolve column a"),
+      _ => panic!("Expected analysis error")
+    }
+    let err: Result<(), CatalystError> = tree_err!("Cannot resolve column a");
+    match err {
+      Err(CatalystError::Tree(msg)) => assert_eq!(msg, "Cannot resolve column a"),
+      _ => panic!("Expected tree error")
+    }
+  }
+}
@@ -12,12 +12,48 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+use errors::CatalystError;
+use expr::literal::{Literal, OverflowMode, lit};
+use expr::logical::{eq, ge, gt, is_not_null, le, lt};
+use expr::reference::Reference;
 use trees::TreeNode;
-use types::DataType;
+use types::{DataType, StructField};
+
+/// Input row for expression evaluation, values are bound by ordinal.
+pub type Row = Vec<Literal>;
+
+// Renders expression, takes rendered strings of children.
+type RenderFunc = Fn(&Expression, &[String]) -> String + Send + Sync;
+
+// Returns property of the expression, e.g. whether or not expression is foldable.
+type PropertyFunc = Fn(&Expression) -> bool + Send + Sync;
+
+// Returns data type of the expression.
+type DataTypeFunc = (Fn(&Expression) -> &DataType) + Send + Sync;
+
+// Evaluates expression for the input row.
+type EvalFunc = Fn(&Expression, &Row) -> Literal + Send + Sync;
+
+// Evaluates expression for the input row with overflow mode.
+type TryEvalFunc =
+  (Fn(&Expression, &Row, OverflowMode) -> Result<Literal, CatalystError>) + Send + Sync;
+
+// Reconstructs the expression with its constructor.
+type CloneFunc = Fn(&Expression) -> Expression + Send + Sync;
+
+// Partial equality function for expressions.
+type EqFunc = Fn(&Expression, &Expression) -> bool + Send + Sync;
 
 /// A generic expression.
 ///
-/// Each implementation should provide all closure fields.
+/// Each implementation should provide all closure fields, closures are shared between
+/// clones of the expression. Closures are `Send + Sync`, so expressions and plans that
+/// contain them can be optimized in separate threads.
+#[derive(Clone)]
 pub struct Expression {
   // Unique node name.
   name: String,
@@ -25,35 +61,192 @@ pub struct Expression {
   // List of children for the node.
   children: Vec<Expression>,
 
-  // Pretty string for expression.
-  display_func: Box<Fn(&Expression) -> String>,
+  // Value for literal expression.
+  literal: Option<Literal>,
+
+  // Column for reference expression.
+  reference: Option<Reference>,
+
+  // Field name for struct field extraction.
+  field_name: Option<String>,
+
+  // Field names for struct construction, one per child.
+  field_names: Vec<String>,
+
+  // Data type of constructed struct, `None` if any of the children is unresolved.
+  struct_type: Option<DataType>,
+
+  // Target data type for cast expression.
+  target_type: Option<DataType>,
+
+  // Seed for random expression.
+  seed: Option<i64>,
+
+  // Id of the node in the tree, assigned with `assign_ids`, used for debugging.
+  id: Option<usize>,
+
+  // Whether or not this expression evaluates to null when any of its children is null.
+  null_intolerant: bool,
+
+  // Whether or not operands of this binary expression can be swapped, e.g. `a + b`.
+  commutative: bool,
+
+  // Pretty string for expression, takes rendered pretty strings of children.
+  display_func: Arc<RenderFunc>,
+
+  // SQL string for expression, takes rendered SQL strings of children, `None` if SQL is
+  // the same as the pretty string.
+  sql_func: Option<Arc<RenderFunc>>,
 
   // Whether or not this expression is foldable.
-  foldable_func: Box<Fn(&Expression) -> bool>,
+  foldable_func: Arc<PropertyFunc>,
 
   // Whether or not this expression is deterministic.
-  deterministic_func: Box<Fn(&Expression) -> bool>,
+  deterministic_func: Arc<PropertyFunc>,
 
   // Whether or not this expression is nullable.
-  nullable_func: Box<Fn(&Expression) -> bool>,
+  nullable_func: Arc<PropertyFunc>,
 
   // Whether or not this expression is resolved.
-  resolved_func: Box<Fn(&Expression) -> bool>,
+  resolved_func: Arc<PropertyFunc>,
 
   // Resulting data type for this expression.
-  datatype_func: Box<Fn(&Expression) -> &DataType>,
+  datatype_func: Arc<DataTypeFunc>,
+
+  // Evaluates expression for the input row, `None` if expression cannot be evaluated.
+  eval_func: Option<Arc<EvalFunc>>,
 
-  // Clone function for the expression.
-  clone_func: Box<Fn(&Expression) -> Expression>,
+  // Evaluates expression for the input row with overflow mode, `None` if expression
+  // does not handle overflow, in which case `eval_func` is used.
+  try_eval_func: Option<Arc<TryEvalFunc>>,
+
+  // Reconstructs the expression with its constructor, used by `rebuild`.
+  clone_func: Arc<CloneFunc>,
 
   // Partial equality function for the expression.
-  eq_func: Box<Fn(&Expression, &Expression) -> bool>
+  eq_func: Arc<EqFunc>
 }
 
 impl Expression {
   /// Returns a string with pretty print for the expression and all its children.
   pub fn pretty_string(&self) -> String {
-    (self.display_func)(self)
+    let children: Vec<String> = self.children.iter()
+      .map(|child| child.pretty_string())
+      .collect();
+    (self.display_func)(self, &children)
+  }
+
+  /// Same as `pretty_string`, but renders the tree bottom-up with an explicit stack, so
+  /// very deep expressions, e.g. long chains of `&&`, do not overflow the stack.
+  pub fn pretty_string_iterative(&self) -> String {
+    self.render_iterative(&mut |node, children| node.display_with_children(children))
+  }
+
+  /// Same as `pretty_string`, but each node with assigned id is prefixed with its id,
+  /// e.g. `#0(#1(1) + #2(2))`, see `assign_ids`.
+  pub fn pretty_string_with_ids(&self) -> String {
+    self.render_iterative(&mut |node, children| {
+      let display = node.display_with_children(children);
+      match node.id {
+        Some(id) if display.starts_with('(') && display.ends_with(')') => {
+          format!("#{}{}", id, display)
+        },
+        Some(id) => format!("#{}({})", id, display),
+        None => display
+      }
+    })
+  }
+
+  /// Returns SQL representation of this expression, e.g.
+  /// ``((`a` > 1) AND (`b` IS NULL))``.
+  ///
+  /// Each node is rendered with the function set with `ExpressionBuilder::sql`, or same
+  /// as in `pretty_string` if function is not set.
+  pub fn to_sql(&self) -> String {
+    self.render_iterative(&mut |node, children| node.node_to_sql(children))
+  }
+
+  /// Internal method to render SQL of this node using already rendered SQL of children.
+  fn node_to_sql(&self, children: Vec<String>) -> String {
+    match self.sql_func {
+      Some(ref func) => func(self, &children),
+      None => self.display_with_children(children)
+    }
+  }
+
+  /// Returns `true` if operands of this binary expression can be swapped without
+  /// changing the result, set with `ExpressionBuilder::commutative`.
+  pub fn commutative(&self) -> bool {
+    self.commutative
+  }
+
+  /// Returns canonical form of this expression, so semantically equivalent expressions
+  /// have the same SQL representation.
+  ///
+  /// Operands of commutative expressions, e.g. `+`, `*`, `=`, `<=>`, `AND`, and `OR`,
+  /// are ordered by their SQL representation, comparisons are normalized with
+  /// `normalize_comparison`.
+  pub fn canonicalize(&self) -> Expression {
+    self.normalize_comparison().internal_canonicalize().0
+  }
+
+  /// Internal method to canonicalize this expression bottom-up, returns canonical
+  /// expression and its SQL representation, so SQL of each node is rendered once.
+  fn internal_canonicalize(&self) -> (Expression, String) {
+    if self.children.is_empty() {
+      return (self.clone(), self.node_to_sql(vec![]));
+    }
+    let (mut children, mut keys): (Vec<Expression>, Vec<String>) = self.children.iter()
+      .map(|child| child.internal_canonicalize())
+      .unzip();
+    if self.commutative && keys[0] > keys[1] {
+      children.swap(0, 1);
+      keys.swap(0, 1);
+    }
+    let exp = self.with_new_children(children);
+    let key = exp.node_to_sql(keys);
+    (exp, key)
+  }
+
+  /// Returns hash of SQL representation of the canonical form of this expression.
+  ///
+  /// Unlike `tree_hash`, hash is computed with FNV-1a, so it is stable across processes
+  /// and can be used as a key of a shared plan cache.
+  pub fn sql_hash(&self) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    let (_, sql) = self.normalize_comparison().internal_canonicalize();
+    for byte in sql.bytes() {
+      hash ^= byte as u64;
+      hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+  }
+
+  /// Internal method to render this expression bottom-up with an explicit stack, `render`
+  /// is called for each node with already rendered strings of its children.
+  fn render_iterative<F>(&self, render: &mut F) -> String
+    where F: FnMut(&Expression, Vec<String>) -> String
+  {
+    // stack of nodes with flag indicating if children of the node are already rendered
+    let mut stack = vec![(self, false)];
+    let mut rendered: Vec<String> = Vec::new();
+    while let Some((node, children_rendered)) = stack.pop() {
+      if children_rendered {
+        let children = rendered.split_off(rendered.len() - node.children.len());
+        rendered.push(render(node, children));
+      } else {
+        stack.push((node, true));
+        for child in node.children.iter().rev() {
+          stack.push((child, false));
+        }
+      }
+    }
+    rendered.pop().unwrap()
+  }
+
+  /// Internal method to display this node using already rendered strings of children.
+  fn display_with_children(&self, children: Vec<String>) -> String {
+    (self.display_func)(self, &children)
   }
 
   /// Returns `true` when an expression is a candidate for static evaluation before the
@@ -81,6 +274,14 @@ impl Expression {
     (self.deterministic_func)(self)
   }
 
+  /// Returns `true` if result of this expression can be computed once and reused by
+  /// common subexpression elimination, i.e. the whole subtree is deterministic and the
+  /// expression is not a leaf, such as literal or column reference, which is cheaper to
+  /// evaluate than to cache.
+  pub fn is_cse_eligible(&self) -> bool {
+    !self.children.is_empty() && self.deterministic()
+  }
+
   /// Returns `true` when the current expression is nullable.
   ///
   /// Some expressions rely on nullability of their children to determine the nullability
@@ -104,10 +305,479 @@ impl Expression {
     (self.datatype_func)(self)
   }
 
+  /// Returns result of evaluating this expression on the input row.
+  ///
+  /// Panics if expression does not define eval function.
+  pub fn eval(&self, row: &Row) -> Literal {
+    match self.eval_func {
+      Some(ref func) => func(self, row),
+      None => panic!("Eval function is not defined for expression {}", self.name)
+    }
+  }
+
+  /// Returns result of evaluating this expression on the input row, where overflow of
+  /// integral arithmetic is handled according to the mode. Expressions that do not
+  /// handle overflow are evaluated with `eval`.
+  ///
+  /// Panics if expression does not define eval function.
+  pub fn try_eval(
+    &self,
+    row: &Row,
+    mode: OverflowMode
+  ) -> Result<Literal, CatalystError>
+  {
+    match self.try_eval_func {
+      Some(ref func) => func(self, row, mode),
+      None => Ok(self.eval(row))
+    }
+  }
+
   /// Returns list of children for this expression.
   pub fn children(&self) -> &[Expression] {
     &self.children[..]
   }
+
+  /// Returns copy of this expression with the new list of children.
+  ///
+  /// Panics if number of new children is different from the number of children of this
+  /// expression, since expression functions rely on the fixed number of children.
+  pub fn with_new_children(&self, children: Vec<Expression>) -> Expression {
+    if children.len() != self.children.len() {
+      panic!("Expression {} expects {} children, found {}",
+        self.name, self.children.len(), children.len());
+    }
+    // children are not cloned, since they are replaced
+    let mut exp = Expression {
+      name: self.name.clone(),
+      children,
+      literal: self.literal.clone(),
+      reference: self.reference.clone(),
+      field_name: self.field_name.clone(),
+      field_names: self.field_names.clone(),
+      struct_type: None,
+      target_type: self.target_type.clone(),
+      seed: self.seed,
+      id: self.id,
+      null_intolerant: self.null_intolerant,
+      commutative: self.commutative,
+      display_func: self.display_func.clone(),
+      sql_func: self.sql_func.clone(),
+      foldable_func: self.foldable_func.clone(),
+      deterministic_func: self.deterministic_func.clone(),
+      nullable_func: self.nullable_func.clone(),
+      resolved_func: self.resolved_func.clone(),
+      datatype_func: self.datatype_func.clone(),
+      eval_func: self.eval_func.clone(),
+      try_eval_func: self.try_eval_func.clone(),
+      clone_func: self.clone_func.clone(),
+      eq_func: self.eq_func.clone()
+    };
+    exp.update_struct_type();
+    exp
+  }
+
+  /// Returns copy of this expression with the new list of children, reconstructed with
+  /// the constructor of the expression, so all derived properties, e.g. `data_type`,
+  /// `nullable`, and `resolved`, are recomputed for the new children.
+  ///
+  /// Panics if number of new children is different from the number of children of this
+  /// expression.
+  pub fn rebuild(&self, new_children: Vec<Expression>) -> Expression {
+    let exp = self.with_new_children(new_children);
+    let mut rebuilt = (exp.clone_func)(&exp);
+    rebuilt.id = exp.id;
+    rebuilt
+  }
+
+  /// Returns id of this node, or `None` if ids have not been assigned.
+  pub fn id(&self) -> Option<usize> {
+    self.id
+  }
+
+  /// Returns copy of this expression where every node is assigned an id based on its
+  /// position in pre-order traversal, starting with 0 for the root.
+  ///
+  /// Ids are only used for debugging and are ignored when comparing expressions.
+  pub fn assign_ids(&self) -> Expression {
+    self.internal_assign_ids(&mut 0)
+  }
+
+  /// Internal method to assign ids to the nodes in pre-order starting with `next_id`.
+  fn internal_assign_ids(&self, next_id: &mut usize) -> Expression {
+    let mut exp = self.clone();
+    exp.id = Some(*next_id);
+    *next_id += 1;
+    exp.children = self.children.iter().map(|child| child.internal_assign_ids(next_id))
+      .collect();
+    exp
+  }
+
+  /// Returns literal value if this expression is a literal, `None` otherwise.
+  pub fn literal(&self) -> Option<&Literal> {
+    self.literal.as_ref()
+  }
+
+  /// Returns column if this expression is a reference, `None` otherwise.
+  pub fn reference(&self) -> Option<&Reference> {
+    self.reference.as_ref()
+  }
+
+  /// Returns field name if this expression extracts a struct field, `None` otherwise.
+  pub fn field_name(&self) -> Option<&str> {
+    self.field_name.as_deref()
+  }
+
+  /// Returns field names if this expression constructs a struct, empty list otherwise.
+  pub fn field_names(&self) -> &[String] {
+    &self.field_names[..]
+  }
+
+  /// Returns data type of constructed struct, or `None` if this expression does not
+  /// construct a struct or any of the children is unresolved.
+  pub fn struct_type(&self) -> Option<&DataType> {
+    self.struct_type.as_ref()
+  }
+
+  /// Returns target data type if this expression is a cast, `None` otherwise.
+  pub fn target_type(&self) -> Option<&DataType> {
+    self.target_type.as_ref()
+  }
+
+  /// Returns seed if this expression is random, `None` otherwise.
+  pub fn seed(&self) -> Option<i64> {
+    self.seed
+  }
+
+  /// Internal method to update struct type from the current children, should be called
+  /// every time children are modified.
+  fn update_struct_type(&mut self) {
+    if self.field_names.is_empty() {
+      return;
+    }
+    self.struct_type = if self.children.iter().all(|child| child.resolved()) {
+      let fields = self.field_names.iter().zip(self.children.iter()).map(|(name, child)| {
+        StructField::new(name.clone(), child.data_type().clone())
+          .with_nullable(child.nullable())
+      });
+      Some(DataType::struct_type(fields.collect()))
+    } else {
+      None
+    };
+  }
+
+  /// Returns all column references in this expression in pre-order.
+  pub fn references(&self) -> Vec<Reference> {
+    self.collect(&mut |exp| exp.reference().cloned())
+  }
+
+  /// Returns names of all unresolved column references in this expression in pre-order,
+  /// e.g. to report columns that analyzer could not resolve.
+  pub fn unresolved_references(&self) -> Vec<String> {
+    self.collect(&mut |exp| {
+      if exp.reference().is_some() && !exp.resolved() {
+        Some(exp.reference().unwrap().name().to_owned())
+      } else {
+        None
+      }
+    })
+  }
+
+  /// Returns name of each column reference in this expression in pre-order paired with
+  /// index of the field in `schema`, or `None` if field does not exist, e.g. to report
+  /// ordinals of the references when binding them to the input.
+  ///
+  /// Index is always `None` if `schema` is not `StructType`.
+  pub fn reference_bindings(&self, schema: &DataType) -> Vec<(String, Option<usize>)> {
+    self.references().iter().map(|reference| {
+      let index = match schema {
+        DataType::StructType(_) => schema.field_index(reference.name()),
+        _ => None
+      };
+      (reference.name().to_owned(), index)
+    }).collect()
+  }
+
+  /// Returns `true` if every column referenced in this expression is available in the
+  /// `available` struct schema, e.g. to check whether a predicate can be pushed below a
+  /// projection. Expression without references can always be pushed through.
+  ///
+  /// Panics if `available` is not `StructType`.
+  pub fn can_push_through(&self, available: &DataType) -> bool {
+    let fields = match available {
+      DataType::StructType(ref fields) => fields,
+      _ => panic!("Not a StructType")
+    };
+    self.references().iter().all(|column| {
+      fields.iter().any(|field| field.name() == column.name())
+    })
+  }
+
+  /// Returns `true` if this expression is semantically equal to `other` expression.
+  ///
+  /// Unlike `==`, which relies on the equality function of each expression, this method
+  /// structurally compares names, literal values, references, and children recursively,
+  /// so it is consistent for expressions built through different code paths.
+  pub fn semantic_equals(&self, other: &Expression) -> bool {
+    self.name == other.name &&
+      self.literal == other.literal &&
+      self.reference == other.reference &&
+      self.field_name == other.field_name &&
+      self.field_names == other.field_names &&
+      self.target_type == other.target_type &&
+      self.seed == other.seed &&
+      self.children.len() == other.children.len() &&
+      self.children.iter().zip(other.children.iter()).all(|(a, b)| a.semantic_equals(b))
+  }
+
+  /// Returns hash of this expression tree based on names, literal values, references,
+  /// and children, same properties that are compared in `semantic_equals`.
+  ///
+  /// Floating point literals are hashed after normalization, so `0.0` and `-0.0`, which
+  /// are equal, have the same hash, and all NaN values have the same hash.
+  ///
+  /// Can be used as `RuleExecutor::plan_hash` to speed up fixed point detection.
+  pub fn tree_hash(&self) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    self.internal_tree_hash(&mut hasher);
+    hasher.finish()
+  }
+
+  /// Internal method to feed this expression tree into the hasher.
+  fn internal_tree_hash(&self, hasher: &mut DefaultHasher) {
+    self.name.hash(hasher);
+    self.literal.is_some().hash(hasher);
+    if let Some(ref value) = self.literal {
+      hash_literal(value, hasher);
+    }
+    self.reference.as_ref().map(|value| format!("{:?}", value)).hash(hasher);
+    self.field_name.hash(hasher);
+    self.field_names.hash(hasher);
+    self.target_type.as_ref().map(|value| value.to_string()).hash(hasher);
+    self.seed.hash(hasher);
+    self.children.len().hash(hasher);
+    for child in &self.children {
+      child.internal_tree_hash(hasher);
+    }
+  }
+
+  /// Returns copy of this expression where each literal is replaced with the result of
+  /// applying `func` to its value. Other nodes are left unchanged.
+  pub fn map_literals<F>(
+    &self,
+    func: &mut F
+  ) -> Expression where F: FnMut(&Literal) -> Literal
+  {
+    self.transform_up(&mut |exp| exp.literal().map(|value| lit(func(value))))
+  }
+
+  /// Same as `transform_down`, but returns `None` when `rule` did not change any node in
+  /// the tree, and `Some` with the new tree otherwise.
+  ///
+  /// Result of `rule` that is equal to the original node is not considered a change.
+  pub fn transform_down_opt<F>(
+    &self,
+    rule: &mut F
+  ) -> Option<Expression> where F: FnMut(&Expression) -> Option<Expression>
+  {
+    let mut changed = false;
+    let updated = self.transform_down(&mut |exp| {
+      match rule(exp) {
+        Some(after_rule) => {
+          if !after_rule.semantic_equals(exp) {
+            changed = true;
+          }
+          Some(after_rule)
+        },
+        None => None
+      }
+    });
+    if changed { Some(updated) } else { None }
+  }
+
+  /// Returns copy of this expression where each maximal foldable subtree is replaced
+  /// with a literal of its evaluated value, non-foldable parts are left unchanged.
+  ///
+  /// Only resolved subtrees that define eval function are folded, so unresolved or
+  /// invalid expressions are returned as is instead of failing evaluation.
+  ///
+  /// For example, `(2 * 3) + a` becomes `6 + a`.
+  pub fn partial_eval(&self) -> Expression {
+    self.transform_down(&mut |exp| {
+      if exp.literal().is_none() && exp.eval_func.is_some() && exp.resolved() &&
+          exp.foldable() {
+        Some(lit(exp.eval(&Row::new())))
+      } else {
+        None
+      }
+    })
+  }
+
+  /// Constant folding pass, returns copy of this expression with all constant subtrees
+  /// folded into literals, same as `partial_eval`.
+  pub fn fold_constants(&self) -> Expression {
+    self.partial_eval()
+  }
+
+  /// Same as `fold_constants`, but overflow of integral arithmetic is handled according
+  /// to the mode, e.g. `2147483647 + 1` is folded into null with `OverflowMode::Null`,
+  /// and returns eval error with `OverflowMode::Error`.
+  pub fn fold_constants_with_mode(
+    &self,
+    mode: OverflowMode
+  ) -> Result<Expression, CatalystError>
+  {
+    let mut children = Vec::with_capacity(self.children.len());
+    for child in &self.children {
+      children.push(child.fold_constants_with_mode(mode)?);
+    }
+    let exp = self.with_new_children(children);
+    if exp.literal().is_none() && exp.eval_func.is_some() && exp.resolved() &&
+        exp.foldable() {
+      Ok(lit(exp.try_eval(&Row::new(), mode)?))
+    } else {
+      Ok(exp)
+    }
+  }
+
+  /// Returns `true` if this expression evaluates to null when any of its children is
+  /// null, set with `ExpressionBuilder::null_intolerant`.
+  pub fn null_intolerant(&self) -> bool {
+    self.null_intolerant
+  }
+
+  /// Returns copy of this expression where each comparison is put into canonical
+  /// direction, so `a > 1` and `1 < a` have the same normalized form.
+  ///
+  /// Operands are swapped (and comparison is flipped, e.g. `>` to `<`) to put a column
+  /// reference on the left side, or, when both or none of the operands are references,
+  /// the operand with lexicographically smaller display string.
+  pub fn normalize_comparison(&self) -> Expression {
+    self.transform_up(&mut |exp| {
+      let flipped: fn(Expression, Expression) -> Expression = match exp.name.as_str() {
+        "EQUAL" => eq,
+        "GREATER_THAN" => lt,
+        "GREATER_OR_EQUAL" => le,
+        "LESS_THAN" => gt,
+        "LESS_OR_EQUAL" => ge,
+        _ => return None
+      };
+      let left = &exp.children[0];
+      let right = &exp.children[1];
+      let swap = match (left.reference.is_some(), right.reference.is_some()) {
+        (false, true) => true,
+        (true, false) => false,
+        _ => left.pretty_string() > right.pretty_string()
+      };
+      if swap { Some(flipped(right.clone(), left.clone())) } else { None }
+    })
+  }
+
+  /// Returns `true` if this expression preserves ordering of its input, i.e. it is a
+  /// monotonically increasing function of a column.
+  ///
+  /// This is a conservative check: only columns and their shifts or scaling by a
+  /// positive literal, e.g. `a + 1` or `a * 2`, are considered order preserving.
+  pub fn is_order_preserving(&self) -> bool {
+    if self.reference.is_some() {
+      return true;
+    }
+    match self.name.as_str() {
+      "ADD" => {
+        let (left, right) = (&self.children[0], &self.children[1]);
+        (left.is_order_preserving() && is_non_null_literal(right)) ||
+          (is_non_null_literal(left) && right.is_order_preserving())
+      },
+      "SUB" => {
+        self.children[0].is_order_preserving() && is_non_null_literal(&self.children[1])
+      },
+      "MULTIPLY" => {
+        let (left, right) = (&self.children[0], &self.children[1]);
+        (left.is_order_preserving() && is_positive_literal(right)) ||
+          (is_positive_literal(left) && right.is_order_preserving())
+      },
+      _ => false
+    }
+  }
+
+  /// Splits conjunctive predicate into a list of predicates.
+  ///
+  /// For example, `a && (b && c)` is split into `[a, b, c]`.
+  pub fn split_conjunctive_predicates(&self) -> Vec<Expression> {
+    if self.name == "AND" {
+      self.children.iter()
+        .flat_map(|child| child.split_conjunctive_predicates())
+        .collect()
+    } else {
+      vec![self.clone()]
+    }
+  }
+
+  /// Returns constraints implied by this predicate.
+  ///
+  /// For each conjunct, every column that is used in a null-intolerant expression
+  /// results in `is_not_null` constraint, e.g. `a > 1` implies `a is not null`.
+  pub fn infer_constraints(&self) -> Vec<Expression> {
+    let mut constraints = Vec::new();
+    for predicate in self.split_conjunctive_predicates() {
+      for column in predicate.null_intolerant_references() {
+        let constraint = is_not_null(column);
+        if !constraints.contains(&constraint) {
+          constraints.push(constraint);
+        }
+      }
+    }
+    constraints
+  }
+
+  /// Internal method to collect references that are only reachable through
+  /// null-intolerant expressions.
+  fn null_intolerant_references(&self) -> Vec<Expression> {
+    if self.reference.is_some() {
+      vec![self.clone()]
+    } else if self.null_intolerant() {
+      self.children.iter().flat_map(|child| child.null_intolerant_references()).collect()
+    } else {
+      vec![]
+    }
+  }
+}
+
+/// Internal method to feed literal value into the hasher, floating point values are
+/// hashed as bits after replacing `-0.0` with `0.0` and any NaN with canonical NaN.
+fn hash_literal(value: &Literal, hasher: &mut DefaultHasher) {
+  match value {
+    Literal::Float(v) => {
+      "Float".hash(hasher);
+      v.map(|v| {
+        if v == 0.0 { 0.0f32 } else if v.is_nan() { f32::NAN } else { v }
+      }).map(f32::to_bits).hash(hasher);
+    },
+    Literal::Double(v) => {
+      "Double".hash(hasher);
+      v.map(|v| {
+        if v == 0.0 { 0.0f64 } else if v.is_nan() { f64::NAN } else { v }
+      }).map(f64::to_bits).hash(hasher);
+    },
+    other => format!("{:?}", other).hash(hasher)
+  }
+}
+
+/// Returns `true` if expression is a literal with non-null value.
+fn is_non_null_literal(exp: &Expression) -> bool {
+  exp.literal().map(|value| !value.is_null()).unwrap_or(false)
+}
+
+/// Returns `true` if expression is a literal with positive numeric value.
+fn is_positive_literal(exp: &Expression) -> bool {
+  match exp.literal() {
+    Some(Literal::Byte(Some(v))) => *v > 0,
+    Some(Literal::Short(Some(v))) => *v > 0,
+    Some(Literal::Integer(Some(v))) => *v > 0,
+    Some(Literal::Long(Some(v))) => *v > 0,
+    Some(Literal::Float(Some(v))) => *v > 0.0,
+    Some(Literal::Double(Some(v))) => *v > 0.0,
+    _ => false
+  }
 }
 
 impl TreeNode<Expression> for Expression {
@@ -133,6 +803,7 @@ impl TreeNode<Expression> for Expression {
 
   fn set_child(&mut self, pos: usize, child: Expression) {
     self.children[pos] = child;
+    self.update_struct_type();
   }
 
   fn clone_tree(&self) -> Expression {
@@ -144,18 +815,20 @@ impl TreeNode<Expression> for Expression {
   }
 }
 
-impl Clone for Expression {
-  fn clone(&self) -> Self {
-    (self.clone_func)(self)
-  }
-}
-
 impl PartialEq for Expression {
   fn eq(&self, other: &Expression) -> bool {
     (self.eq_func)(self, other)
   }
 }
 
+/// Hash of the expression is its `tree_hash`, e.g. to use with
+/// `TreeNode::transform_down_memoized`.
+impl Hash for Expression {
+  fn hash<H: Hasher>(&self, state: &mut H) {
+    self.tree_hash().hash(state);
+  }
+}
+
 /// Expression builder.
 /// Used to build templates for other expressions.
 pub struct ExpressionBuilder {
@@ -169,14 +842,27 @@ impl ExpressionBuilder {
       expression: Expression {
         name: name,
         children: vec![],
-        display_func: Box::new(|_| unimplemented!()),
-        foldable_func: Box::new(|_| unimplemented!()),
-        deterministic_func: Box::new(|_| unimplemented!()),
-        nullable_func: Box::new(|_| unimplemented!()),
-        resolved_func: Box::new(|_| unimplemented!()),
-        datatype_func: Box::new(|_| unimplemented!()),
-        clone_func: Box::new(|_| unimplemented!()),
-        eq_func: Box::new(|_, _| unimplemented!())
+        literal: None,
+        reference: None,
+        field_name: None,
+        field_names: vec![],
+        struct_type: None,
+        target_type: None,
+        seed: None,
+        id: None,
+        null_intolerant: false,
+        commutative: false,
+        display_func: Arc::new(|_, _| unimplemented!()),
+        sql_func: None,
+        foldable_func: Arc::new(|_| unimplemented!()),
+        deterministic_func: Arc::new(|_| unimplemented!()),
+        nullable_func: Arc::new(|_| unimplemented!()),
+        resolved_func: Arc::new(|_| unimplemented!()),
+        datatype_func: Arc::new(|_| unimplemented!()),
+        eval_func: None,
+        try_eval_func: None,
+        clone_func: Arc::new(|exp: &Expression| exp.clone()),
+        eq_func: Arc::new(|_, _| unimplemented!())
       }
     }
   }
@@ -187,56 +873,129 @@ impl ExpressionBuilder {
     self
   }
 
-  /// Sets display function.
-  pub fn display(mut self, func: Box<Fn(&Expression) -> String>) -> Self {
-    self.expression.display_func = func;
+  /// Sets literal value.
+  pub fn literal(mut self, value: Literal) -> Self {
+    self.expression.literal = Some(value);
+    self
+  }
+
+  /// Sets column reference.
+  pub fn reference(mut self, value: Reference) -> Self {
+    self.expression.reference = Some(value);
+    self
+  }
+
+  /// Sets field name for struct field extraction.
+  pub fn field_name(mut self, value: String) -> Self {
+    self.expression.field_name = Some(value);
+    self
+  }
+
+  /// Sets field names for struct construction.
+  pub fn field_names(mut self, value: Vec<String>) -> Self {
+    self.expression.field_names = value;
+    self
+  }
+
+  /// Sets target data type for cast.
+  pub fn target_type(mut self, value: DataType) -> Self {
+    self.expression.target_type = Some(value);
+    self
+  }
+
+  /// Sets seed for random expression.
+  pub fn seed(mut self, value: i64) -> Self {
+    self.expression.seed = Some(value);
+    self
+  }
+
+  /// Marks expression as null-intolerant, i.e. it evaluates to null when any of its
+  /// children is null, by default expression is not null-intolerant.
+  pub fn null_intolerant(mut self, value: bool) -> Self {
+    self.expression.null_intolerant = value;
+    self
+  }
+
+  /// Marks binary expression as commutative, i.e. its operands can be swapped without
+  /// changing the result, e.g. `a + b`, by default expression is not commutative.
+  pub fn commutative(mut self, value: bool) -> Self {
+    self.expression.commutative = value;
+    self
+  }
+
+  /// Sets display function, which is called with already rendered pretty strings of the
+  /// children.
+  pub fn display(mut self, func: Box<RenderFunc>) -> Self {
+    self.expression.display_func = Arc::from(func);
+    self
+  }
+
+  /// Sets SQL function, which is called with already rendered SQL strings of the
+  /// children, by default SQL is rendered with display function.
+  pub fn sql(mut self, func: Box<RenderFunc>) -> Self {
+    self.expression.sql_func = Some(Arc::from(func));
     self
   }
 
   /// Sets foldable function.
-  pub fn foldable(mut self, func: Box<Fn(&Expression) -> bool>) -> Self {
-    self.expression.foldable_func = func;
+  pub fn foldable(mut self, func: Box<PropertyFunc>) -> Self {
+    self.expression.foldable_func = Arc::from(func);
     self
   }
 
   /// Sets deterministic function.
-  pub fn deterministic(mut self, func: Box<Fn(&Expression) -> bool>) -> Self {
-    self.expression.deterministic_func = func;
+  pub fn deterministic(mut self, func: Box<PropertyFunc>) -> Self {
+    self.expression.deterministic_func = Arc::from(func);
     self
   }
 
   /// Sets nullable function.
-  pub fn nullable(mut self, func: Box<Fn(&Expression) -> bool>) -> Self {
-    self.expression.nullable_func = func;
+  pub fn nullable(mut self, func: Box<PropertyFunc>) -> Self {
+    self.expression.nullable_func = Arc::from(func);
     self
   }
 
   /// Sets resolved function.
-  pub fn resolved(mut self, func: Box<Fn(&Expression) -> bool>) -> Self {
-    self.expression.resolved_func = func;
+  pub fn resolved(mut self, func: Box<PropertyFunc>) -> Self {
+    self.expression.resolved_func = Arc::from(func);
     self
   }
 
   /// Sets data type function.
-  pub fn datatype(mut self, func: Box<Fn(&Expression) -> &DataType>) -> Self {
-    self.expression.datatype_func = func;
+  pub fn datatype(mut self, func: Box<DataTypeFunc>) -> Self {
+    self.expression.datatype_func = Arc::from(func);
     self
   }
 
-  /// Sets clone function.
-  pub fn clone(mut self, func: Box<Fn(&Expression) -> Expression>) -> Self {
-    self.expression.clone_func = func;
+  /// Sets eval function.
+  pub fn eval(mut self, func: Box<EvalFunc>) -> Self {
+    self.expression.eval_func = Some(Arc::from(func));
+    self
+  }
+
+  /// Sets eval function that handles overflow according to the mode, used by
+  /// `Expression::try_eval`, should be consistent with `eval` for `OverflowMode::Wrap`.
+  pub fn try_eval(mut self, func: Box<TryEvalFunc>) -> Self {
+    self.expression.try_eval_func = Some(Arc::from(func));
+    self
+  }
+
+  /// Sets function to reconstruct the expression with its constructor, see `rebuild`,
+  /// by default expression is copied with all its functions.
+  pub fn clone(mut self, func: Box<CloneFunc>) -> Self {
+    self.expression.clone_func = Arc::from(func);
     self
   }
 
   /// Sets equality function.
-  pub fn eq(mut self, func: Box<Fn(&Expression, &Expression) -> bool>) -> Self {
-    self.expression.eq_func = func;
+  pub fn eq(mut self, func: Box<EqFunc>) -> Self {
+    self.expression.eq_func = Arc::from(func);
     self
   }
 
   /// Returns expression.
-  pub fn build(self) -> Expression {
+  pub fn build(mut self) -> Expression {
+    self.expression.update_struct_type();
     self.expression
   }
 }
@@ -251,12 +1010,8 @@ pub fn binary(
 {
   ExpressionBuilder::new(name)
     .children(vec![left, right])
-    .display(Box::new(move |exp| {
-      format!("({} {} {})",
-        exp.children[0].pretty_string(),
-        symbol,
-        exp.children[1].pretty_string()
-      )
+    .display(Box::new(move |_, children| {
+      format!("({} {} {})", children[0], symbol, children[1])
     }))
     .foldable(Box::new(|exp| {
       exp.children[0].foldable() && exp.children[1].foldable()
@@ -273,9 +1028,6 @@ pub fn binary(
     .datatype(Box::new(|exp| {
       exp.children[0].data_type()
     }))
-    .clone(Box::new(|_| {
-      unimplemented!()
-    }))
     .eq(Box::new(|a, b| {
       a.name == b.name &&
         a.children.len() == b.children.len() &&
@@ -284,12 +1036,12 @@ pub fn binary(
     }))
 }
 
-// Represents unary expression node.
+/// Represents unary expression node.
 pub fn unary(name: String, symbol: String, child: Expression) -> ExpressionBuilder {
   ExpressionBuilder::new(name)
     .children(vec![child])
-    .display(Box::new(move |exp| {
-      format!("({}{})", symbol, exp.children[0].pretty_string())
+    .display(Box::new(move |_, children| {
+      format!("({}{})", symbol, children[0])
     }))
     .foldable(Box::new(|exp| {
       exp.children[0].foldable()
@@ -306,12 +1058,671 @@ pub fn unary(name: String, symbol: String, child: Expression) -> ExpressionBuild
     .datatype(Box::new(|exp| {
       exp.children[0].data_type()
     }))
-    .clone(Box::new(|_| {
-      unimplemented!()
-    }))
     .eq(Box::new(|a, b| {
       a.name == b.name &&
         a.children.len() == b.children.len() &&
         a.children[0].eq(&b.children[0])
     }))
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use expr::arithmetic::{add, mul, sub};
+  use expr::cast::cast;
+  use expr::complex::{create_struct, get_struct_field};
+  use expr::logical::{and, eq, eq_null_safe, ge, gt, in_list, is_not_null, is_null, le,
+    lt, not, or};
+  use expr::random::rand;
+  use expr::reference::{bound_reference, reference};
+
+  fn col(name: &str) -> Expression {
+    bound_reference(name, DataType::IntegerType, true)
+  }
+
+  fn int(value: i32) -> Expression {
+    lit(Literal::Integer(Some(value)))
+  }
+
+  #[test]
+  fn test_clone_all_expressions() {
+    let expressions = vec![
+      int(1),
+      col("a"),
+      reference("b"),
+      add(col("a"), int(1)),
+      sub(col("a"), int(1)),
+      mul(col("a"), int(1)),
+      gt(col("a"), int(1)),
+      ge(col("a"), int(1)),
+      lt(col("a"), int(1)),
+      le(col("a"), int(1)),
+      and(gt(col("a"), int(1)), lt(col("a"), int(2))),
+      or(gt(col("a"), int(1)), lt(col("a"), int(2))),
+      not(gt(col("a"), int(1))),
+      is_null(col("a")),
+      is_not_null(col("a")),
+      get_struct_field(reference("s"), "x")
+    ];
+    for exp in expressions {
+      let cloned = exp.clone();
+      assert!(cloned == exp);
+      assert_eq!(cloned.pretty_string(), exp.pretty_string());
+      // rebuilding children keeps display and clone functions of the parent
+      let updated = exp.map_literals(&mut |_| Literal::Integer(Some(7)));
+      assert!(updated.clone() == updated);
+      assert_eq!(updated.node_name(), exp.node_name());
+    }
+  }
+
+  #[test]
+  fn test_clone_template_expressions() {
+    let exp = binary("TEST".to_owned(), "?".to_owned(), int(1), col("a")).build();
+    let cloned = exp.clone();
+    assert!(cloned == exp);
+    assert_eq!(cloned.pretty_string(), "(1 ? a)");
+
+    let updated = exp.transform_up(&mut |e| {
+      if e.literal().is_some() { Some(int(2)) } else { None }
+    });
+    assert_eq!(updated.pretty_string(), "(2 ? a)");
+    assert!(updated.clone() == updated);
+
+    let exp = unary("TEST".to_owned(), "~".to_owned(), col("a")).build();
+    assert!(exp.clone() == exp);
+    assert_eq!(exp.clone().pretty_string(), "(~a)");
+    let updated = exp.transform_up(&mut |e| {
+      if e.reference().is_some() { Some(col("b")) } else { None }
+    });
+    assert_eq!(updated.clone().pretty_string(), "(~b)");
+  }
+
+  #[test]
+  fn test_clone_nested_binary_expressions() {
+    let exp = add(add(int(1), int(2)), mul(int(3), sub(int(5), int(4))));
+    let cloned = exp.clone();
+    assert!(cloned == exp);
+    assert!(cloned.semantic_equals(&exp));
+    assert_eq!(cloned.pretty_string(), "((1 + 2) + (3 * (5 - 4)))");
+    assert_eq!(cloned.eval(&Row::new()), Literal::Integer(Some(6)));
+    assert_eq!(cloned.children()[0].clone().eval(&Row::new()), Literal::Integer(Some(3)));
+
+    let exp = unary("TEST".to_owned(), "-".to_owned(), add(int(1), int(2))).build();
+    let cloned = exp.clone();
+    assert!(cloned == exp);
+    assert_eq!(cloned.pretty_string(), "(-(1 + 2))");
+    assert_eq!(cloned.children()[0].eval(&Row::new()), Literal::Integer(Some(3)));
+  }
+
+  #[test]
+  fn test_clone_keeps_overridden_functions() {
+    let exp = binary("TEST".to_owned(), "?".to_owned(), int(1), int(2))
+      .display(Box::new(|_, children| format!("test({}, {})", children[0], children[1])))
+      .datatype(Box::new(|_| &DataType::LongType))
+      .eval(Box::new(|_, _| Literal::Long(Some(3))))
+      .build();
+    let cloned = exp.clone();
+    assert_eq!(cloned.pretty_string(), "test(1, 2)");
+    assert_eq!(cloned.data_type(), &DataType::LongType);
+    assert_eq!(cloned.eval(&Row::new()), Literal::Long(Some(3)));
+
+    let updated = exp.map_literals(&mut |_| Literal::Integer(Some(7)));
+    assert_eq!(updated.clone().pretty_string(), "test(7, 7)");
+    assert_eq!(exp.rebuild(vec![int(5), int(6)]).pretty_string(), "test(5, 6)");
+  }
+
+  #[test]
+  fn test_pretty_string_iterative() {
+    let expressions = vec![
+      int(1),
+      col("a"),
+      reference("b"),
+      lit(Literal::string("x")),
+      add(col("a"), mul(int(2), sub(col("b"), int(1)))),
+      and(gt(col("a"), int(1)), or(not(le(col("a"), int(2))), is_null(col("c")))),
+      is_not_null(eq(col("a"), int(3))),
+      in_list(col("a"), vec![int(1), int(2)]),
+      cast(col("a"), DataType::LongType),
+      get_struct_field(reference("s"), "x"),
+      create_struct(vec![("x".to_owned(), int(1)), ("y".to_owned(), col("a"))]),
+      unary("TEST".to_owned(), "~".to_owned(), col("a")).build()
+    ];
+    for exp in expressions {
+      assert_eq!(exp.pretty_string_iterative(), exp.pretty_string());
+    }
+  }
+
+  #[test]
+  fn test_pretty_string_iterative_deep() {
+    let mut exp = is_null(col("a"));
+    for _ in 0..5000 {
+      exp = and(exp, is_null(col("a")));
+    }
+    let res = exp.pretty_string_iterative();
+    assert!(res.starts_with(&format!("{}a is null) && (a is null))", "(".repeat(5001))));
+    assert!(res.ends_with(" && (a is null))"));
+    assert_eq!(res.matches("&&").count(), 5000);
+  }
+
+  #[test]
+  fn test_to_sql() {
+    let exp = and(gt(col("a"), int(1)),
+      or(not(is_null(col("b"))), is_not_null(col("c"))));
+    assert_eq!(exp.to_sql(),
+      "((`a` > 1) AND ((NOT (`b` IS NULL)) OR (`c` IS NOT NULL)))");
+    let exp = in_list(add(col("a"), int(1)), vec![int(1), lit(Literal::string("x'y"))]);
+    assert_eq!(exp.to_sql(), "((`a` + 1) IN (1, 'x''y'))");
+    assert_eq!(cast(reference("a"), DataType::LongType).to_sql(), "CAST(`a` AS BIGINT)");
+    let tpe = DataType::struct_type(vec![])
+      .add_field("abc", DataType::IntegerType)
+      .add_field("Xy", DataType::array_type(DataType::VarcharType(10), true));
+    assert_eq!(cast(int(1), tpe).to_sql(),
+      "CAST(1 AS STRUCT<`abc`:INT,`Xy`:ARRAY<VARCHAR(10)>>)");
+    let tpe = DataType::map_type(DataType::StringType, DataType::DecimalType(10, 2),
+      true);
+    assert_eq!(cast(reference("m"), tpe).to_sql(),
+      "CAST(`m` AS MAP<STRING,DECIMAL(10,2)>)");
+    let exp = create_struct(vec![("x".to_owned(), int(1)), ("y".to_owned(), col("a"))]);
+    assert_eq!(exp.to_sql(), "named_struct('x', 1, 'y', `a`)");
+    assert_eq!(get_struct_field(reference("s"), "x").to_sql(), "`s`.`x`");
+  }
+
+  #[test]
+  fn test_to_sql_quoted_identifiers() {
+    assert_eq!(reference("a b").to_sql(), "`a b`");
+    assert_eq!(gt(col("select"), int(1)).to_sql(), "(`select` > 1)");
+    assert_eq!(reference("a`b").to_sql(), "`a``b`");
+    assert_eq!(get_struct_field(reference("s t"), "from").to_sql(), "`s t`.`from`");
+    let tpe = DataType::struct_type(vec![]).add_field("a b", DataType::IntegerType);
+    assert_eq!(cast(reference("c"), tpe).to_sql(), "CAST(`c` AS STRUCT<`a b`:INT>)");
+  }
+
+  #[test]
+  fn test_builder_sql_and_commutative() {
+    // SQL defaults to the pretty string, operands are not swapped by default
+    let exp = binary("TEST".to_owned(), "?".to_owned(), col("b"), col("a")).build();
+    assert!(!exp.commutative());
+    assert_eq!(exp.to_sql(), "(`b` ? `a`)");
+    assert_eq!(exp.canonicalize().to_sql(), "(`b` ? `a`)");
+
+    let exp = binary("TEST".to_owned(), "?".to_owned(), col("b"), col("a"))
+      .commutative(true)
+      .sql(Box::new(|_, children| format!("test({}, {})", children[0], children[1])))
+      .build();
+    assert!(exp.commutative());
+    assert_eq!(exp.pretty_string(), "(b ? a)");
+    assert_eq!(exp.to_sql(), "test(`b`, `a`)");
+    assert_eq!(exp.canonicalize().to_sql(), "test(`a`, `b`)");
+
+    assert!(add(col("a"), col("b")).commutative());
+    assert!(!sub(col("a"), col("b")).commutative());
+  }
+
+  #[test]
+  fn test_canonicalize() {
+    assert_eq!(add(int(2), int(1)).canonicalize().to_sql(), "(1 + 2)");
+    assert_eq!(sub(int(2), int(1)).canonicalize().to_sql(), "(2 - 1)");
+    assert_eq!(gt(int(1), col("a")).canonicalize().to_sql(), "(`a` < 1)");
+    let exp = and(eq(col("b"), col("a")), mul(col("c"), int(2)));
+    assert_eq!(exp.canonicalize().to_sql(), "((2 * `c`) AND (`a` = `b`))");
+
+    let exp = or(and(eq(col("d"), add(col("c"), col("b"))), gt(int(3), col("a"))),
+      and(is_null(col("b")), eq(mul(int(2), col("a")), add(int(2), int(1)))));
+    let canonical = exp.canonicalize();
+    assert_eq!(canonical.to_sql(), "((((1 + 2) = (2 * `a`)) AND (`b` IS NULL)) OR \
+      (((`b` + `c`) = `d`) AND (`a` < 3)))");
+    assert!(canonical.canonicalize() == canonical);
+  }
+
+  #[test]
+  fn test_sql_hash() {
+    assert_eq!(add(int(1), int(2)).sql_hash(), add(int(2), int(1)).sql_hash());
+    assert_eq!(add(int(1), int(2)).sql_hash(), add(int(1), int(2)).clone().sql_hash());
+    assert!(sub(int(1), int(2)).sql_hash() != sub(int(2), int(1)).sql_hash());
+    assert!(add(int(1), int(2)).sql_hash() != sub(int(1), int(2)).sql_hash());
+    assert_eq!(gt(col("a"), int(1)).sql_hash(), lt(int(1), col("a")).sql_hash());
+  }
+
+  #[test]
+  fn test_with_new_children() {
+    let exp = add(col("a"), int(1));
+    let updated = exp.with_new_children(vec![col("b"), int(2)]);
+    assert!(updated == add(col("b"), int(2)));
+    assert!(exp == add(col("a"), int(1)));
+    assert!(int(1).with_new_children(vec![]) == int(1));
+  }
+
+  #[test]
+  #[should_panic(expected = "Expression ADD expects 2 children, found 1")]
+  fn test_with_new_children_wrong_arity() {
+    add(col("a"), int(1)).with_new_children(vec![col("b")]);
+  }
+
+  #[test]
+  fn test_references() {
+    let exp = add(col("a"), mul(int(1), col("b")));
+    let names: Vec<String> =
+      exp.references().iter().map(|r| r.name().to_owned()).collect();
+
+    assert_eq!(names, vec!["a", "b"]);
+    assert!(int(1).references().is_empty());
+  }
+
+  #[test]
+  fn test_unresolved_references() {
+    assert_eq!(add(reference("x"), reference("y")).unresolved_references(),
+      vec!["x", "y"]);
+    assert_eq!(add(col("x"), mul(reference("y"), int(1))).unresolved_references(),
+      vec!["y"]);
+    assert!(add(col("x"), int(1)).unresolved_references().is_empty());
+  }
+
+  #[test]
+  fn test_can_push_through() {
+    let exp = gt(col("a"), int(1));
+    assert!(exp.can_push_through(&DataType::struct_type(vec![])
+      .add_field("a", DataType::IntegerType)));
+    assert!(!exp.can_push_through(&DataType::struct_type(vec![])
+      .add_field("b", DataType::IntegerType)));
+
+    let exp = and(gt(col("a"), int(1)), lt(col("b"), int(2)));
+    assert!(exp.can_push_through(&DataType::struct_type(vec![])
+      .add_field("b", DataType::IntegerType)
+      .add_field("a", DataType::IntegerType)));
+    assert!(!exp.can_push_through(&DataType::struct_type(vec![])
+      .add_field("a", DataType::IntegerType)));
+
+    let exp = gt(int(2), int(1));
+    assert!(exp.can_push_through(&DataType::struct_type(vec![])));
+    assert!(exp.can_push_through(&DataType::struct_type(vec![])
+      .add_field("b", DataType::IntegerType)));
+  }
+
+  #[test]
+  fn test_reference_bindings() {
+    let schema = DataType::struct_type(vec![])
+      .add_field("a", DataType::IntegerType)
+      .add_field("b", DataType::IntegerType);
+    let exp = add(reference("b"), reference("z"));
+    assert_eq!(exp.reference_bindings(&schema),
+      vec![("b".to_owned(), Some(1)), ("z".to_owned(), None)]);
+    let exp = gt(mul(col("a"), reference("b")), col("a"));
+    assert_eq!(exp.reference_bindings(&schema),
+      vec![
+        ("a".to_owned(), Some(0)),
+        ("b".to_owned(), Some(1)),
+        ("a".to_owned(), Some(0))
+      ]);
+    assert!(int(1).reference_bindings(&schema).is_empty());
+  }
+
+  #[test]
+  fn test_reference_bindings_not_struct() {
+    let exp = add(reference("a"), reference("b"));
+    assert_eq!(exp.reference_bindings(&DataType::IntegerType),
+      vec![("a".to_owned(), None), ("b".to_owned(), None)]);
+    assert!(int(1).reference_bindings(&DataType::IntegerType).is_empty());
+  }
+
+  #[test]
+  fn test_rebuild() {
+    let exp = add(col("a"), int(1));
+    assert_eq!(exp.data_type(), &DataType::IntegerType);
+    assert!(exp.nullable());
+
+    let long = lit(Literal::Long(Some(2)));
+    let res = exp.rebuild(vec![int(1), long.clone()]);
+    assert_eq!(res.pretty_string(), "(1 + 2)");
+    assert_eq!(res.data_type(), &DataType::LongType);
+    assert!(!res.nullable());
+    assert!(res.resolved());
+    assert!(res == add(int(1), long));
+
+    let res = exp.rebuild(vec![col("a"), lit(Literal::string("b"))]);
+    assert!(!res.resolved());
+
+    let exp = create_struct(vec![("x".to_owned(), int(1))]);
+    let res = exp.rebuild(vec![lit(Literal::Long(Some(1)))]);
+    assert_eq!(res.data_type().to_string(), "struct<x:long>");
+
+    let exp = add(int(1), int(2)).assign_ids();
+    assert_eq!(exp.rebuild(vec![int(3), int(4)]).id(), Some(0));
+  }
+
+  #[test]
+  #[should_panic(expected = "Expression ADD expects 2 children, found 1")]
+  fn test_rebuild_wrong_number_of_children() {
+    add(int(1), int(2)).rebuild(vec![int(1)]);
+  }
+
+  #[test]
+  fn test_to_json_with_attributes() {
+    let exp = add(col("a"), int(1));
+    let res = exp.to_json_with(&mut |node| vec![
+      ("dataType".to_owned(), node.data_type().to_string()),
+      ("nullable".to_owned(), node.nullable().to_string())
+    ]);
+    assert!(res.contains("\"name\": \"ADD\""));
+    assert!(res.contains("\"name\": \"LITERAL\""));
+    assert_eq!(res.matches("\"dataType\": \"int\"").count(), 3);
+    assert_eq!(res.matches("\"nullable\": \"true\"").count(), 2);
+    assert_eq!(res.matches("\"nullable\": \"false\"").count(), 1);
+  }
+
+  #[test]
+  fn test_assign_ids() {
+    let exp = add(int(1), int(2));
+    assert_eq!(exp.id(), None);
+    assert_eq!(exp.pretty_string_with_ids(), "(1 + 2)");
+
+    let exp = exp.assign_ids();
+    assert_eq!(exp.id(), Some(0));
+    assert_eq!(exp.children()[0].id(), Some(1));
+    assert_eq!(exp.children()[1].id(), Some(2));
+    assert_eq!(exp.pretty_string_with_ids(), "#0(#1(1) + #2(2))");
+    assert_eq!(exp.pretty_string(), "(1 + 2)");
+    assert_eq!(exp.clone().pretty_string_with_ids(), "#0(#1(1) + #2(2))");
+    assert!(exp == add(int(1), int(2)));
+
+    let exp = and(is_null(col("a")), not(gt(col("b"), int(1)))).assign_ids();
+    assert_eq!(exp.pretty_string_with_ids(),
+      "#0(#1(#2(a) is null) && #3(!#4(#5(b) > #6(1))))");
+  }
+
+  #[test]
+  fn test_transform_up_stable_keeps_equal_nodes() {
+    // rule returns new literal node that is equal to the input, but without id
+    let exp = add(int(1), mul(int(2), int(3))).assign_ids();
+    let mut rule = |e: &Expression| e.literal().map(|value| lit(value.clone()));
+
+    let updated = exp.transform_up_stable(&mut rule);
+    assert!(updated == exp);
+    assert_eq!(updated.pretty_string_with_ids(), "#0(#1(1) + #2(#3(2) * #4(3)))");
+
+    let updated = exp.transform_up(&mut rule);
+    assert!(updated == exp);
+    assert_eq!(updated.pretty_string_with_ids(), "#0(1 + #2(2 * 3))");
+  }
+
+  #[test]
+  fn test_is_cse_eligible() {
+    assert!(mul(col("a"), col("b")).is_cse_eligible());
+    assert!(gt(add(col("a"), int(1)), int(2)).is_cse_eligible());
+    // non-deterministic expressions
+    assert!(!rand(0).is_cse_eligible());
+    assert!(!add(rand(0), col("a")).is_cse_eligible());
+    // leaf expressions are too cheap to cache
+    assert!(!int(1).is_cse_eligible());
+    assert!(!col("a").is_cse_eligible());
+  }
+
+  #[test]
+  fn test_semantic_equals() {
+    let exp = add(int(1), col("a"));
+    assert!(exp.semantic_equals(&exp));
+    assert!(exp.semantic_equals(&exp.clone()));
+
+    // expression built directly through builder
+    let built = binary("ADD".to_owned(), "+".to_owned(), int(1), col("a")).build();
+    assert!(exp.semantic_equals(&built));
+    assert!(built.semantic_equals(&exp));
+
+    let built = ExpressionBuilder::new("LITERAL".to_owned())
+      .literal(Literal::Integer(Some(1)))
+      .build();
+    assert!(int(1).semantic_equals(&built));
+    assert!(!int(2).semantic_equals(&built));
+
+    assert!(!exp.semantic_equals(&add(int(2), col("a"))));
+    assert!(!exp.semantic_equals(&add(int(1), col("b"))));
+    assert!(!exp.semantic_equals(&sub(int(1), col("a"))));
+    assert!(!exp.semantic_equals(&add(col("a"), int(1))));
+    assert!(!exp.semantic_equals(&not(int(1))));
+  }
+
+  #[test]
+  fn test_tree_hash() {
+    let exp = add(int(1), mul(int(2), col("a")));
+    assert_eq!(exp.tree_hash(), exp.clone().tree_hash());
+    assert_eq!(exp.tree_hash(), add(int(1), mul(int(2), col("a"))).tree_hash());
+    assert!(exp.tree_hash() != add(int(1), mul(int(3), col("a"))).tree_hash());
+    assert!(exp.tree_hash() != add(int(1), mul(int(2), col("b"))).tree_hash());
+    assert!(exp.tree_hash() != sub(int(1), mul(int(2), col("a"))).tree_hash());
+  }
+
+  #[test]
+  fn test_transform_down_memoized() {
+    let exp = add(mul(col("a"), int(2)), mul(col("a"), int(2)));
+    let mut invocations = 0;
+    let res = exp.transform_down_memoized(&mut |node| {
+      invocations += 1;
+      if node.literal().is_some() { Some(int(3)) } else { None }
+    });
+    // invoked once for each of the distinct subtrees
+    assert_eq!(invocations, 4);
+    assert!(res == add(mul(col("a"), int(3)), mul(col("a"), int(3))));
+  }
+
+  #[test]
+  fn test_tree_hash_float_literals() {
+    let double = |v| lit(Literal::Double(Some(v)));
+    let float = |v| lit(Literal::Float(Some(v)));
+    assert!(double(0.0) == double(-0.0));
+    assert_eq!(double(0.0).tree_hash(), double(-0.0).tree_hash());
+    assert_eq!(float(0.0).tree_hash(), float(-0.0).tree_hash());
+    let exp = add(col("a"), double(-0.0));
+    assert_eq!(exp.tree_hash(), add(col("a"), double(0.0)).tree_hash());
+    assert_eq!(double(f64::NAN).tree_hash(), double(-f64::NAN).tree_hash());
+    assert_eq!(float(f32::NAN).tree_hash(), float(-f32::NAN).tree_hash());
+    assert!(double(1.0).tree_hash() != double(-1.0).tree_hash());
+    assert!(double(1.0).tree_hash() != float(1.0).tree_hash());
+    assert!(double(0.0).tree_hash() != lit(Literal::Double(None)).tree_hash());
+  }
+
+  #[test]
+  fn test_map_literals() {
+    let exp = add(int(1), mul(int(2), col("a")));
+    let res = exp.map_literals(&mut |value| {
+      match value {
+        Literal::Integer(Some(v)) => Literal::Integer(Some(v * 2)),
+        other => other.clone()
+      }
+    });
+    assert!(res == add(int(2), mul(int(4), col("a"))));
+    assert_eq!(res.pretty_string(), "(2 + (4 * a))");
+    // should not modify original expression
+    assert!(exp == add(int(1), mul(int(2), col("a"))));
+  }
+
+  #[test]
+  fn test_count_by_name() {
+    let counts = add(add(int(1), int(2)), int(3)).count_by_name();
+    assert_eq!(counts.len(), 2);
+    assert_eq!(counts["ADD"], 2);
+    assert_eq!(counts["LITERAL"], 3);
+  }
+
+  #[test]
+  fn test_transform_down_opt() {
+    let exp = add(int(1), mul(int(2), col("a")));
+    assert!(exp.transform_down_opt(&mut |_| None).is_none());
+    // rule that returns the same node is not a change
+    assert!(exp.transform_down_opt(&mut |e| Some(e.clone())).is_none());
+
+    let res = exp.transform_down_opt(&mut |e| {
+      if e.literal() == Some(&Literal::Integer(Some(2))) { Some(int(3)) } else { None }
+    });
+    assert!(res.unwrap() == add(int(1), mul(int(3), col("a"))));
+  }
+
+  #[test]
+  fn test_partial_eval() {
+    let exp = add(mul(int(2), int(3)), col("a"));
+    assert!(exp.partial_eval() == add(int(6), col("a")));
+
+    let exp = sub(add(int(1), int(2)), mul(col("a"), add(int(3), int(4))));
+    assert!(exp.partial_eval() == sub(int(3), mul(col("a"), int(7))));
+    assert!(exp.partial_eval().partial_eval() == exp.partial_eval());
+
+    let exp = and(gt(int(2), int(1)), lt(col("a"), int(1)));
+    let expected = and(lit(Literal::Boolean(Some(true))), lt(col("a"), int(1)));
+    assert!(exp.partial_eval() == expected);
+
+    // fully foldable expression is replaced with literal
+    let exp = add(int(1), mul(int(2), int(3)));
+    assert!(exp.partial_eval() == int(7));
+
+    // non-foldable expression is unchanged
+    let exp = add(col("a"), col("b"));
+    assert!(exp.partial_eval() == exp);
+  }
+
+  #[test]
+  fn test_fold_constants() {
+    assert!(add(int(1), int(2)).fold_constants() == int(3));
+    assert!(add(col("a"), int(2)).fold_constants() == add(col("a"), int(2)));
+
+    let exp = mul(add(int(1), int(2)), sub(int(10), mul(int(2), int(3))));
+    assert!(exp.fold_constants() == int(12));
+
+    let exp = add(mul(add(int(1), int(2)), col("a")), sub(int(5), int(1)));
+    assert!(exp.fold_constants() == add(mul(int(3), col("a")), int(4)));
+    assert!(exp.fold_constants() == exp.partial_eval());
+
+    // non-foldable trees are untouched
+    let exp = mul(add(col("a"), col("b")), sub(col("c"), int(1)));
+    assert!(exp.fold_constants() == exp);
+    let exp = add(rand(0), int(1));
+    assert!(exp.fold_constants() == exp);
+  }
+
+  #[test]
+  fn test_fold_constants_unresolved_and_complex() {
+    let boolean = lit(Literal::Boolean(Some(true)));
+    let expressions = vec![
+      add(int(1), boolean.clone()),
+      and(int(1), boolean.clone()),
+      not(int(1)),
+      cast(lit(Literal::string("a")), DataType::IntegerType),
+      create_struct(vec![("a".to_owned(), int(1))]),
+      get_struct_field(create_struct(vec![("a".to_owned(), int(1))]), "a"),
+      binary("TEST".to_owned(), "?".to_owned(), int(1), int(2)).build()
+    ];
+    for exp in expressions {
+      assert!(exp.fold_constants() == exp);
+    }
+
+    // foldable children of unresolved expressions are still folded
+    let exp = add(add(int(1), int(2)), boolean.clone());
+    assert!(exp.fold_constants() == add(int(3), boolean));
+  }
+
+  #[test]
+  fn test_fold_constants_with_mode() {
+    let exp = add(col("a"), mul(int(2), int(3)));
+    for mode in [OverflowMode::Wrap, OverflowMode::Null, OverflowMode::Error] {
+      assert!(exp.fold_constants_with_mode(mode).unwrap() == exp.fold_constants());
+    }
+
+    let exp = add(col("a"), add(int(i32::MAX), int(1)));
+    let res = exp.fold_constants_with_mode(OverflowMode::Wrap).unwrap();
+    assert!(res == add(col("a"), int(i32::MIN)));
+    assert!(res == exp.fold_constants());
+    let res = exp.fold_constants_with_mode(OverflowMode::Null).unwrap();
+    assert!(res == add(col("a"), lit(Literal::Integer(None))));
+    match exp.fold_constants_with_mode(OverflowMode::Error) {
+      Err(CatalystError::Eval(msg)) => {
+        assert_eq!(msg, "Overflow when trying to add 2147483647 and 1")
+      },
+      _ => panic!("Expected eval error")
+    }
+
+    // overflow is handled before parent expression without overflow handling is folded
+    let exp = cast(add(int(i32::MAX), int(1)), DataType::LongType);
+    let res = exp.fold_constants_with_mode(OverflowMode::Null).unwrap();
+    assert!(res == lit(Literal::Long(None)));
+  }
+
+  #[test]
+  #[should_panic(expected = "Eval function is not defined for expression TEST")]
+  fn test_eval_not_defined() {
+    binary("TEST".to_owned(), "?".to_owned(), int(1), int(2)).build().eval(&Row::new());
+  }
+
+  #[test]
+  fn test_normalize_comparison() {
+    let exp = gt(col("a"), int(1)).normalize_comparison();
+    assert!(exp.semantic_equals(&lt(int(1), col("a")).normalize_comparison()));
+    assert_eq!(exp.pretty_string(), "(a > 1)");
+    assert_eq!(le(int(1), col("a")).normalize_comparison().pretty_string(), "(a >= 1)");
+    assert_eq!(lt(col("b"), col("a")).normalize_comparison().pretty_string(), "(a > b)");
+    assert_eq!(ge(col("a"), col("b")).normalize_comparison().pretty_string(), "(a >= b)");
+    assert!(!exp.semantic_equals(&ge(col("a"), int(1)).normalize_comparison()));
+
+    let exp = and(gt(int(1), col("a")), not(lt(int(2), col("b"))));
+    assert_eq!(exp.normalize_comparison().pretty_string(), "((a < 1) && (!(b > 2)))");
+  }
+
+  #[test]
+  fn test_is_order_preserving() {
+    assert!(col("a").is_order_preserving());
+    assert!(add(col("a"), int(1)).is_order_preserving());
+    assert!(add(int(-1), col("a")).is_order_preserving());
+    assert!(sub(col("a"), int(1)).is_order_preserving());
+    assert!(mul(col("a"), int(2)).is_order_preserving());
+    assert!(add(mul(col("a"), int(2)), int(3)).is_order_preserving());
+
+    assert!(!int(1).is_order_preserving());
+    assert!(!mul(col("a"), int(-1)).is_order_preserving());
+    assert!(!mul(col("a"), int(0)).is_order_preserving());
+    assert!(!sub(int(1), col("a")).is_order_preserving());
+    assert!(!add(col("a"), col("b")).is_order_preserving());
+    assert!(!add(col("a"), lit(Literal::Integer(None))).is_order_preserving());
+    assert!(!gt(col("a"), int(1)).is_order_preserving());
+  }
+
+  #[test]
+  fn test_split_conjunctive_predicates() {
+    let exp = and(gt(col("a"), col("b")), and(col("c"), col("d")));
+    assert!(exp.split_conjunctive_predicates() ==
+      vec![gt(col("a"), col("b")), col("c"), col("d")]);
+
+    let exp = gt(col("a"), col("b"));
+    assert!(exp.split_conjunctive_predicates() == vec![gt(col("a"), col("b"))]);
+  }
+
+  #[test]
+  fn test_infer_constraints() {
+    let exp = gt(col("a"), int(1));
+    assert!(exp.infer_constraints() == vec![is_not_null(col("a"))]);
+
+    let exp = is_null(col("a"));
+    assert!(exp.infer_constraints().is_empty());
+
+    let exp = and(gt(col("a"), col("b")), and(lt(col("a"), col("c")), is_null(col("d"))));
+    assert!(exp.infer_constraints() ==
+      vec![is_not_null(col("a")), is_not_null(col("b")), is_not_null(col("c"))]);
+  }
+
+  #[test]
+  fn test_null_intolerant() {
+    assert!(add(col("a"), int(1)).null_intolerant());
+    assert!(gt(col("a"), int(1)).null_intolerant());
+    assert!(not(col("a")).null_intolerant());
+    assert!(!and(col("a"), col("b")).null_intolerant());
+    assert!(!is_null(col("a")).null_intolerant());
+    assert!(!eq_null_safe(col("a"), int(1)).null_intolerant());
+    assert!(eq_null_safe(col("a"), int(1)).infer_constraints().is_empty());
+
+    let exp = binary("TEST".to_owned(), "?".to_owned(), col("a"), col("b"));
+    assert!(!exp.build().null_intolerant());
+    let exp = binary("TEST".to_owned(), "?".to_owned(), col("a"), col("b"))
+      .null_intolerant(true)
+      .build();
+    assert!(exp.null_intolerant());
+    assert!(exp.clone().null_intolerant());
+    assert!(
+      exp.infer_constraints() == vec![is_not_null(col("a")), is_not_null(col("b"))]
+    );
+  }
+}
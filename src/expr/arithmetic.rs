@@ -14,11 +14,85 @@
 
 //! Arithmetic expressions.
 
-use expr::api::{Expression, binary};
+use errors::CatalystError;
+use expr::api::{Expression, ExpressionBuilder, Row, binary, unary};
+use expr::literal::{Literal, OverflowMode};
+use types::widen_numeric;
+
+pub use expr::math::abs;
+
+/// Internal method to create binary arithmetic expression, result type is the wider
+/// numeric type of the children, e.g. `int + double` is `double`. Expression is not
+/// resolved if either child is not numeric.
+fn arithmetic(
+  name: &str,
+  symbol: &str,
+  left: Expression,
+  right: Expression
+) -> ExpressionBuilder
+{
+  binary(name.to_owned(), symbol.to_owned(), left, right)
+    .null_intolerant(true)
+    .resolved(Box::new(|exp| {
+      let (left, right) = (&exp.children()[0], &exp.children()[1]);
+      left.resolved() && right.resolved() &&
+        widen_numeric(left.data_type(), right.data_type()).is_some()
+    }))
+    .datatype(Box::new(|exp| {
+      let (left, right) = (&exp.children()[0], &exp.children()[1]);
+      let wider = if right.resolved() {
+        widen_numeric(left.data_type(), right.data_type())
+      } else {
+        None
+      };
+      if wider.as_ref() == Some(right.data_type()) {
+        right.data_type()
+      } else {
+        left.data_type()
+      }
+    }))
+}
+
+/// Internal method to evaluate children and cast both values to the result type.
+fn eval_operands(exp: &Expression, row: &Row) -> (Literal, Literal) {
+  let data_type = exp.data_type();
+  let left = exp.children()[0].eval(row);
+  let right = exp.children()[1].eval(row);
+  (
+    left.cast(data_type).unwrap_or(left),
+    right.cast(data_type).unwrap_or(right)
+  )
+}
+
+/// Internal method to evaluate children with overflow mode and cast both values to the
+/// result type.
+fn try_eval_operands(
+  exp: &Expression,
+  row: &Row,
+  mode: OverflowMode
+) -> Result<(Literal, Literal), CatalystError>
+{
+  let data_type = exp.data_type();
+  let left = exp.children()[0].try_eval(row, mode)?;
+  let right = exp.children()[1].try_eval(row, mode)?;
+  Ok((
+    left.cast(data_type).unwrap_or(left),
+    right.cast(data_type).unwrap_or(right)
+  ))
+}
 
 /// Adds left and right expressions.
 pub fn add(left: Expression, right: Expression) -> Expression {
-  binary("ADD".to_owned(), "+".to_owned(), left, right)
+  arithmetic("ADD", "+", left, right)
+    .commutative(true)
+    .eval(Box::new(|exp, row| {
+      let (left, right) = eval_operands(exp, row);
+      left.add(&right)
+    }))
+    .try_eval(Box::new(|exp, row, mode| {
+      let (left, right) = try_eval_operands(exp, row, mode)?;
+      left.try_add(&right, mode)
+    }))
     .clone(Box::new(|exp| {
       add(exp.children()[0].clone(), exp.children()[1].clone())
     }))
@@ -27,9 +101,258 @@ pub fn add(left: Expression, right: Expression) -> Expression {
 
 /// Subtracts right expression from left expression.
 pub fn sub(left: Expression, right: Expression) -> Expression {
-  binary("SUB".to_owned(), "-".to_owned(), left, right)
+  arithmetic("SUB", "-", left, right)
+    .eval(Box::new(|exp, row| {
+      let (left, right) = eval_operands(exp, row);
+      left.sub(&right)
+    }))
+    .try_eval(Box::new(|exp, row, mode| {
+      let (left, right) = try_eval_operands(exp, row, mode)?;
+      left.try_sub(&right, mode)
+    }))
     .clone(Box::new(|exp| {
       sub(exp.children()[0].clone(), exp.children()[1].clone())
     }))
     .build()
 }
+
+/// Multiplies left and right expressions.
+pub fn mul(left: Expression, right: Expression) -> Expression {
+  arithmetic("MULTIPLY", "*", left, right)
+    .commutative(true)
+    .eval(Box::new(|exp, row| {
+      let (left, right) = eval_operands(exp, row);
+      left.mul(&right)
+    }))
+    .try_eval(Box::new(|exp, row, mode| {
+      let (left, right) = try_eval_operands(exp, row, mode)?;
+      left.try_mul(&right, mode)
+    }))
+    .clone(Box::new(|exp| {
+      mul(exp.children()[0].clone(), exp.children()[1].clone())
+    }))
+    .build()
+}
+
+/// Divides left expression by right expression, returns null on division by zero.
+pub fn div(left: Expression, right: Expression) -> Expression {
+  arithmetic("DIVIDE", "/", left, right)
+    .nullable(Box::new(|_| true))
+    .eval(Box::new(|exp, row| {
+      let (left, right) = eval_operands(exp, row);
+      left.div(&right)
+    }))
+    .clone(Box::new(|exp| {
+      div(exp.children()[0].clone(), exp.children()[1].clone())
+    }))
+    .build()
+}
+
+/// Remainder of division of left expression by right expression, returns null on
+/// division by zero.
+pub fn rem(left: Expression, right: Expression) -> Expression {
+  arithmetic("REMAINDER", "%", left, right)
+    .nullable(Box::new(|_| true))
+    .eval(Box::new(|exp, row| {
+      let (left, right) = eval_operands(exp, row);
+      left.rem(&right)
+    }))
+    .clone(Box::new(|exp| {
+      rem(exp.children()[0].clone(), exp.children()[1].clone())
+    }))
+    .build()
+}
+
+/// Negates numeric child expression, e.g. `(-a)`.
+pub fn neg(child: Expression) -> Expression {
+  unary("UNARY_MINUS".to_owned(), "-".to_owned(), child)
+    .null_intolerant(true)
+    .resolved(Box::new(|exp| {
+      let child = &exp.children()[0];
+      child.resolved() && child.data_type().is_numeric()
+    }))
+    .eval(Box::new(|exp, row| {
+      exp.children()[0].eval(row).neg()
+    }))
+    .clone(Box::new(|exp| {
+      neg(exp.children()[0].clone())
+    }))
+    .build()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use expr::api::Row;
+  use expr::literal::{Literal, lit};
+  use expr::logical::gt;
+  use expr::reference::{bound_reference, reference};
+  use trees::TreeNode;
+  use types::DataType;
+
+  fn int(value: Option<i32>) -> Expression {
+    lit(Literal::Integer(value))
+  }
+
+  #[test]
+  fn test_arithmetic_eval() {
+    let exp = add(int(Some(1)), int(Some(2)));
+    assert_eq!(exp.pretty_string(), "(1 + 2)");
+    assert_eq!(exp.eval(&Row::new()), Literal::Integer(Some(3)));
+    let exp = sub(int(Some(1)), int(Some(2)));
+    assert_eq!(exp.eval(&Row::new()), Literal::Integer(Some(-1)));
+    let exp = mul(int(Some(3)), int(Some(2)));
+    assert_eq!(exp.eval(&Row::new()), Literal::Integer(Some(6)));
+    let exp = add(lit(Literal::double(1.5)), lit(Literal::double(2.0)));
+    assert_eq!(exp.eval(&Row::new()), Literal::double(3.5));
+  }
+
+  #[test]
+  fn test_arithmetic_eval_null() {
+    assert_eq!(add(int(None), int(Some(2))).eval(&Row::new()), Literal::Integer(None));
+    assert_eq!(sub(int(Some(1)), int(None)).eval(&Row::new()), Literal::Integer(None));
+    assert_eq!(mul(int(None), int(None)).eval(&Row::new()), Literal::Integer(None));
+    let exp = gt(add(int(Some(1)), int(None)), int(Some(0)));
+    assert_eq!(exp.eval(&Row::new()), Literal::Boolean(None));
+    let exp = gt(add(int(Some(1)), int(Some(2))), sub(int(Some(5)), int(Some(3))));
+    assert_eq!(exp.eval(&Row::new()), Literal::Boolean(Some(true)));
+  }
+
+  #[test]
+  fn test_arithmetic_try_eval() {
+    let max = int(Some(i32::MAX));
+    let exp = add(max.clone(), int(Some(1)));
+    assert_eq!(exp.try_eval(&Row::new(), OverflowMode::Wrap).unwrap(),
+      exp.eval(&Row::new()));
+    assert_eq!(exp.try_eval(&Row::new(), OverflowMode::Null).unwrap(),
+      Literal::Integer(None));
+    assert!(exp.try_eval(&Row::new(), OverflowMode::Error).is_err());
+
+    // overflow in children is handled with the same mode
+    let exp = sub(mul(max.clone(), int(Some(2))), int(Some(1)));
+    assert_eq!(exp.try_eval(&Row::new(), OverflowMode::Wrap).unwrap(),
+      Literal::Integer(Some(-3)));
+    assert_eq!(exp.try_eval(&Row::new(), OverflowMode::Null).unwrap(),
+      Literal::Integer(None));
+    match exp.try_eval(&Row::new(), OverflowMode::Error) {
+      Err(CatalystError::Eval(msg)) => {
+        assert_eq!(msg, "Overflow when trying to multiply 2147483647 and 2")
+      },
+      _ => panic!("Expected eval error")
+    }
+
+    // expressions without overflow handling fall back to eval
+    let exp = div(max, int(Some(2)));
+    assert_eq!(exp.try_eval(&Row::new(), OverflowMode::Error).unwrap(),
+      Literal::Integer(Some(1073741823)));
+  }
+
+  #[test]
+  fn test_division() {
+    let exp = div(int(Some(7)), int(Some(2)));
+    assert_eq!(exp.pretty_string(), "(7 / 2)");
+    assert_eq!(exp.node_name(), "DIVIDE");
+    assert!(exp.nullable());
+    assert!(exp == exp.clone());
+    assert_eq!(exp.eval(&Row::new()), Literal::Integer(Some(3)));
+    assert_eq!(div(int(Some(7)), int(Some(0))).eval(&Row::new()), Literal::Integer(None));
+
+    let exp = rem(int(Some(7)), int(Some(2)));
+    assert_eq!(exp.pretty_string(), "(7 % 2)");
+    assert_eq!(exp.node_name(), "REMAINDER");
+    assert!(exp.nullable());
+    assert!(exp == exp.clone());
+    assert_eq!(exp.eval(&Row::new()), Literal::Integer(Some(1)));
+    assert_eq!(rem(int(Some(7)), int(Some(0))).eval(&Row::new()), Literal::Integer(None));
+
+    let exp = mul(int(Some(7)), int(Some(2)));
+    assert_eq!(exp.pretty_string(), "(7 * 2)");
+    assert_eq!(exp.node_name(), "MULTIPLY");
+    assert!(!exp.nullable());
+  }
+
+  #[test]
+  fn test_arithmetic_widening() {
+    let exp = add(lit(Literal::Byte(Some(1))), lit(Literal::long(2)));
+    assert!(exp.resolved());
+    assert_eq!(exp.data_type(), &DataType::LongType);
+    assert_eq!(exp.eval(&Row::new()), Literal::long(3));
+
+    let exp = add(int(Some(1)), lit(Literal::double(2.5)));
+    assert!(exp.resolved());
+    assert_eq!(exp.data_type(), &DataType::DoubleType);
+    assert_eq!(exp.eval(&Row::new()), Literal::double(3.5));
+
+    let exp = sub(lit(Literal::double(2.5)), int(Some(1)));
+    assert_eq!(exp.data_type(), &DataType::DoubleType);
+    assert_eq!(exp.eval(&Row::new()), Literal::double(1.5));
+
+    let exp = mul(lit(Literal::Short(Some(3))), int(None));
+    assert_eq!(exp.data_type(), &DataType::IntegerType);
+    assert_eq!(exp.eval(&Row::new()), Literal::Integer(None));
+
+    let exp = div(lit(Literal::long(7)), int(Some(2)));
+    assert_eq!(exp.data_type(), &DataType::LongType);
+    assert_eq!(exp.eval(&Row::new()), Literal::long(3));
+  }
+
+  #[test]
+  fn test_arithmetic_type_mismatch() {
+    let exp = add(int(Some(1)), lit(Literal::Boolean(Some(true))));
+    assert!(!exp.resolved());
+    assert!(!sub(lit(Literal::Boolean(Some(true))), int(Some(1))).resolved());
+    assert!(!mul(int(Some(1)), reference("a")).resolved());
+  }
+
+  #[test]
+  fn test_neg() {
+    let exp = neg(int(Some(5)));
+    assert_eq!(exp.pretty_string(), "(-5)");
+    assert_eq!(exp.node_name(), "UNARY_MINUS");
+    assert!(exp.foldable());
+    assert!(exp.deterministic());
+    assert!(exp.resolved());
+    assert_eq!(exp.data_type(), &DataType::IntegerType);
+    assert!(exp == exp.clone());
+    assert_eq!(exp.eval(&Row::new()), Literal::Integer(Some(-5)));
+    assert_eq!(neg(int(None)).eval(&Row::new()), Literal::Integer(None));
+
+    let exp = neg(add(int(Some(1)), lit(Literal::double(2.0))));
+    assert_eq!(exp.pretty_string(), "(-(1 + 2.0))");
+    assert_eq!(exp.data_type(), &DataType::DoubleType);
+    assert_eq!(exp.eval(&Row::new()), Literal::double(-3.0));
+
+    assert!(!neg(lit(Literal::Boolean(Some(true)))).resolved());
+    assert!(!neg(reference("a")).foldable());
+    let decimal = bound_reference("d", DataType::decimal_type(10, 2), true);
+    assert!(!neg(decimal.clone()).resolved());
+    assert!(!add(decimal.clone(), decimal).resolved());
+  }
+
+  #[test]
+  fn test_abs_reexport() {
+    let exp = abs(neg(int(Some(5))));
+    assert_eq!(exp.pretty_string(), "abs((-5))");
+    assert_eq!(exp.eval(&Row::new()), Literal::Integer(Some(5)));
+  }
+
+  #[test]
+  fn test_lit_macro() {
+    let exp = add(lit![Some(5_i64), i64], lit![Some(2), i64]);
+    assert_eq!(exp.pretty_string(), "(5 + 2)");
+    assert!(exp.resolved());
+    assert_eq!(exp.data_type(), &DataType::LongType);
+    assert_eq!(exp.eval(&Row::new()), Literal::long(7));
+
+    let exp = add(lit![Some(1), i32], lit![None, i64]);
+    assert!(exp.resolved());
+    assert_eq!(exp.data_type(), &DataType::LongType);
+    assert_eq!(exp.eval(&Row::new()), Literal::Long(None));
+
+    assert!(gt(lit![Some(5_i64), i64], lit![Some(1.5), f64]).resolved());
+    assert!(!add(lit![Some(5_i64), i64], lit![Some("a"), str]).resolved());
+    assert!(!add(lit![Some(true), bool], lit![Some(1), i8]).resolved());
+    assert_eq!(lit![Some(1), i16].data_type(), &DataType::ShortType);
+    assert_eq!(lit![Some(1.0), f32].data_type(), &DataType::FloatType);
+  }
+}
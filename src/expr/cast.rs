@@ -0,0 +1,177 @@
+// Copyright 2017 sadikovi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Cast expression to convert values between data types.
+
+use expr::api::{Expression, ExpressionBuilder};
+use expr::reference::quote_identifier;
+use types::DataType;
+
+/// Casts child expression to the data type, e.g. `cast(a as long)`.
+///
+/// Expression is resolved only if child value can be cast to the data type, see
+/// `Literal::cast` for the list of supported conversions.
+pub fn cast(child: Expression, data_type: DataType) -> Expression {
+  ExpressionBuilder::new("CAST".to_owned())
+    .children(vec![child])
+    .target_type(data_type)
+    .display(Box::new(|exp, children| {
+      format!("cast({} as {})", children[0], exp.target_type().unwrap())
+    }))
+    .sql(Box::new(|exp, children| {
+      format!("CAST({} AS {})", children[0], type_to_sql(exp.target_type().unwrap()))
+    }))
+    .foldable(Box::new(|exp| {
+      exp.children()[0].foldable()
+    }))
+    .deterministic(Box::new(|exp| {
+      exp.children()[0].deterministic()
+    }))
+    .nullable(Box::new(|exp| {
+      exp.children()[0].nullable()
+    }))
+    .resolved(Box::new(|exp| {
+      let child = &exp.children()[0];
+      child.resolved() && can_cast(child.data_type(), exp.target_type().unwrap())
+    }))
+    .datatype(Box::new(|exp| {
+      exp.target_type().unwrap()
+    }))
+    .eval(Box::new(|exp, row| {
+      let value = exp.children()[0].eval(row);
+      match value.cast(exp.target_type().unwrap()) {
+        Some(result) => result,
+        None => panic!("Cannot cast {} to {}", value, exp.target_type().unwrap())
+      }
+    }))
+    .clone(Box::new(|exp| {
+      cast(exp.children()[0].clone(), exp.target_type().unwrap().clone())
+    }))
+    .eq(Box::new(|a, b| {
+      a.target_type().is_some() && a.target_type() == b.target_type() &&
+        b.children().len() == 1 && a.children()[0] == b.children()[0]
+    }))
+    .build()
+}
+
+/// Internal method to check if value of type `from` can be cast to type `to`.
+fn can_cast(from: &DataType, to: &DataType) -> bool {
+  from == to ||
+    (from.is_numeric() && to.is_numeric()) ||
+    ((from.is_numeric() || from == &DataType::BooleanType) && to == &DataType::StringType)
+}
+
+/// Internal method to render data type in SQL, e.g. ``STRUCT<`a`:INT>``, types are
+/// rendered with Spark SQL keywords, struct field names are quoted, user-defined type
+/// names are kept as is.
+fn type_to_sql(data_type: &DataType) -> String {
+  match data_type {
+    DataType::BooleanType => "BOOLEAN".to_owned(),
+    DataType::ByteType => "TINYINT".to_owned(),
+    DataType::ShortType => "SMALLINT".to_owned(),
+    DataType::IntegerType => "INT".to_owned(),
+    DataType::LongType => "BIGINT".to_owned(),
+    DataType::FloatType => "FLOAT".to_owned(),
+    DataType::DoubleType => "DOUBLE".to_owned(),
+    DataType::StringType => "STRING".to_owned(),
+    DataType::CharType(length) => format!("CHAR({})", length),
+    DataType::VarcharType(length) => format!("VARCHAR({})", length),
+    DataType::BinaryType => "BINARY".to_owned(),
+    DataType::DateType => "DATE".to_owned(),
+    DataType::TimestampType => "TIMESTAMP".to_owned(),
+    DataType::DecimalType(precision, scale) => {
+      format!("DECIMAL({},{})", precision, scale)
+    },
+    DataType::StructType(ref fields) => {
+      let fields: Vec<String> = fields.iter()
+        .map(|field| {
+          format!("{}:{}", quote_identifier(field.name()), type_to_sql(field.data_type()))
+        })
+        .collect();
+      format!("STRUCT<{}>", fields.join(","))
+    },
+    DataType::ArrayType(ref element_type, _) => {
+      format!("ARRAY<{}>", type_to_sql(element_type))
+    },
+    DataType::MapType(ref key_type, ref value_type, _) => {
+      format!("MAP<{},{}>", type_to_sql(key_type), type_to_sql(value_type))
+    },
+    DataType::UserDefined { ref name, .. } => name.clone()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use expr::api::Row;
+  use expr::literal::{Literal, lit};
+  use expr::reference::{bound_reference, reference};
+
+  #[test]
+  fn test_cast() {
+    let exp = cast(lit(Literal::int(7)), DataType::LongType);
+    assert_eq!(exp.pretty_string(), "cast(7 as long)");
+    assert_eq!(exp.data_type(), &DataType::LongType);
+    assert!(exp.resolved());
+    assert!(exp.foldable());
+    assert!(!exp.nullable());
+    assert_eq!(exp.eval(&Row::new()), Literal::long(7));
+    assert_eq!(cast(lit(Literal::int(12)), DataType::StringType).eval(&Row::new()),
+      Literal::string("12"));
+    assert!(exp == exp.clone());
+    assert!(exp != cast(lit(Literal::int(7)), DataType::IntegerType));
+  }
+
+  #[test]
+  fn test_cast_resolved() {
+    let col = bound_reference("a", DataType::IntegerType, true);
+    let exp = cast(col, DataType::StringType);
+    assert!(exp.resolved());
+    assert!(exp.nullable());
+
+    let col = bound_reference("a", DataType::StringType, true);
+    let exp = cast(col, DataType::IntegerType);
+    assert!(!exp.resolved());
+    let exp = cast(reference("a"), DataType::IntegerType);
+    assert!(!exp.resolved());
+
+    // decimal values are not supported by literals
+    let exp = cast(lit(Literal::int(1)), DataType::decimal_type(10, 2));
+    assert!(!exp.resolved());
+    assert!(exp.partial_eval() == exp);
+  }
+
+  #[test]
+  fn test_cast_to_sql() {
+    let types = vec![
+      (DataType::BooleanType, "BOOLEAN"),
+      (DataType::ByteType, "TINYINT"),
+      (DataType::ShortType, "SMALLINT"),
+      (DataType::IntegerType, "INT"),
+      (DataType::LongType, "BIGINT"),
+      (DataType::FloatType, "FLOAT"),
+      (DataType::DoubleType, "DOUBLE"),
+      (DataType::StringType, "STRING"),
+      (DataType::CharType(10), "CHAR(10)"),
+      (DataType::VarcharType(255), "VARCHAR(255)"),
+      (DataType::BinaryType, "BINARY"),
+      (DataType::DateType, "DATE"),
+      (DataType::TimestampType, "TIMESTAMP"),
+      (DataType::decimal_type(10, 2), "DECIMAL(10,2)")
+    ];
+    for (tpe, sql) in types {
+      assert_eq!(cast(reference("a"), tpe).to_sql(), format!("CAST(`a` AS {})", sql));
+    }
+  }
+}
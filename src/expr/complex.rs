@@ -0,0 +1,249 @@
+// Copyright 2017 sadikovi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Contains expressions to construct and access complex types.
+
+use expr::api::{Expression, ExpressionBuilder};
+use expr::literal::Literal;
+use expr::reference::quote_identifier;
+use trees::TreeNode;
+use types::StructField;
+
+/// Expression to extract field `field_name` of a struct child, e.g. `a.b`.
+///
+/// Expression is not foldable, since struct values cannot be represented as literals.
+pub fn get_struct_field(child: Expression, field_name: &str) -> Expression {
+  ExpressionBuilder::new("GET_STRUCT_FIELD".to_owned())
+    .children(vec![child])
+    .field_name(field_name.to_owned())
+    .display(Box::new(|exp, children| {
+      format!("{}.{}", children[0], exp.field_name().unwrap())
+    }))
+    .sql(Box::new(|exp, children| {
+      format!("{}.{}", children[0], quote_identifier(exp.field_name().unwrap()))
+    }))
+    .foldable(Box::new(|_| false))
+    .deterministic(Box::new(|exp| {
+      exp.children()[0].deterministic()
+    }))
+    .nullable(Box::new(|exp| {
+      exp.children()[0].nullable() || struct_field_nullable(exp)
+    }))
+    .resolved(Box::new(|exp| {
+      exp.children()[0].resolved() && struct_field(exp).is_some()
+    }))
+    .datatype(Box::new(|exp| {
+      match struct_field(exp) {
+        Some(field) => field.data_type(),
+        None => panic!("Cannot extract field {} from {}, expected struct with this field",
+          exp.field_name().unwrap(), exp.children()[0].data_type())
+      }
+    }))
+    .clone(Box::new(|exp| {
+      get_struct_field(exp.children()[0].clone(), exp.field_name().unwrap())
+    }))
+    .eq(Box::new(|a, b| {
+      a.node_name() == b.node_name() && a.field_name() == b.field_name() &&
+        a.children() == b.children()
+    }))
+    .build()
+}
+
+/// Expression to construct struct from the list of field names and values, e.g.
+/// `struct(a = 1, b = c)`.
+///
+/// Expression is not foldable, since struct values cannot be represented as literals.
+pub fn create_struct(fields: Vec<(String, Expression)>) -> Expression {
+  let (names, children): (Vec<String>, Vec<Expression>) = fields.into_iter().unzip();
+  ExpressionBuilder::new("CREATE_STRUCT".to_owned())
+    .children(children)
+    .field_names(names)
+    .display(Box::new(|exp, children| {
+      let fields: Vec<String> = exp.field_names().iter().zip(children.iter())
+        .map(|(name, child)| format!("{} = {}", name, child))
+        .collect();
+      format!("struct({})", fields.join(", "))
+    }))
+    .sql(Box::new(|exp, children| {
+      let fields: Vec<String> = exp.field_names().iter().zip(children.iter())
+        .map(|(name, child)| format!("{}, {}", Literal::string(name).to_sql(), child))
+        .collect();
+      format!("named_struct({})", fields.join(", "))
+    }))
+    .foldable(Box::new(|_| false))
+    .deterministic(Box::new(|exp| {
+      exp.children().iter().all(|child| child.deterministic())
+    }))
+    .nullable(Box::new(|_| false))
+    .resolved(Box::new(|exp| {
+      exp.struct_type().is_some()
+    }))
+    .datatype(Box::new(|exp| {
+      match exp.struct_type() {
+        Some(dt) => dt,
+        None => panic!("Cannot extract data type from unresolved struct {}",
+          exp.pretty_string())
+      }
+    }))
+    .clone(Box::new(|exp| {
+      let fields = exp.field_names().iter().cloned().zip(exp.children().iter().cloned());
+      create_struct(fields.collect())
+    }))
+    .eq(Box::new(|a, b| {
+      a.struct_type().is_some() == b.struct_type().is_some() &&
+        a.field_names() == b.field_names() &&
+        a.children() == b.children()
+    }))
+    .build()
+}
+
+/// Internal method to find extracted field in the data type of the child, `None` if
+/// child is unresolved, is not a struct or does not have the field.
+fn struct_field(exp: &Expression) -> Option<&StructField> {
+  if !exp.children()[0].resolved() {
+    return None;
+  }
+  let data_type = exp.children()[0].data_type();
+  if data_type.is_struct() { data_type.field(exp.field_name().unwrap()) } else { None }
+}
+
+/// Internal method to check if extracted field is nullable.
+fn struct_field_nullable(exp: &Expression) -> bool {
+  match struct_field(exp) {
+    Some(field) => field.is_nullable(),
+    None => true
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use expr::literal::{Literal, lit};
+  use expr::reference::{bound_reference, reference};
+  use types::DataType;
+
+  fn struct_ref(nullable: bool) -> Expression {
+    let schema = DataType::struct_type(vec![])
+      .add_field_n("x", DataType::IntegerType, false)
+      .add_field("y", DataType::StringType);
+    bound_reference("s", schema, nullable)
+  }
+
+  #[test]
+  fn test_get_struct_field() {
+    let exp = get_struct_field(struct_ref(false), "x");
+    assert_eq!(exp.node_name(), "GET_STRUCT_FIELD");
+    assert_eq!(exp.pretty_string(), "s.x");
+    assert_eq!(exp.field_name(), Some("x"));
+    assert_eq!(exp.data_type(), &DataType::IntegerType);
+    assert!(exp.resolved());
+    assert!(!exp.nullable());
+    assert!(!exp.foldable());
+    assert!(exp.deterministic());
+    assert!(exp.clone() == exp);
+    assert!(exp.semantic_equals(&exp.clone()));
+    assert!(exp != get_struct_field(struct_ref(false), "y"));
+    assert!(!exp.semantic_equals(&get_struct_field(struct_ref(false), "y")));
+    assert!(exp != struct_ref(false));
+  }
+
+  #[test]
+  fn test_get_struct_field_not_foldable() {
+    let child = create_struct(vec![("x".to_owned(), lit(Literal::Integer(Some(1))))]);
+    let exp = get_struct_field(child, "x");
+    assert!(exp.resolved());
+    assert!(!exp.foldable());
+    assert!(exp.partial_eval() == exp);
+  }
+
+  #[test]
+  fn test_create_struct() {
+    let exp = create_struct(vec![
+      ("x".to_owned(), lit(Literal::Integer(Some(1)))),
+      ("y".to_owned(), lit(Literal::String(Some("a".to_owned()))))
+    ]);
+    assert_eq!(exp.node_name(), "CREATE_STRUCT");
+    assert_eq!(exp.pretty_string(), "struct(x = 1, y = \"a\")");
+    assert!(exp.resolved());
+    assert!(!exp.nullable());
+    assert!(!exp.foldable());
+    assert!(exp.partial_eval() == exp);
+    assert_eq!(exp.data_type(), &DataType::struct_type(vec![])
+      .add_field_n("x", DataType::IntegerType, false)
+      .add_field_n("y", DataType::StringType, false));
+    assert_eq!(exp.data_type().to_string(), "struct<x:int,y:string>");
+    assert!(exp.clone() == exp);
+    assert!(exp.clone().semantic_equals(&exp));
+
+    // nested field access
+    let field = get_struct_field(exp, "y");
+    assert!(field.resolved());
+    assert_eq!(field.data_type(), &DataType::StringType);
+  }
+
+  #[test]
+  fn test_create_struct_unresolved() {
+    let exp = create_struct(vec![
+      ("x".to_owned(), lit(Literal::Integer(Some(1)))),
+      ("y".to_owned(), reference("a"))
+    ]);
+    assert!(!exp.resolved());
+    assert_eq!(exp.pretty_string(), "struct(x = 1, y = 'a)");
+
+    // resolving children updates data type
+    let exp = exp.transform_up(&mut |e| {
+      if e.reference().is_some() {
+        Some(bound_reference("a", DataType::LongType, true))
+      } else {
+        None
+      }
+    });
+    assert!(exp.resolved());
+    assert_eq!(exp.data_type(), &DataType::struct_type(vec![])
+      .add_field_n("x", DataType::IntegerType, false)
+      .add_field_n("y", DataType::LongType, true));
+  }
+
+  #[test]
+  #[should_panic(expected = "Cannot extract data type from unresolved struct")]
+  fn test_create_struct_unresolved_data_type() {
+    create_struct(vec![("x".to_owned(), reference("a"))]).data_type();
+  }
+
+  #[test]
+  fn test_get_struct_field_nullable() {
+    assert!(get_struct_field(struct_ref(false), "y").nullable());
+    assert!(get_struct_field(struct_ref(true), "x").nullable());
+  }
+
+  #[test]
+  fn test_get_struct_field_unresolved() {
+    assert!(!get_struct_field(struct_ref(false), "z").resolved());
+    assert!(!get_struct_field(reference("s"), "x").resolved());
+    assert!(!get_struct_field(bound_reference("s", DataType::IntegerType, false), "x")
+      .resolved());
+    assert_eq!(get_struct_field(reference("s"), "x").pretty_string(), "'s.x");
+
+    let child = create_struct(vec![("x".to_owned(), reference("a"))]);
+    let exp = get_struct_field(child, "x");
+    assert!(!exp.resolved());
+    assert!(exp.nullable());
+  }
+
+  #[test]
+  #[should_panic(expected = "Cannot extract field z from struct<x:int,y:string>")]
+  fn test_get_struct_field_missing_field() {
+    get_struct_field(struct_ref(false), "z").data_type();
+  }
+}
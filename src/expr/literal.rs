@@ -14,15 +14,1020 @@
 
 //! Literal expressions.
 
-#[derive(Clone, PartialEq)]
-pub enum Literal {
+use std::cmp::Ordering;
+use std::fmt;
+
+use errors::CatalystError;
+use expr::api::{Expression, ExpressionBuilder};
+use types::DataType;
+
+/// Applies arithmetic operation to two numeric literals of the same type.
+/// Integral types use wrapping `$int_op`, floating point types use `$float_op`.
+macro_rules! numeric_op {
+  ($left:expr, $right:expr, $int_op:ident, $float_op:tt, $op_name:expr) => {
+    match ($left, $right) {
+      (Literal::Byte(a), Literal::Byte(b)) => {
+        Literal::Byte(combine(a, b, |x, y| x.$int_op(y)))
+      },
+      (Literal::Short(a), Literal::Short(b)) => {
+        Literal::Short(combine(a, b, |x, y| x.$int_op(y)))
+      },
+      (Literal::Integer(a), Literal::Integer(b)) => {
+        Literal::Integer(combine(a, b, |x, y| x.$int_op(y)))
+      },
+      (Literal::Long(a), Literal::Long(b)) => {
+        Literal::Long(combine(a, b, |x, y| x.$int_op(y)))
+      },
+      (Literal::Float(a), Literal::Float(b)) => {
+        Literal::Float(combine(a, b, |x, y| x $float_op y))
+      },
+      (Literal::Double(a), Literal::Double(b)) => {
+        Literal::Double(combine(a, b, |x, y| x $float_op y))
+      },
+      (left, right) => panic!("Cannot {} {} and {}", $op_name, left, right)
+    }
+  }
+}
+
+/// Applies division operation to two numeric literals of the same type, division by
+/// zero returns null. Integral types use wrapping `$int_op`, floating point types use
+/// `$float_op`.
+macro_rules! division_op {
+  ($left:expr, $right:expr, $int_op:ident, $float_op:tt, $op_name:expr) => {
+    match ($left, $right) {
+      (Literal::Byte(a), Literal::Byte(b)) => {
+        Literal::Byte(combine_nonzero(a, b, 0, |x, y| x.$int_op(y)))
+      },
+      (Literal::Short(a), Literal::Short(b)) => {
+        Literal::Short(combine_nonzero(a, b, 0, |x, y| x.$int_op(y)))
+      },
+      (Literal::Integer(a), Literal::Integer(b)) => {
+        Literal::Integer(combine_nonzero(a, b, 0, |x, y| x.$int_op(y)))
+      },
+      (Literal::Long(a), Literal::Long(b)) => {
+        Literal::Long(combine_nonzero(a, b, 0, |x, y| x.$int_op(y)))
+      },
+      (Literal::Float(a), Literal::Float(b)) => {
+        Literal::Float(combine_nonzero(a, b, 0.0, |x, y| x $float_op y))
+      },
+      (Literal::Double(a), Literal::Double(b)) => {
+        Literal::Double(combine_nonzero(a, b, 0.0, |x, y| x $float_op y))
+      },
+      (left, right) => panic!("Cannot {} {} and {}", $op_name, left, right)
+    }
+  }
+}
+
+/// Applies checked arithmetic operation to two numeric literals of the same type.
+/// Integral types use `$checked_op` and resolve overflow according to `$mode`, floating
+/// point types use `$float_op`. Returns error if literals are not numeric or have
+/// different types.
+macro_rules! checked_numeric_op {
+  (
+    $left:expr, $right:expr, $mode:expr,
+    $checked_op:ident, $wrapping_op:ident, $float_op:tt, $op_name:expr
+  ) => {
+    match ($left, $right) {
+      (Literal::Byte(a), Literal::Byte(b)) => {
+        combine_checked(a, b, $mode, $op_name,
+          |x, y| x.$checked_op(y), |x, y| x.$wrapping_op(y)).map(Literal::Byte)
+      },
+      (Literal::Short(a), Literal::Short(b)) => {
+        combine_checked(a, b, $mode, $op_name,
+          |x, y| x.$checked_op(y), |x, y| x.$wrapping_op(y)).map(Literal::Short)
+      },
+      (Literal::Integer(a), Literal::Integer(b)) => {
+        combine_checked(a, b, $mode, $op_name,
+          |x, y| x.$checked_op(y), |x, y| x.$wrapping_op(y)).map(Literal::Integer)
+      },
+      (Literal::Long(a), Literal::Long(b)) => {
+        combine_checked(a, b, $mode, $op_name,
+          |x, y| x.$checked_op(y), |x, y| x.$wrapping_op(y)).map(Literal::Long)
+      },
+      (Literal::Float(a), Literal::Float(b)) => {
+        Ok(Literal::Float(combine(a, b, |x, y| x $float_op y)))
+      },
+      (Literal::Double(a), Literal::Double(b)) => {
+        Ok(Literal::Double(combine(a, b, |x, y| x $float_op y)))
+      },
+      (left, right) => eval_err!("Cannot {} {} and {}", $op_name, left, right)
+    }
+  }
+}
+
+/// Converts primitive numeric value into literal of the numeric data type using `as`.
+/// Returns `None` if data type is not numeric.
+macro_rules! cast_numeric {
+  ($value:expr, $data_type:expr) => {
+    match $data_type {
+      DataType::ByteType => Some(Literal::Byte(Some($value as i8))),
+      DataType::ShortType => Some(Literal::Short(Some($value as i16))),
+      DataType::IntegerType => Some(Literal::Integer(Some($value as i32))),
+      DataType::LongType => Some(Literal::Long(Some($value as i64))),
+      DataType::FloatType => Some(Literal::Float(Some($value as f32))),
+      DataType::DoubleType => Some(Literal::Double(Some($value as f64))),
+      _ => None
+    }
+  }
+}
+
+/// Defines how overflow of integral arithmetic is handled.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OverflowMode {
+  /// Result wraps around the boundary of the type.
+  Wrap,
+  /// Result is null.
   Null,
-  Boolean(bool),
-  Byte(i8),
-  Short(i16),
-  Integer(i32),
-  Long(i64),
-  Float(f32),
-  Double(f64),
-  String(String)
+  /// Operation returns error, similar to ANSI mode.
+  Error
+}
+
+/// Literal value of a specific data type.
+///
+/// Each variant is typed, `None` represents null value of that type.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Literal {
+  Boolean(Option<bool>),
+  Byte(Option<i8>),
+  Short(Option<i16>),
+  Integer(Option<i32>),
+  Long(Option<i64>),
+  Float(Option<f32>),
+  Double(Option<f64>),
+  String(Option<String>),
+  Binary(Option<Vec<u8>>)
+}
+
+impl Literal {
+  /// Creates non-null boolean literal.
+  pub fn boolean(value: bool) -> Literal {
+    Literal::Boolean(Some(value))
+  }
+
+  /// Creates non-null integer literal.
+  pub fn int(value: i32) -> Literal {
+    Literal::Integer(Some(value))
+  }
+
+  /// Creates non-null long literal.
+  pub fn long(value: i64) -> Literal {
+    Literal::Long(Some(value))
+  }
+
+  /// Creates non-null double literal.
+  pub fn double(value: f64) -> Literal {
+    Literal::Double(Some(value))
+  }
+
+  /// Creates non-null string literal.
+  pub fn string(value: &str) -> Literal {
+    Literal::String(Some(value.to_owned()))
+  }
+
+  /// Creates null boolean literal.
+  pub fn null_boolean() -> Literal {
+    Literal::Boolean(None)
+  }
+
+  /// Creates null integer literal.
+  pub fn null_int() -> Literal {
+    Literal::Integer(None)
+  }
+
+  /// Creates null long literal.
+  pub fn null_long() -> Literal {
+    Literal::Long(None)
+  }
+
+  /// Creates null double literal.
+  pub fn null_double() -> Literal {
+    Literal::Double(None)
+  }
+
+  /// Creates null string literal.
+  pub fn null_string() -> Literal {
+    Literal::String(None)
+  }
+
+  /// Creates non-null integer literal from native value, same as `int`.
+  pub fn from_i32(value: i32) -> Literal {
+    Literal::int(value)
+  }
+
+  /// Creates non-null long literal from native value, same as `long`.
+  pub fn from_i64(value: i64) -> Literal {
+    Literal::long(value)
+  }
+
+  /// Creates non-null double literal from native value, same as `double`.
+  pub fn from_f64(value: f64) -> Literal {
+    Literal::double(value)
+  }
+
+  /// Creates non-null boolean literal from native value, same as `boolean`.
+  pub fn from_bool(value: bool) -> Literal {
+    Literal::boolean(value)
+  }
+
+  /// Creates non-null string literal from native value, same as `string`.
+  ///
+  /// This is not `FromStr::from_str`: value is not parsed and always becomes a string.
+  #[allow(clippy::should_implement_trait)]
+  pub fn from_str(value: &str) -> Literal {
+    Literal::string(value)
+  }
+
+  /// Creates null literal of the data type.
+  ///
+  /// Panics if data type does not have a literal representation, e.g. struct or array.
+  pub fn null(data_type: DataType) -> Literal {
+    match Literal::typed_null(&data_type) {
+      Ok(literal) => literal,
+      Err(_) => panic!("Cannot create null literal of type {}", data_type)
+    }
+  }
+
+  /// Creates null literal of the data type, similar to `null`.
+  ///
+  /// Returns error if data type does not have a literal representation, e.g. struct,
+  /// array, or map.
+  pub fn typed_null(data_type: &DataType) -> Result<Literal, CatalystError> {
+    match data_type {
+      DataType::BooleanType => Ok(Literal::Boolean(None)),
+      DataType::ByteType => Ok(Literal::Byte(None)),
+      DataType::ShortType => Ok(Literal::Short(None)),
+      DataType::IntegerType => Ok(Literal::Integer(None)),
+      DataType::LongType => Ok(Literal::Long(None)),
+      DataType::FloatType => Ok(Literal::Float(None)),
+      DataType::DoubleType => Ok(Literal::Double(None)),
+      DataType::StringType => Ok(Literal::String(None)),
+      DataType::BinaryType => Ok(Literal::Binary(None)),
+      _ => schema_err!("Cannot create null literal of type {}", data_type)
+    }
+  }
+
+  /// Returns `true` if literal represents null value.
+  pub fn is_null(&self) -> bool {
+    match self {
+      Literal::Boolean(v) => v.is_none(),
+      Literal::Byte(v) => v.is_none(),
+      Literal::Short(v) => v.is_none(),
+      Literal::Integer(v) => v.is_none(),
+      Literal::Long(v) => v.is_none(),
+      Literal::Float(v) => v.is_none(),
+      Literal::Double(v) => v.is_none(),
+      Literal::String(v) => v.is_none(),
+      Literal::Binary(v) => v.is_none()
+    }
+  }
+
+  /// Returns data type of the literal.
+  pub fn data_type(&self) -> &DataType {
+    match self {
+      Literal::Boolean(_) => &DataType::BooleanType,
+      Literal::Byte(_) => &DataType::ByteType,
+      Literal::Short(_) => &DataType::ShortType,
+      Literal::Integer(_) => &DataType::IntegerType,
+      Literal::Long(_) => &DataType::LongType,
+      Literal::Float(_) => &DataType::FloatType,
+      Literal::Double(_) => &DataType::DoubleType,
+      Literal::String(_) => &DataType::StringType,
+      Literal::Binary(_) => &DataType::BinaryType
+    }
+  }
+
+  /// Adds two numeric literals of the same type, integral types wrap on overflow.
+  /// Returns null if either value is null.
+  pub fn add(&self, other: &Literal) -> Literal {
+    numeric_op!(self, other, wrapping_add, +, "add")
+  }
+
+  /// Subtracts other numeric literal of the same type, integral types wrap on overflow.
+  /// Returns null if either value is null.
+  pub fn sub(&self, other: &Literal) -> Literal {
+    numeric_op!(self, other, wrapping_sub, -, "subtract")
+  }
+
+  /// Multiplies two numeric literals of the same type, integral types wrap on overflow.
+  /// Returns null if either value is null.
+  pub fn mul(&self, other: &Literal) -> Literal {
+    numeric_op!(self, other, wrapping_mul, *, "multiply")
+  }
+
+  /// Divides two numeric literals of the same type, integral division truncates.
+  /// Returns null if either value is null or divisor is zero.
+  pub fn div(&self, other: &Literal) -> Literal {
+    division_op!(self, other, wrapping_div, /, "divide")
+  }
+
+  /// Returns remainder of division of two numeric literals of the same type.
+  /// Returns null if either value is null or divisor is zero.
+  pub fn rem(&self, other: &Literal) -> Literal {
+    division_op!(self, other, wrapping_rem, %, "compute remainder of")
+  }
+
+  /// Negates numeric literal, integral types wrap on overflow, e.g. negation of the
+  /// minimum value is the minimum value. Returns null if value is null.
+  pub fn neg(&self) -> Literal {
+    match self {
+      Literal::Byte(a) => Literal::Byte(a.map(|x| x.wrapping_neg())),
+      Literal::Short(a) => Literal::Short(a.map(|x| x.wrapping_neg())),
+      Literal::Integer(a) => Literal::Integer(a.map(|x| x.wrapping_neg())),
+      Literal::Long(a) => Literal::Long(a.map(|x| x.wrapping_neg())),
+      Literal::Float(a) => Literal::Float(a.map(|x| -x)),
+      Literal::Double(a) => Literal::Double(a.map(|x| -x)),
+      other => panic!("Cannot negate {}", other)
+    }
+  }
+
+  /// Returns absolute value of numeric literal, integral types wrap on overflow, e.g.
+  /// absolute value of the minimum value is the minimum value. Returns null if value is
+  /// null.
+  pub fn abs(&self) -> Literal {
+    match self {
+      Literal::Byte(a) => Literal::Byte(a.map(|x| x.wrapping_abs())),
+      Literal::Short(a) => Literal::Short(a.map(|x| x.wrapping_abs())),
+      Literal::Integer(a) => Literal::Integer(a.map(|x| x.wrapping_abs())),
+      Literal::Long(a) => Literal::Long(a.map(|x| x.wrapping_abs())),
+      Literal::Float(a) => Literal::Float(a.map(|x| x.abs())),
+      Literal::Double(a) => Literal::Double(a.map(|x| x.abs())),
+      other => panic!("Cannot compute absolute value of {}", other)
+    }
+  }
+
+  /// Adds two numeric literals of the same type, overflow of integral types is handled
+  /// according to the mode. Returns null if either value is null.
+  /// Returns error if literals are not numeric or have different types.
+  pub fn try_add(
+    &self,
+    other: &Literal,
+    mode: OverflowMode
+  ) -> Result<Literal, CatalystError>
+  {
+    checked_numeric_op!(self, other, mode, checked_add, wrapping_add, +, "add")
+  }
+
+  /// Subtracts other numeric literal of the same type, overflow of integral types is
+  /// handled according to the mode. Returns null if either value is null.
+  /// Returns error if literals are not numeric or have different types.
+  pub fn try_sub(
+    &self,
+    other: &Literal,
+    mode: OverflowMode
+  ) -> Result<Literal, CatalystError>
+  {
+    checked_numeric_op!(self, other, mode, checked_sub, wrapping_sub, -, "subtract")
+  }
+
+  /// Multiplies two numeric literals of the same type, overflow of integral types is
+  /// handled according to the mode. Returns null if either value is null.
+  /// Returns error if literals are not numeric or have different types.
+  pub fn try_mul(
+    &self,
+    other: &Literal,
+    mode: OverflowMode
+  ) -> Result<Literal, CatalystError>
+  {
+    checked_numeric_op!(self, other, mode, checked_mul, wrapping_mul, *, "multiply")
+  }
+
+  /// Compares two literals of the same type.
+  ///
+  /// Returns `None` if either value is null or values are not comparable, e.g. NaN.
+  /// Panics if literals have different types.
+  pub fn compare(&self, other: &Literal) -> Option<Ordering> {
+    match (self, other) {
+      (Literal::Boolean(a), Literal::Boolean(b)) => compare_values(a, b),
+      (Literal::Byte(a), Literal::Byte(b)) => compare_values(a, b),
+      (Literal::Short(a), Literal::Short(b)) => compare_values(a, b),
+      (Literal::Integer(a), Literal::Integer(b)) => compare_values(a, b),
+      (Literal::Long(a), Literal::Long(b)) => compare_values(a, b),
+      (Literal::Float(a), Literal::Float(b)) => compare_values(a, b),
+      (Literal::Double(a), Literal::Double(b)) => compare_values(a, b),
+      (Literal::String(a), Literal::String(b)) => compare_values(a, b),
+      (Literal::Binary(a), Literal::Binary(b)) => compare_values(a, b),
+      (left, right) => panic!("Cannot compare {} and {}", left, right)
+    }
+  }
+
+  /// Casts literal to the data type, null value is cast to null of that type.
+  ///
+  /// Numeric values are converted between numeric types, integral types are truncated
+  /// similar to `as`, numeric and boolean values can also be cast to string.
+  /// Returns `None` if cast is not supported.
+  pub fn cast(&self, data_type: &DataType) -> Option<Literal> {
+    if self.data_type() == data_type {
+      return Some(self.clone());
+    }
+    if self.is_null() {
+      return Literal::typed_null(data_type).ok();
+    }
+    match (self, data_type) {
+      (Literal::Boolean(Some(v)), DataType::StringType) => {
+        Some(Literal::String(Some(v.to_string())))
+      },
+      (Literal::Boolean(_), _) => None,
+      (Literal::String(_), _) | (Literal::Binary(_), _) => None,
+      (_, DataType::StringType) => Some(Literal::String(Some(self.to_string()))),
+      (Literal::Byte(Some(v)), _) => cast_numeric!(*v, data_type),
+      (Literal::Short(Some(v)), _) => cast_numeric!(*v, data_type),
+      (Literal::Integer(Some(v)), _) => cast_numeric!(*v, data_type),
+      (Literal::Long(Some(v)), _) => cast_numeric!(*v, data_type),
+      (Literal::Float(Some(v)), _) => cast_numeric!(*v, data_type),
+      (Literal::Double(Some(v)), _) => cast_numeric!(*v, data_type),
+      _ => unreachable!()
+    }
+  }
+
+  /// Parses literal of the data type from its display string, e.g. `1.5` or `"abc"`,
+  /// `null` is parsed as null value of the data type.
+  ///
+  /// Returns `None` if text cannot be parsed or data type is not atomic.
+  pub fn from_display(text: &str, data_type: &DataType) -> Option<Literal> {
+    if text == "null" {
+      return Literal::typed_null(data_type).ok();
+    }
+    match data_type {
+      DataType::BooleanType => text.parse().ok().map(|v| Literal::Boolean(Some(v))),
+      DataType::ByteType => text.parse().ok().map(|v| Literal::Byte(Some(v))),
+      DataType::ShortType => text.parse().ok().map(|v| Literal::Short(Some(v))),
+      DataType::IntegerType => text.parse().ok().map(|v| Literal::Integer(Some(v))),
+      DataType::LongType => text.parse().ok().map(|v| Literal::Long(Some(v))),
+      DataType::FloatType => text.parse().ok().map(|v| Literal::Float(Some(v))),
+      DataType::DoubleType => text.parse().ok().map(|v| Literal::Double(Some(v))),
+      DataType::StringType => {
+        unescape_debug_string(text).map(|v| Literal::String(Some(v)))
+      },
+      DataType::BinaryType => parse_debug_bytes(text).map(|v| Literal::Binary(Some(v))),
+      _ => None
+    }
+  }
+
+  /// Returns SQL representation of the literal.
+  ///
+  /// Strings are single-quoted with quotes and special characters escaped, binary
+  /// values are rendered as hex string, e.g. `X'0A1B'`. NaN and infinity have no literal
+  /// syntax and are rendered as cast from string, e.g. `CAST('NaN' AS DOUBLE)`.
+  pub fn to_sql(&self) -> String {
+    if self.is_null() {
+      return "NULL".to_owned();
+    }
+    match self {
+      Literal::Boolean(Some(v)) => {
+        if *v { "TRUE".to_owned() } else { "FALSE".to_owned() }
+      },
+      Literal::Byte(Some(v)) => format!("{}Y", v),
+      Literal::Short(Some(v)) => format!("{}S", v),
+      Literal::Integer(Some(v)) => format!("{}", v),
+      Literal::Long(Some(v)) => format!("{}L", v),
+      Literal::Float(Some(v)) => format!("CAST('{}' AS FLOAT)", float_sql_string(*v)),
+      Literal::Double(Some(v)) if v.is_finite() => format!("{:?}D", v),
+      Literal::Double(Some(v)) => format!("CAST('{}' AS DOUBLE)", float_sql_string(*v)),
+      Literal::String(Some(v)) => format!("'{}'", escape_sql_string(v)),
+      Literal::Binary(Some(v)) => {
+        let hex: Vec<String> = v.iter().map(|byte| format!("{:02X}", byte)).collect();
+        format!("X'{}'", hex.join(""))
+      },
+      _ => unreachable!()
+    }
+  }
+}
+
+impl fmt::Display for Literal {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Literal::Boolean(Some(v)) => write!(f, "{}", v),
+      Literal::Byte(Some(v)) => write!(f, "{}", v),
+      Literal::Short(Some(v)) => write!(f, "{}", v),
+      Literal::Integer(Some(v)) => write!(f, "{}", v),
+      Literal::Long(Some(v)) => write!(f, "{}", v),
+      Literal::Float(Some(v)) => write!(f, "{:?}", v),
+      Literal::Double(Some(v)) => write!(f, "{:?}", v),
+      Literal::String(Some(v)) => write!(f, "{:?}", v),
+      Literal::Binary(Some(v)) => write!(f, "{:?}", v),
+      _ => write!(f, "null")
+    }
+  }
+}
+
+/// Internal method to combine two nullable values with checked function, overflow is
+/// resolved according to the mode. Returns null if either value is null.
+fn combine_checked<T: Copy + fmt::Display, C, W>(
+  left: &Option<T>,
+  right: &Option<T>,
+  mode: OverflowMode,
+  op_name: &str,
+  checked_func: C,
+  wrapping_func: W
+) -> Result<Option<T>, CatalystError> where C: Fn(T, T) -> Option<T>, W: Fn(T, T) -> T
+{
+  match (left, right) {
+    (Some(a), Some(b)) => match checked_func(*a, *b) {
+      Some(value) => Ok(Some(value)),
+      None => match mode {
+        OverflowMode::Wrap => Ok(Some(wrapping_func(*a, *b))),
+        OverflowMode::Null => Ok(None),
+        OverflowMode::Error => {
+          eval_err!("Overflow when trying to {} {} and {}", op_name, a, b)
+        }
+      }
+    },
+    _ => Ok(None)
+  }
+}
+
+/// Internal method to combine two nullable values, returns null if either is null.
+fn combine<T: Copy, F>(left: &Option<T>, right: &Option<T>, func: F) -> Option<T>
+    where F: Fn(T, T) -> T {
+  match (left, right) {
+    (Some(a), Some(b)) => Some(func(*a, *b)),
+    _ => None
+  }
+}
+
+/// Internal method to combine two nullable values, returns null if either is null or
+/// right value is equal to `zero`.
+fn combine_nonzero<T: Copy + PartialEq, F>(
+  left: &Option<T>,
+  right: &Option<T>,
+  zero: T,
+  func: F
+) -> Option<T> where F: Fn(T, T) -> T
+{
+  match (left, right) {
+    (Some(a), Some(b)) if *b != zero => Some(func(*a, *b)),
+    _ => None
+  }
+}
+
+/// Internal method to compare two nullable values.
+fn compare_values<T>(left: &Option<T>, right: &Option<T>) -> Option<Ordering>
+    where T: PartialOrd {
+  match (left, right) {
+    (Some(a), Some(b)) => a.partial_cmp(b),
+    _ => None
+  }
+}
+
+/// Literal expression for the value.
+pub fn lit(value: Literal) -> Expression {
+  ExpressionBuilder::new("LITERAL".to_owned())
+    .literal(value)
+    .display(Box::new(|exp, _| {
+      format!("{}", exp.literal().unwrap())
+    }))
+    .sql(Box::new(|exp, _| {
+      exp.literal().unwrap().to_sql()
+    }))
+    .foldable(Box::new(|_| true))
+    .deterministic(Box::new(|_| true))
+    .nullable(Box::new(|exp| {
+      exp.literal().unwrap().is_null()
+    }))
+    .resolved(Box::new(|_| true))
+    .datatype(Box::new(|exp| {
+      exp.literal().unwrap().data_type()
+    }))
+    .eval(Box::new(|exp, _| {
+      exp.literal().unwrap().clone()
+    }))
+    .clone(Box::new(|exp| {
+      lit(exp.literal().unwrap().clone())
+    }))
+    .eq(Box::new(|a, b| {
+      a.literal().is_some() && a.literal() == b.literal()
+    }))
+    .build()
+}
+
+/// Internal method to render floating point value as string that can be cast to float or
+/// double in SQL, NaN and infinity use their SQL names.
+fn float_sql_string<T: Copy + fmt::Debug + Into<f64>>(value: T) -> String {
+  let v: f64 = value.into();
+  if v.is_nan() {
+    "NaN".to_owned()
+  } else if v.is_infinite() {
+    if v > 0.0 { "Infinity".to_owned() } else { "-Infinity".to_owned() }
+  } else {
+    format!("{:?}", value)
+  }
+}
+
+/// Internal method to escape string value for SQL string literal.
+fn escape_sql_string(value: &str) -> String {
+  let mut buf = String::with_capacity(value.len());
+  for ch in value.chars() {
+    match ch {
+      '\'' => buf.push_str("''"),
+      '\\' => buf.push_str("\\\\"),
+      '\n' => buf.push_str("\\n"),
+      '\t' => buf.push_str("\\t"),
+      '\r' => buf.push_str("\\r"),
+      _ => buf.push(ch)
+    }
+  }
+  buf
+}
+
+/// Internal method to parse double-quoted string with escape sequences produced by
+/// debug formatting, e.g. `"a\"b"`.
+fn unescape_debug_string(text: &str) -> Option<String> {
+  if text.len() < 2 || !text.starts_with('"') || !text.ends_with('"') {
+    return None;
+  }
+  let mut buf = String::with_capacity(text.len());
+  let mut chars = text[1..text.len() - 1].chars();
+  while let Some(ch) = chars.next() {
+    if ch != '\\' {
+      buf.push(ch);
+      continue;
+    }
+    match chars.next() {
+      Some('n') => buf.push('\n'),
+      Some('t') => buf.push('\t'),
+      Some('r') => buf.push('\r'),
+      Some('0') => buf.push('\0'),
+      Some('\\') => buf.push('\\'),
+      Some('"') => buf.push('"'),
+      Some('\'') => buf.push('\''),
+      Some('u') => {
+        if chars.next() != Some('{') {
+          return None;
+        }
+        let code: String = chars.by_ref().take_while(|c| *c != '}').collect();
+        match u32::from_str_radix(&code, 16).ok().and_then(::std::char::from_u32) {
+          Some(value) => buf.push(value),
+          None => return None
+        }
+      },
+      _ => return None
+    }
+  }
+  Some(buf)
+}
+
+/// Internal method to parse list of bytes produced by debug formatting, e.g. `[1, 2]`.
+fn parse_debug_bytes(text: &str) -> Option<Vec<u8>> {
+  if !text.starts_with('[') || !text.ends_with(']') {
+    return None;
+  }
+  let inner = text[1..text.len() - 1].trim();
+  if inner.is_empty() {
+    return Some(vec![]);
+  }
+  inner.split(',').map(|value| value.trim().parse().ok()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use trees::TreeNode;
+
+  #[test]
+  fn test_literal_is_null() {
+    assert!(Literal::Integer(None).is_null());
+    assert!(Literal::Binary(None).is_null());
+    assert!(!Literal::Integer(Some(1)).is_null());
+    assert!(!Literal::String(Some("".to_owned())).is_null());
+  }
+
+  #[test]
+  fn test_literal_constructors() {
+    assert_eq!(Literal::boolean(true), Literal::Boolean(Some(true)));
+    assert_eq!(Literal::int(5), Literal::Integer(Some(5)));
+    assert_eq!(Literal::long(5), Literal::Long(Some(5)));
+    assert_eq!(Literal::double(1.5), Literal::Double(Some(1.5)));
+    assert_eq!(Literal::string("a"), Literal::String(Some("a".to_owned())));
+    assert_eq!(Literal::null_boolean(), Literal::Boolean(None));
+    assert_eq!(Literal::null_int(), Literal::Integer(None));
+    assert_eq!(Literal::null_long(), Literal::Long(None));
+    assert_eq!(Literal::null_double(), Literal::Double(None));
+    assert_eq!(Literal::null_string(), Literal::String(None));
+
+    assert_eq!(Literal::int(5).data_type(), &DataType::IntegerType);
+    assert_eq!(Literal::null_int().data_type(), &DataType::IntegerType);
+    assert!(Literal::null_string().is_null());
+  }
+
+  #[test]
+  fn test_literal_native_constructors() {
+    assert_eq!(Literal::from_i32(5), Literal::int(5));
+    assert_eq!(Literal::from_i64(5), Literal::long(5));
+    assert_eq!(Literal::from_f64(1.5), Literal::double(1.5));
+    assert_eq!(Literal::from_bool(false), Literal::boolean(false));
+    assert_eq!(Literal::from_str("x"), Literal::string("x"));
+    assert_eq!(Literal::from_str("x").to_string(), "\"x\"");
+  }
+
+  #[test]
+  fn test_literal_null_of_type() {
+    assert!(Literal::null(DataType::IntegerType).is_null());
+    assert_eq!(Literal::null(DataType::IntegerType), Literal::Integer(None));
+    assert_eq!(Literal::null(DataType::ByteType), Literal::Byte(None));
+    assert_eq!(Literal::null(DataType::StringType), Literal::String(None));
+    assert_eq!(Literal::null(DataType::BinaryType).data_type(), &DataType::BinaryType);
+  }
+
+  #[test]
+  fn test_literal_typed_null() {
+    let types = vec![
+      DataType::BooleanType,
+      DataType::ByteType,
+      DataType::ShortType,
+      DataType::IntegerType,
+      DataType::LongType,
+      DataType::FloatType,
+      DataType::DoubleType,
+      DataType::StringType,
+      DataType::BinaryType
+    ];
+    for tpe in types {
+      let literal = Literal::typed_null(&tpe).unwrap();
+      assert!(literal.is_null());
+      assert_eq!(literal.data_type(), &tpe);
+      assert_eq!(literal, Literal::null(tpe));
+    }
+    assert_eq!(Literal::typed_null(&DataType::LongType).unwrap(), Literal::null_long());
+
+    let tpe = DataType::struct_type(vec![]).add_field("a", DataType::IntegerType);
+    match Literal::typed_null(&tpe) {
+      Err(CatalystError::Schema(msg)) => {
+        assert_eq!(msg, format!("Cannot create null literal of type {}", tpe));
+      },
+      _ => panic!("Expected schema error")
+    }
+    let tpe = DataType::array_type(DataType::IntegerType, true);
+    assert!(Literal::typed_null(&tpe).is_err());
+    let tpe = DataType::map_type(DataType::StringType, DataType::IntegerType, true);
+    assert!(Literal::typed_null(&tpe).is_err());
+  }
+
+  #[test]
+  #[should_panic(expected = "Cannot create null literal of type array<int>")]
+  fn test_literal_null_not_atomic() {
+    Literal::null(DataType::array_type(DataType::IntegerType, true));
+  }
+
+  #[test]
+  fn test_literal_from_display_roundtrip() {
+    let values = vec![
+      Literal::Boolean(Some(true)),
+      Literal::Boolean(Some(false)),
+      Literal::Byte(Some(-8)),
+      Literal::Short(Some(300)),
+      Literal::Integer(Some(0)),
+      Literal::Integer(Some(i32::MIN)),
+      Literal::Long(Some(i64::MAX)),
+      Literal::Float(Some(1.5)),
+      Literal::Double(Some(2.0)),
+      Literal::Double(Some(-0.1)),
+      Literal::Double(Some(1e-10)),
+      Literal::Double(Some(f64::INFINITY)),
+      Literal::String(Some("".to_owned())),
+      Literal::String(Some("abc".to_owned())),
+      Literal::String(Some("null".to_owned())),
+      Literal::String(Some("it's \"quoted\"\n\t\\ \u{1}".to_owned())),
+      Literal::Binary(Some(vec![])),
+      Literal::Binary(Some(vec![0, 10, 255])),
+      Literal::Integer(None),
+      Literal::Double(None),
+      Literal::String(None),
+      Literal::Binary(None)
+    ];
+    for value in values {
+      let text = value.to_string();
+      assert_eq!(Literal::from_display(&text, value.data_type()), Some(value));
+    }
+  }
+
+  #[test]
+  fn test_literal_from_display_invalid() {
+    assert_eq!(Literal::from_display("abc", &DataType::IntegerType), None);
+    assert_eq!(Literal::from_display("1.5", &DataType::LongType), None);
+    assert_eq!(Literal::from_display("abc", &DataType::StringType), None);
+    assert_eq!(Literal::from_display("\"a\\x\"", &DataType::StringType), None);
+    assert_eq!(Literal::from_display("[1, 256]", &DataType::BinaryType), None);
+    assert_eq!(Literal::from_display("null", &DataType::struct_type(vec![])), None);
+  }
+
+  #[test]
+  fn test_literal_cast() {
+    assert_eq!(Literal::int(1).cast(&DataType::IntegerType), Some(Literal::int(1)));
+    assert_eq!(Literal::int(1).cast(&DataType::LongType), Some(Literal::long(1)));
+    assert_eq!(Literal::long(4294967297).cast(&DataType::IntegerType),
+      Some(Literal::int(1)));
+    assert_eq!(Literal::double(1.7).cast(&DataType::IntegerType), Some(Literal::int(1)));
+    assert_eq!(Literal::int(12).cast(&DataType::StringType), Some(Literal::string("12")));
+    assert_eq!(Literal::boolean(true).cast(&DataType::StringType),
+      Some(Literal::string("true")));
+    assert_eq!(Literal::null_int().cast(&DataType::DoubleType),
+      Some(Literal::null_double()));
+    assert_eq!(Literal::string("1").cast(&DataType::IntegerType), None);
+    assert_eq!(Literal::boolean(true).cast(&DataType::IntegerType), None);
+    assert_eq!(Literal::int(1).cast(&DataType::BooleanType), None);
+  }
+
+  #[test]
+  fn test_literal_display() {
+    assert_eq!(format!("{}", Literal::Boolean(Some(true))), "true");
+    assert_eq!(format!("{}", Literal::Integer(Some(12))), "12");
+    assert_eq!(format!("{}", Literal::Double(Some(1.0))), "1.0");
+    assert_eq!(format!("{}", Literal::String(Some("a".to_owned()))), "\"a\"");
+    assert_eq!(format!("{}", Literal::Long(None)), "null");
+  }
+
+  #[test]
+  fn test_lit_expression() {
+    let exp = lit(Literal::Integer(Some(1)));
+    assert_eq!(exp.node_name(), "LITERAL");
+    assert_eq!(exp.pretty_string(), "1");
+    assert_eq!(exp.data_type(), &DataType::IntegerType);
+    assert!(exp.foldable());
+    assert!(exp.deterministic());
+    assert!(exp.resolved());
+    assert!(!exp.nullable());
+    assert!(lit(Literal::Integer(None)).nullable());
+
+    assert_eq!(exp.eval(&vec![]), Literal::Integer(Some(1)));
+    assert!(exp.clone() == exp);
+    assert!(exp != lit(Literal::Integer(Some(2))));
+    assert!(exp != lit(Literal::Long(Some(1))));
+  }
+
+  #[test]
+  fn test_literal_arithmetic() {
+    let res = Literal::Integer(Some(1)).add(&Literal::Integer(Some(2)));
+    assert_eq!(res, Literal::Integer(Some(3)));
+    let res = Literal::Long(Some(1)).sub(&Literal::Long(Some(2)));
+    assert_eq!(res, Literal::Long(Some(-1)));
+    let res = Literal::Double(Some(1.5)).mul(&Literal::Double(Some(2.0)));
+    assert_eq!(res, Literal::Double(Some(3.0)));
+    let res = Literal::Byte(Some(127)).add(&Literal::Byte(Some(1)));
+    assert_eq!(res, Literal::Byte(Some(-128)));
+    let res = Literal::Integer(None).add(&Literal::Integer(Some(2)));
+    assert_eq!(res, Literal::Integer(None));
+    let res = Literal::Short(Some(1)).mul(&Literal::Short(None));
+    assert_eq!(res, Literal::Short(None));
+  }
+
+  #[test]
+  fn test_literal_neg() {
+    assert_eq!(Literal::int(7).neg(), Literal::int(-7));
+    assert_eq!(Literal::Byte(Some(i8::MIN)).neg(), Literal::Byte(Some(i8::MIN)));
+    assert_eq!(Literal::double(1.5).neg(), Literal::double(-1.5));
+    assert_eq!(Literal::null_long().neg(), Literal::null_long());
+  }
+
+  #[test]
+  #[should_panic(expected = "Cannot negate true")]
+  fn test_literal_neg_not_numeric() {
+    Literal::Boolean(Some(true)).neg();
+  }
+
+  #[test]
+  fn test_literal_abs() {
+    assert_eq!(Literal::int(-7).abs(), Literal::int(7));
+    assert_eq!(Literal::long(7).abs(), Literal::long(7));
+    assert_eq!(Literal::Short(Some(i16::MIN)).abs(), Literal::Short(Some(i16::MIN)));
+    assert_eq!(Literal::double(-1.5).abs(), Literal::double(1.5));
+    assert_eq!(Literal::null_int().abs(), Literal::null_int());
+  }
+
+  #[test]
+  #[should_panic(expected = "Cannot compute absolute value of \"abc\"")]
+  fn test_literal_abs_not_numeric() {
+    Literal::string("abc").abs();
+  }
+
+  #[test]
+  fn test_literal_division() {
+    assert_eq!(Literal::int(7).div(&Literal::int(2)), Literal::int(3));
+    assert_eq!(Literal::int(-7).rem(&Literal::int(2)), Literal::int(-1));
+    assert_eq!(Literal::double(7.0).div(&Literal::double(2.0)), Literal::double(3.5));
+    assert_eq!(Literal::double(7.5).rem(&Literal::double(2.0)), Literal::double(1.5));
+    assert_eq!(Literal::int(i32::MIN).div(&Literal::int(-1)), Literal::int(i32::MIN));
+    // division by zero returns null
+    assert_eq!(Literal::int(1).div(&Literal::int(0)), Literal::null_int());
+    assert_eq!(Literal::long(1).rem(&Literal::long(0)), Literal::null_long());
+    assert_eq!(Literal::double(1.0).div(&Literal::double(0.0)), Literal::null_double());
+    assert_eq!(Literal::null_int().div(&Literal::int(2)), Literal::null_int());
+  }
+
+  #[test]
+  #[should_panic(expected = "Cannot divide 1 and 2")]
+  fn test_literal_division_type_mismatch() {
+    Literal::Integer(Some(1)).div(&Literal::Long(Some(2)));
+  }
+
+  #[test]
+  #[should_panic(expected = "Cannot add 1 and 2")]
+  fn test_literal_arithmetic_type_mismatch() {
+    Literal::Integer(Some(1)).add(&Literal::Long(Some(2)));
+  }
+
+  #[test]
+  fn test_literal_overflow_mode() {
+    let max = Literal::Integer(Some(i32::MAX));
+    let one = Literal::Integer(Some(1));
+    assert_eq!(max.try_add(&one, OverflowMode::Wrap).unwrap(),
+      Literal::Integer(Some(i32::MIN)));
+    assert_eq!(max.try_add(&one, OverflowMode::Null).unwrap(), Literal::Integer(None));
+    match max.try_add(&one, OverflowMode::Error) {
+      Err(CatalystError::Eval(msg)) => {
+        assert_eq!(msg, "Overflow when trying to add 2147483647 and 1")
+      },
+      _ => panic!("Expected eval error")
+    }
+
+    let min = Literal::Long(Some(i64::MIN));
+    assert_eq!(min.try_sub(&Literal::Long(Some(1)), OverflowMode::Wrap).unwrap(),
+      Literal::Long(Some(i64::MAX)));
+    let byte = Literal::Byte(Some(64));
+    assert_eq!(byte.try_mul(&Literal::Byte(Some(2)), OverflowMode::Null).unwrap(),
+      Literal::Byte(None));
+    assert!(Literal::Short(Some(i16::MAX))
+      .try_mul(&Literal::Short(Some(2)), OverflowMode::Error).is_err());
+  }
+
+  #[test]
+  fn test_literal_checked_arithmetic() {
+    for mode in [OverflowMode::Wrap, OverflowMode::Null, OverflowMode::Error] {
+      let (one, two) = (Literal::int(1), Literal::int(2));
+      assert_eq!(one.try_add(&two, mode).unwrap(), Literal::int(3));
+      assert_eq!(one.try_sub(&two, mode).unwrap(), Literal::int(-1));
+      assert_eq!(two.try_mul(&two, mode).unwrap(), Literal::int(4));
+      assert_eq!(Literal::null_int().try_add(&Literal::int(2), mode).unwrap(),
+        Literal::null_int());
+      assert_eq!(Literal::double(1.5).try_add(&Literal::double(2.0), mode).unwrap(),
+        Literal::double(3.5));
+    }
+  }
+
+  #[test]
+  fn test_literal_checked_arithmetic_type_mismatch() {
+    match Literal::int(1).try_add(&Literal::Long(Some(2)), OverflowMode::Wrap) {
+      Err(CatalystError::Eval(msg)) => assert_eq!(msg, "Cannot add 1 and 2"),
+      _ => panic!("Expected eval error")
+    }
+    let string = Literal::String(Some("a".to_owned()));
+    match string.try_mul(&Literal::int(2), OverflowMode::Null) {
+      Err(CatalystError::Eval(msg)) => assert_eq!(msg, "Cannot multiply \"a\" and 2"),
+      _ => panic!("Expected eval error")
+    }
+  }
+
+  #[test]
+  fn test_literal_compare() {
+    let res = Literal::Integer(Some(1)).compare(&Literal::Integer(Some(2)));
+    assert_eq!(res, Some(Ordering::Less));
+    let a = Literal::String(Some("a".to_owned()));
+    let b = Literal::String(Some("b".to_owned()));
+
+    let res = b.compare(&a);
+    assert_eq!(res, Some(Ordering::Greater));
+    let res = Literal::Double(Some(1.0)).compare(&Literal::Double(Some(1.0)));
+    assert_eq!(res, Some(Ordering::Equal));
+    let res = Literal::Double(Some(1.0)).compare(&Literal::Double(None));
+    assert_eq!(res, None);
+    let res = Literal::Boolean(Some(false)).compare(&Literal::Boolean(Some(true)));
+    assert_eq!(res, Some(Ordering::Less));
+  }
+
+  #[test]
+  fn test_literal_to_sql() {
+    assert_eq!(Literal::Boolean(Some(true)).to_sql(), "TRUE");
+    assert_eq!(Literal::Byte(Some(1)).to_sql(), "1Y");
+    assert_eq!(Literal::Short(Some(-2)).to_sql(), "-2S");
+    assert_eq!(Literal::Integer(Some(3)).to_sql(), "3");
+    assert_eq!(Literal::Long(Some(4)).to_sql(), "4L");
+    assert_eq!(Literal::Float(Some(1.5)).to_sql(), "CAST('1.5' AS FLOAT)");
+    assert_eq!(Literal::Double(Some(2.0)).to_sql(), "2.0D");
+    assert_eq!(Literal::Double(Some(f64::NAN)).to_sql(), "CAST('NaN' AS DOUBLE)");
+    assert_eq!(Literal::Double(Some(f64::INFINITY)).to_sql(),
+      "CAST('Infinity' AS DOUBLE)");
+    assert_eq!(Literal::Double(Some(f64::NEG_INFINITY)).to_sql(),
+      "CAST('-Infinity' AS DOUBLE)");
+    assert_eq!(Literal::Float(Some(f32::NAN)).to_sql(), "CAST('NaN' AS FLOAT)");
+    assert_eq!(Literal::Float(Some(f32::INFINITY)).to_sql(), "CAST('Infinity' AS FLOAT)");
+    assert_eq!(Literal::Float(Some(f32::NEG_INFINITY)).to_sql(),
+      "CAST('-Infinity' AS FLOAT)");
+    assert_eq!(Literal::Integer(None).to_sql(), "NULL");
+    assert_eq!(Literal::String(None).to_sql(), "NULL");
+  }
+
+  #[test]
+  fn test_literal_binary_to_sql() {
+    assert_eq!(Literal::Binary(Some(vec![0x0A, 0x1B])).to_sql(), "X'0A1B'");
+    assert_eq!(Literal::Binary(Some(vec![0x00, 0xFF])).to_sql(), "X'00FF'");
+    assert_eq!(Literal::Binary(Some(vec![])).to_sql(), "X''");
+  }
+
+  #[test]
+  fn test_literal_string_to_sql() {
+    assert_eq!(Literal::String(Some("abc".to_owned())).to_sql(), "'abc'");
+    assert_eq!(Literal::String(Some("".to_owned())).to_sql(), "''");
+    assert_eq!(Literal::String(Some("it's".to_owned())).to_sql(), "'it''s'");
+    assert_eq!(Literal::String(Some("a\\b".to_owned())).to_sql(), "'a\\\\b'");
+    assert_eq!(Literal::String(Some("a\nb\tc".to_owned())).to_sql(), "'a\\nb\\tc'");
+  }
 }
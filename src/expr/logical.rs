@@ -14,7 +14,11 @@
 
 //! Logical expressions.
 
-use expr::api::{Expression, ExpressionBuilder, binary, unary};
+use std::cmp::Ordering;
+
+use expr::api::{Expression, ExpressionBuilder, Row, binary, unary};
+use expr::literal::Literal;
+use trees::TreeNode;
 use types::DataType;
 
 /// Returns builder for logical binary expression.
@@ -35,9 +39,97 @@ fn logical_unary(name: &str, symbol: &str, child: Expression) -> ExpressionBuild
     .datatype(Box::new(|_| &DataType::BooleanType))
 }
 
+/// Returns builder for comparison of left and right expressions, `func` is evaluated on
+/// the ordering of non-null values.
+///
+/// Comparison is resolved only if types of both sides can be coerced to a common type.
+fn comparison(
+  name: &str,
+  symbol: &str,
+  left: Expression,
+  right: Expression,
+  func: fn(Ordering) -> bool) ->
+ExpressionBuilder
+{
+  logical_binary(name, symbol, left, right)
+    .null_intolerant(true)
+    .resolved(Box::new(|exp| {
+      let left = &exp.children()[0];
+      let right = &exp.children()[1];
+      left.resolved() && right.resolved() &&
+        left.data_type().coerce_to_common(right.data_type()).is_some()
+    }))
+    .eval(Box::new(move |exp, row| {
+      let left = exp.children()[0].eval(row);
+      let right = exp.children()[1].eval(row);
+      Literal::Boolean(compare_common(&left, &right).map(func))
+    }))
+}
+
+/// Internal method to compare two values after casting both of them to their common
+/// type, e.g. `int` and `long` are compared as `long` values.
+fn compare_common(left: &Literal, right: &Literal) -> Option<Ordering> {
+  match left.data_type().coerce_to_common(right.data_type()) {
+    Some(ref data_type) => match (left.cast(data_type), right.cast(data_type)) {
+      (Some(left), Some(right)) => left.compare(&right),
+      _ => left.compare(right)
+    },
+    None => left.compare(right)
+  }
+}
+
+/// Internal method to check if all children are resolved boolean expressions.
+fn boolean_children(exp: &Expression) -> bool {
+  exp.children().iter()
+    .all(|child| child.resolved() && child.data_type() == &DataType::BooleanType)
+}
+
+/// Internal method to evaluate expression into a nullable boolean value.
+fn eval_boolean(exp: &Expression, row: &Row) -> Option<bool> {
+  match exp.eval(row) {
+    Literal::Boolean(value) => value,
+    other => panic!("Expected boolean value, found {}", other)
+  }
+}
+
+/// Left == right.
+pub fn eq(left: Expression, right: Expression) -> Expression {
+  comparison("EQUAL", "==", left, right, |ord| ord == Ordering::Equal)
+    .commutative(true)
+    .sql(Box::new(|_, children| {
+      format!("({} = {})", children[0], children[1])
+    }))
+    .clone(Box::new(|exp| {
+      eq(exp.children()[0].clone(), exp.children()[1].clone())
+    }))
+    .build()
+}
+
+/// Left <=> right, same as `eq`, but returns `true` if both sides are null and `false`
+/// if only one side is null, so result is never null.
+pub fn eq_null_safe(left: Expression, right: Expression) -> Expression {
+  comparison("EQUAL_NULL_SAFE", "<=>", left, right, |ord| ord == Ordering::Equal)
+    .commutative(true)
+    .null_intolerant(false)
+    .nullable(Box::new(|_| false))
+    .eval(Box::new(|exp, row| {
+      let left = exp.children()[0].eval(row);
+      let right = exp.children()[1].eval(row);
+      if left.is_null() || right.is_null() {
+        Literal::Boolean(Some(left.is_null() && right.is_null()))
+      } else {
+        Literal::Boolean(compare_common(&left, &right).map(|ord| ord == Ordering::Equal))
+      }
+    }))
+    .clone(Box::new(|exp| {
+      eq_null_safe(exp.children()[0].clone(), exp.children()[1].clone())
+    }))
+    .build()
+}
+
 /// Left > right.
 pub fn gt(left: Expression, right: Expression) -> Expression {
-  logical_binary("GREATER_THAN", ">", left, right)
+  comparison("GREATER_THAN", ">", left, right, |ord| ord == Ordering::Greater)
     .clone(Box::new(|exp| {
       gt(exp.children()[0].clone(), exp.children()[1].clone())
     }))
@@ -46,7 +138,7 @@ pub fn gt(left: Expression, right: Expression) -> Expression {
 
 /// Left >= right.
 pub fn ge(left: Expression, right: Expression) -> Expression {
-  logical_binary("GREATER_OR_EQUAL", ">=", left, right)
+  comparison("GREATER_OR_EQUAL", ">=", left, right, |ord| ord != Ordering::Less)
     .clone(Box::new(|exp| {
       ge(exp.children()[0].clone(), exp.children()[1].clone())
     }))
@@ -55,7 +147,7 @@ pub fn ge(left: Expression, right: Expression) -> Expression {
 
 /// Left < right.
 pub fn lt(left: Expression, right: Expression) -> Expression {
-  logical_binary("LESS_THAN", "<", left, right)
+  comparison("LESS_THAN", "<", left, right, |ord| ord == Ordering::Less)
     .clone(Box::new(|exp| {
       lt(exp.children()[0].clone(), exp.children()[1].clone())
     }))
@@ -64,7 +156,7 @@ pub fn lt(left: Expression, right: Expression) -> Expression {
 
 /// Left <= right.
 pub fn le(left: Expression, right: Expression) -> Expression {
-  logical_binary("LESS_OR_EQUAL", "<=", left, right)
+  comparison("LESS_OR_EQUAL", "<=", left, right, |ord| ord != Ordering::Greater)
     .clone(Box::new(|exp| {
       le(exp.children()[0].clone(), exp.children()[1].clone())
     }))
@@ -72,8 +164,27 @@ pub fn le(left: Expression, right: Expression) -> Expression {
 }
 
 /// Left && right.
+///
+/// Right side is not evaluated when left side is `false`.
 pub fn and(left: Expression, right: Expression) -> Expression {
   logical_binary("AND", "&&", left, right)
+    .commutative(true)
+    .sql(Box::new(|_, children| {
+      format!("({} AND {})", children[0], children[1])
+    }))
+    .resolved(Box::new(boolean_children))
+    .eval(Box::new(|exp, row| {
+      let left = eval_boolean(&exp.children()[0], row);
+      if left == Some(false) {
+        return Literal::Boolean(Some(false));
+      }
+      let right = eval_boolean(&exp.children()[1], row);
+      match (left, right) {
+        (_, Some(false)) => Literal::Boolean(Some(false)),
+        (Some(true), Some(true)) => Literal::Boolean(Some(true)),
+        _ => Literal::Boolean(None)
+      }
+    }))
     .clone(Box::new(|exp| {
       and(exp.children()[0].clone(), exp.children()[1].clone())
     }))
@@ -81,8 +192,27 @@ pub fn and(left: Expression, right: Expression) -> Expression {
 }
 
 /// Left || right.
+///
+/// Right side is not evaluated when left side is `true`.
 pub fn or(left: Expression, right: Expression) -> Expression {
   logical_binary("OR", "||", left, right)
+    .commutative(true)
+    .sql(Box::new(|_, children| {
+      format!("({} OR {})", children[0], children[1])
+    }))
+    .resolved(Box::new(boolean_children))
+    .eval(Box::new(|exp, row| {
+      let left = eval_boolean(&exp.children()[0], row);
+      if left == Some(true) {
+        return Literal::Boolean(Some(true));
+      }
+      let right = eval_boolean(&exp.children()[1], row);
+      match (left, right) {
+        (_, Some(true)) => Literal::Boolean(Some(true)),
+        (Some(false), Some(false)) => Literal::Boolean(Some(false)),
+        _ => Literal::Boolean(None)
+      }
+    }))
     .clone(Box::new(|exp| {
       or(exp.children()[0].clone(), exp.children()[1].clone())
     }))
@@ -92,6 +222,14 @@ pub fn or(left: Expression, right: Expression) -> Expression {
 /// Negation
 pub fn not(child: Expression) -> Expression {
   logical_unary("NOT", "!", child)
+    .null_intolerant(true)
+    .sql(Box::new(|_, children| {
+      format!("(NOT {})", children[0])
+    }))
+    .resolved(Box::new(boolean_children))
+    .eval(Box::new(|exp, row| {
+      Literal::Boolean(eval_boolean(&exp.children()[0], row).map(|value| !value))
+    }))
     .clone(Box::new(|exp| {
       not(exp.children()[0].clone())
     }))
@@ -101,11 +239,325 @@ pub fn not(child: Expression) -> Expression {
 /// Is null
 pub fn is_null(child: Expression) -> Expression {
   logical_unary("IS_NULL", "", child)
-    .display(Box::new(|exp| {
-      format!("({} is null)", exp.children()[0].pretty_string())
+    .display(Box::new(|_, children| {
+      format!("({} is null)", children[0])
+    }))
+    .sql(Box::new(|_, children| {
+      format!("({} IS NULL)", children[0])
+    }))
+    .eval(Box::new(|exp, row| {
+      Literal::Boolean(Some(exp.children()[0].eval(row).is_null()))
     }))
     .clone(Box::new(|exp| {
       is_null(exp.children()[0].clone())
     }))
     .build()
 }
+
+/// Is not null
+pub fn is_not_null(child: Expression) -> Expression {
+  logical_unary("IS_NOT_NULL", "", child)
+    .display(Box::new(|_, children| {
+      format!("({} is not null)", children[0])
+    }))
+    .sql(Box::new(|_, children| {
+      format!("({} IS NOT NULL)", children[0])
+    }))
+    .eval(Box::new(|exp, row| {
+      Literal::Boolean(Some(!exp.children()[0].eval(row).is_null()))
+    }))
+    .nullable(Box::new(|_| false))
+    .clone(Box::new(|exp| {
+      is_not_null(exp.children()[0].clone())
+    }))
+    .build()
+}
+
+/// Value is equal to any of the expressions in the list, e.g. `a IN (1, 2)`.
+///
+/// Evaluates to null if value is null, or if no match is found and the list contains
+/// null.
+pub fn in_list(value: Expression, list: Vec<Expression>) -> Expression {
+  let mut children = vec![value];
+  children.extend(list);
+  ExpressionBuilder::new("IN".to_owned())
+    .children(children)
+    .display(Box::new(|_, children| {
+      format!("({} IN ({}))", children[0], children[1..].join(", "))
+    }))
+    .foldable(Box::new(|exp| {
+      exp.children().iter().all(|child| child.foldable())
+    }))
+    .deterministic(Box::new(|exp| {
+      exp.children().iter().all(|child| child.deterministic())
+    }))
+    .nullable(Box::new(|exp| {
+      exp.children().iter().any(|child| child.nullable())
+    }))
+    .resolved(Box::new(|exp| {
+      let value = &exp.children()[0];
+      exp.children().iter().all(|child| child.resolved()) &&
+        exp.children()[1..].iter()
+          .all(|child| value.data_type().coerce_to_common(child.data_type()).is_some())
+    }))
+    .datatype(Box::new(|_| &DataType::BooleanType))
+    .eval(Box::new(|exp, row| {
+      let value = exp.children()[0].eval(row);
+      if value.is_null() {
+        return Literal::Boolean(None);
+      }
+      let mut has_null = false;
+      for child in &exp.children()[1..] {
+        match compare_common(&value, &child.eval(row)) {
+          Some(Ordering::Equal) => return Literal::Boolean(Some(true)),
+          Some(_) => {},
+          None => has_null = true
+        }
+      }
+      Literal::Boolean(if has_null { None } else { Some(false) })
+    }))
+    .clone(Box::new(|exp| {
+      in_list(exp.children()[0].clone(), exp.children()[1..].to_vec())
+    }))
+    .eq(Box::new(|a, b| {
+      a.node_name() == b.node_name() && a.children() == b.children()
+    }))
+    .build()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use expr::literal::lit;
+  use expr::reference::reference;
+
+  fn int(value: Option<i32>) -> Expression {
+    lit(Literal::Integer(value))
+  }
+
+  fn boolean(value: Option<bool>) -> Expression {
+    lit(Literal::Boolean(value))
+  }
+
+  fn eval(exp: Expression) -> Literal {
+    exp.eval(&vec![])
+  }
+
+  #[test]
+  fn test_comparison_eval() {
+    assert_eq!(eval(gt(int(Some(2)), int(Some(1)))), Literal::Boolean(Some(true)));
+    assert_eq!(eval(gt(int(Some(1)), int(Some(1)))), Literal::Boolean(Some(false)));
+    assert_eq!(eval(ge(int(Some(1)), int(Some(1)))), Literal::Boolean(Some(true)));
+    assert_eq!(eval(lt(int(Some(1)), int(Some(2)))), Literal::Boolean(Some(true)));
+    assert_eq!(eval(le(int(Some(2)), int(Some(1)))), Literal::Boolean(Some(false)));
+    assert_eq!(eval(gt(int(None), int(Some(1)))), Literal::Boolean(None));
+    assert_eq!(eval(le(int(Some(1)), int(None))), Literal::Boolean(None));
+  }
+
+  #[test]
+  fn test_comparison_eval_mixed_types() {
+    let long = |value| lit(Literal::Long(value));
+    let double = |value| lit(Literal::Double(value));
+    let exp = gt(int(Some(1)), long(Some(2)));
+    assert!(exp.resolved());
+    assert_eq!(eval(exp), Literal::Boolean(Some(false)));
+    assert_eq!(eval(lt(int(Some(1)), long(Some(2)))), Literal::Boolean(Some(true)));
+    assert_eq!(eval(eq(long(Some(3)), int(Some(3)))), Literal::Boolean(Some(true)));
+    assert_eq!(eval(ge(int(Some(2)), double(Some(1.5)))), Literal::Boolean(Some(true)));
+    assert_eq!(eval(le(double(Some(2.5)), int(Some(2)))), Literal::Boolean(Some(false)));
+    assert_eq!(eval(gt(int(None), long(Some(1)))), Literal::Boolean(None));
+    assert_eq!(eval(eq_null_safe(int(Some(1)), long(Some(1)))),
+      Literal::Boolean(Some(true)));
+    assert_eq!(eval(eq_null_safe(int(None), long(None))), Literal::Boolean(Some(true)));
+
+    let exp = lt(int(Some(1)), long(Some(2))).partial_eval();
+    assert!(exp == lit(Literal::Boolean(Some(true))));
+  }
+
+  #[test]
+  fn test_eq() {
+    assert_eq!(eval(eq(int(Some(1)), int(Some(1)))), Literal::Boolean(Some(true)));
+    assert_eq!(eval(eq(int(Some(1)), int(Some(2)))), Literal::Boolean(Some(false)));
+    assert_eq!(eval(eq(int(None), int(Some(2)))), Literal::Boolean(None));
+    assert_eq!(eq(reference("a"), int(Some(1))).pretty_string(), "('a == 1)");
+    assert_eq!(eq(reference("a"), reference("b")).pretty_string(), "('a == 'b)");
+    assert!(eq(int(Some(1)), int(None)).nullable());
+    assert!(!eq(int(Some(1)), int(Some(2))).nullable());
+  }
+
+  #[test]
+  fn test_eq_null_safe() {
+    let exp = eq_null_safe(int(Some(1)), int(None));
+    assert_eq!(exp.node_name(), "EQUAL_NULL_SAFE");
+    assert!(!exp.nullable());
+    assert!(exp.resolved());
+    assert!(exp == exp.clone());
+    assert_eq!(exp.data_type(), &DataType::BooleanType);
+    assert_eq!(eval(exp), Literal::Boolean(Some(false)));
+    assert_eq!(eval(eq_null_safe(int(None), int(None))), Literal::Boolean(Some(true)));
+    assert_eq!(eval(eq_null_safe(int(Some(1)), int(Some(1)))),
+      Literal::Boolean(Some(true)));
+    assert_eq!(eval(eq_null_safe(int(Some(1)), int(Some(2)))),
+      Literal::Boolean(Some(false)));
+    let exp = eq_null_safe(reference("a"), reference("b"));
+    assert_eq!(exp.pretty_string(), "('a <=> 'b)");
+    assert!(!eq_null_safe(reference("a"), int(Some(1))).resolved());
+    assert!(!eq_null_safe(boolean(Some(true)), int(Some(1))).resolved());
+  }
+
+  #[test]
+  fn test_in_list() {
+    let exp = in_list(int(Some(2)), vec![int(Some(1)), int(Some(2))]);
+    assert_eq!(exp.pretty_string(), "(2 IN (1, 2))");
+    assert!(!exp.nullable());
+    assert_eq!(exp.data_type(), &DataType::BooleanType);
+    assert!(exp.resolved());
+    assert!(exp.foldable());
+    assert!(exp == exp.clone());
+    assert_eq!(eval(exp), Literal::Boolean(Some(true)));
+
+    assert_eq!(eval(in_list(int(Some(3)), vec![int(Some(1)), int(Some(2))])),
+      Literal::Boolean(Some(false)));
+    assert_eq!(eval(in_list(int(Some(3)), vec![int(Some(1)), int(None)])),
+      Literal::Boolean(None));
+    assert_eq!(eval(in_list(int(Some(1)), vec![int(None), int(Some(1))])),
+      Literal::Boolean(Some(true)));
+    assert_eq!(eval(in_list(int(None), vec![int(Some(1))])), Literal::Boolean(None));
+    assert_eq!(eval(in_list(int(Some(1)), vec![])), Literal::Boolean(Some(false)));
+
+    let long = |value| lit(Literal::Long(value));
+    let exp = in_list(int(Some(1)), vec![long(Some(1))]);
+    assert!(exp.resolved());
+    assert_eq!(eval(exp.clone()), Literal::Boolean(Some(true)));
+    assert!(exp.partial_eval() == lit(Literal::Boolean(Some(true))));
+    let exp = in_list(long(Some(2)), vec![int(Some(1)), lit(Literal::Double(Some(2.0)))]);
+    assert_eq!(eval(exp), Literal::Boolean(Some(true)));
+    assert_eq!(eval(in_list(int(Some(3)), vec![long(Some(1)), long(None)])),
+      Literal::Boolean(None));
+
+    assert!(!in_list(int(Some(1)), vec![lit(Literal::string("a"))]).resolved());
+    assert!(!in_list(reference("a"), vec![int(Some(1))]).resolved());
+  }
+
+  #[test]
+  fn test_in_list_folding_null_semantics() {
+    let fold = |exp: Expression| exp.partial_eval().literal().cloned();
+    assert_eq!(fold(in_list(int(None), vec![int(Some(1))])),
+      Some(Literal::Boolean(None)));
+    assert_eq!(fold(in_list(int(Some(1)), vec![int(None), int(Some(2))])),
+      Some(Literal::Boolean(None)));
+    assert_eq!(fold(in_list(int(Some(1)), vec![int(Some(1)), int(None)])),
+      Some(Literal::Boolean(Some(true))));
+    assert_eq!(fold(in_list(int(Some(1)), vec![int(Some(2)), int(Some(3))])),
+      Some(Literal::Boolean(Some(false))));
+    assert_eq!(fold(in_list(reference("a"), vec![int(None)])), None);
+  }
+
+  #[test]
+  fn test_in_list_display_and_resolution() {
+    let exp = in_list(reference("a"), vec![int(Some(1)), int(Some(2)), int(Some(3))]);
+    assert_eq!(exp.pretty_string(), "('a IN (1, 2, 3))");
+    assert_eq!(exp.num_children(), 4);
+    assert_eq!(in_list(int(Some(1)), vec![]).pretty_string(), "(1 IN ())");
+
+    let long = lit(Literal::Long(Some(2)));
+    assert!(in_list(int(Some(1)), vec![int(Some(1)), long]).resolved());
+    let exp = in_list(int(Some(1)), vec![int(Some(2)), boolean(Some(true))]);
+    assert!(!exp.resolved());
+    assert_eq!(exp.data_type(), &DataType::BooleanType);
+
+    assert!(in_list(int(None), vec![int(Some(1))]).nullable());
+    assert!(in_list(int(Some(1)), vec![int(Some(2)), int(None)]).nullable());
+  }
+
+  #[test]
+  fn test_comparison_resolved() {
+    let long = lit(Literal::Long(Some(1)));
+    let string = lit(Literal::String(Some("a".to_owned())));
+    assert!(gt(int(Some(1)), long).resolved());
+    assert!(le(int(Some(1)), int(None)).resolved());
+    assert!(!gt(string.clone(), int(Some(1))).resolved());
+    assert!(!lt(boolean(Some(true)), int(Some(1))).resolved());
+    assert!(!ge(reference("a"), int(Some(1))).resolved());
+    assert_eq!(gt(string, int(Some(1))).data_type(), &DataType::BooleanType);
+  }
+
+  #[test]
+  fn test_logical_eval() {
+    assert_eq!(eval(and(boolean(Some(true)), boolean(Some(true)))),
+      Literal::Boolean(Some(true)));
+    assert_eq!(eval(and(boolean(Some(true)), boolean(Some(false)))),
+      Literal::Boolean(Some(false)));
+    assert_eq!(eval(or(boolean(Some(false)), boolean(Some(true)))),
+      Literal::Boolean(Some(true)));
+    assert_eq!(eval(or(boolean(Some(false)), boolean(Some(false)))),
+      Literal::Boolean(Some(false)));
+    assert_eq!(eval(not(boolean(Some(false)))), Literal::Boolean(Some(true)));
+    assert_eq!(eval(not(boolean(None))), Literal::Boolean(None));
+  }
+
+  #[test]
+  fn test_logical_resolved() {
+    assert!(and(boolean(Some(true)), boolean(None)).resolved());
+    assert!(or(boolean(Some(true)), gt(int(Some(1)), int(Some(2)))).resolved());
+    assert!(not(boolean(Some(true))).resolved());
+    assert!(!and(int(Some(1)), boolean(Some(true))).resolved());
+    assert!(!or(boolean(Some(true)), int(Some(1))).resolved());
+    assert!(!not(int(Some(1))).resolved());
+    assert!(!and(boolean(Some(true)), reference("a")).resolved());
+  }
+
+  #[test]
+  fn test_logical_eval_three_valued() {
+    let (t, f, n) = (Some(true), Some(false), None);
+    assert_eq!(eval(and(boolean(f), boolean(n))), Literal::Boolean(f));
+    assert_eq!(eval(and(boolean(n), boolean(f))), Literal::Boolean(f));
+    assert_eq!(eval(and(boolean(n), boolean(t))), Literal::Boolean(n));
+    assert_eq!(eval(and(boolean(t), boolean(n))), Literal::Boolean(n));
+    assert_eq!(eval(and(boolean(n), boolean(n))), Literal::Boolean(n));
+    assert_eq!(eval(or(boolean(t), boolean(n))), Literal::Boolean(t));
+    assert_eq!(eval(or(boolean(n), boolean(t))), Literal::Boolean(t));
+    assert_eq!(eval(or(boolean(n), boolean(f))), Literal::Boolean(n));
+    assert_eq!(eval(or(boolean(f), boolean(n))), Literal::Boolean(n));
+    assert_eq!(eval(or(boolean(n), boolean(n))), Literal::Boolean(n));
+  }
+
+  #[test]
+  fn test_logical_eval_short_circuit() {
+    let (t, f) = (Some(true), Some(false));
+    // evaluating unbound reference panics, so right side must not be evaluated
+    assert_eq!(eval(and(boolean(f), reference("a"))), Literal::Boolean(f));
+    assert_eq!(eval(or(boolean(t), reference("a"))), Literal::Boolean(t));
+  }
+
+  #[test]
+  #[should_panic(expected = "Cannot evaluate unbound reference a")]
+  fn test_logical_eval_no_short_circuit() {
+    eval(and(boolean(None), reference("a")));
+  }
+
+  #[test]
+  fn test_null_check_eval() {
+    assert_eq!(eval(is_null(int(None))), Literal::Boolean(Some(true)));
+    assert_eq!(eval(is_null(int(Some(1)))), Literal::Boolean(Some(false)));
+    assert_eq!(eval(is_not_null(int(None))), Literal::Boolean(Some(false)));
+    assert_eq!(eval(is_not_null(int(Some(1)))), Literal::Boolean(Some(true)));
+  }
+
+  #[test]
+  fn test_is_not_null() {
+    let exp = is_not_null(int(None));
+    assert_eq!(exp.pretty_string(), "(null is not null)");
+    assert_eq!(exp.node_name(), "IS_NOT_NULL");
+    assert_eq!(exp.data_type(), &DataType::BooleanType);
+    assert!(!exp.nullable());
+    assert!(exp.foldable());
+    assert!(exp == exp.clone());
+
+    let exp = is_not_null(reference("a"));
+    assert_eq!(exp.pretty_string(), "('a is not null)");
+    assert!(!exp.nullable());
+    assert!(!exp.foldable());
+    assert!(!is_not_null(int(Some(1))).nullable());
+  }
+}
@@ -0,0 +1,148 @@
+// Copyright 2017 sadikovi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Mathematical functions.
+
+use expr::api::{Expression, unary};
+use expr::literal::Literal;
+use types::DataType;
+
+/// Returns absolute value of numeric child expression, e.g. `abs(a)`.
+pub fn abs(child: Expression) -> Expression {
+  unary("ABS".to_owned(), "abs".to_owned(), child)
+    .null_intolerant(true)
+    .display(Box::new(|_, children| {
+      format!("abs({})", children[0])
+    }))
+    .resolved(Box::new(|exp| {
+      let child = &exp.children()[0];
+      child.resolved() && child.data_type().is_numeric()
+    }))
+    .eval(Box::new(|exp, row| {
+      exp.children()[0].eval(row).abs()
+    }))
+    .clone(Box::new(|exp| {
+      abs(exp.children()[0].clone())
+    }))
+    .build()
+}
+
+/// Returns square root of numeric child expression as double, e.g. `sqrt(a)`. Result is
+/// null if child value is null or negative.
+pub fn sqrt(child: Expression) -> Expression {
+  unary("SQRT".to_owned(), "sqrt".to_owned(), child)
+    .null_intolerant(true)
+    .display(Box::new(|_, children| {
+      format!("sqrt({})", children[0])
+    }))
+    .nullable(Box::new(|_| true))
+    .resolved(Box::new(|exp| {
+      let child = &exp.children()[0];
+      child.resolved() && child.data_type().is_numeric()
+    }))
+    .datatype(Box::new(|_| &DataType::DoubleType))
+    .eval(Box::new(|exp, row| {
+      match exp.children()[0].eval(row).cast(&DataType::DoubleType) {
+        Some(Literal::Double(Some(value))) if value >= 0.0 => {
+          Literal::Double(Some(value.sqrt()))
+        },
+        _ => Literal::Double(None)
+      }
+    }))
+    .clone(Box::new(|exp| {
+      sqrt(exp.children()[0].clone())
+    }))
+    .build()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use expr::api::Row;
+  use expr::arithmetic::sub;
+  use expr::literal::lit;
+  use expr::reference::reference;
+  use trees::TreeNode;
+
+  fn int(value: Option<i32>) -> Expression {
+    lit(Literal::Integer(value))
+  }
+
+  #[test]
+  fn test_abs() {
+    let exp = abs(int(Some(-5)));
+    assert_eq!(exp.pretty_string(), "abs(-5)");
+    assert_eq!(exp.node_name(), "ABS");
+    assert!(exp.foldable());
+    assert!(exp.resolved());
+    assert!(!exp.nullable());
+    assert_eq!(exp.data_type(), &DataType::IntegerType);
+    assert!(exp == exp.clone());
+    assert_eq!(exp.eval(&Row::new()), Literal::Integer(Some(5)));
+
+    let exp = abs(lit(Literal::double(-2.5)));
+    assert_eq!(exp.pretty_string(), "abs(-2.5)");
+    assert_eq!(exp.data_type(), &DataType::DoubleType);
+    assert_eq!(exp.eval(&Row::new()), Literal::double(2.5));
+
+    let exp = abs(int(None));
+    assert!(exp.nullable());
+    assert_eq!(exp.eval(&Row::new()), Literal::Integer(None));
+  }
+
+  #[test]
+  fn test_abs_not_numeric() {
+    let exp = abs(lit(Literal::string("abc")));
+    assert_eq!(exp.pretty_string(), "abs(\"abc\")");
+    assert!(!exp.resolved());
+    assert!(!abs(lit(Literal::Boolean(Some(true)))).resolved());
+    assert!(!abs(reference("a")).resolved());
+  }
+
+  #[test]
+  fn test_sqrt() {
+    let exp = sqrt(lit(Literal::double(4.0)));
+    assert_eq!(exp.pretty_string(), "sqrt(4.0)");
+    assert_eq!(exp.node_name(), "SQRT");
+    assert!(exp.foldable());
+    assert!(exp.deterministic());
+    assert!(exp.resolved());
+    assert!(exp.nullable());
+    assert_eq!(exp.data_type(), &DataType::DoubleType);
+    assert!(exp == exp.clone());
+    assert_eq!(exp.eval(&Row::new()), Literal::Double(Some(2.0)));
+
+    let exp = sqrt(int(Some(9)));
+    assert_eq!(exp.data_type(), &DataType::DoubleType);
+    assert_eq!(exp.eval(&Row::new()), Literal::Double(Some(3.0)));
+
+    assert_eq!(sqrt(int(Some(-4))).eval(&Row::new()), Literal::Double(None));
+    assert_eq!(sqrt(int(None)).eval(&Row::new()), Literal::Double(None));
+    assert!(!sqrt(lit(Literal::string("4"))).resolved());
+    assert!(!sqrt(reference("a")).foldable());
+  }
+
+  #[test]
+  fn test_math_constant_folding() {
+    let res = abs(int(Some(-5))).partial_eval();
+    assert_eq!(res.literal(), Some(&Literal::Integer(Some(5))));
+    let res = sqrt(lit(Literal::double(4.0))).partial_eval();
+    assert_eq!(res.literal(), Some(&Literal::Double(Some(2.0))));
+    let res = sqrt(sub(int(Some(1)), int(Some(5)))).partial_eval();
+    assert_eq!(res.literal(), Some(&Literal::Double(None)));
+
+    let exp = sqrt(abs(reference("a")));
+    assert_eq!(exp.partial_eval().pretty_string(), "sqrt(abs('a))");
+  }
+}
@@ -14,8 +14,45 @@
 
 //! Module defines base expression trait and implementations.
 
+/// Creates literal expression for the nullable value of the type, e.g.
+/// `lit![Some(1), i32]` or `lit![None, str]`.
+#[cfg(test)]
+macro_rules! lit {
+  ($value:expr, bool) => (
+    $crate::expr::literal::lit($crate::expr::literal::Literal::Boolean($value))
+  );
+  ($value:expr, i8) => (
+    $crate::expr::literal::lit($crate::expr::literal::Literal::Byte($value))
+  );
+  ($value:expr, i16) => (
+    $crate::expr::literal::lit($crate::expr::literal::Literal::Short($value))
+  );
+  ($value:expr, i32) => (
+    $crate::expr::literal::lit($crate::expr::literal::Literal::Integer($value))
+  );
+  ($value:expr, i64) => (
+    $crate::expr::literal::lit($crate::expr::literal::Literal::Long($value))
+  );
+  ($value:expr, f32) => (
+    $crate::expr::literal::lit($crate::expr::literal::Literal::Float($value))
+  );
+  ($value:expr, f64) => (
+    $crate::expr::literal::lit($crate::expr::literal::Literal::Double($value))
+  );
+  ($value:expr, str) => (
+    $crate::expr::literal::lit(
+      $crate::expr::literal::Literal::String($value.map(|v: &str| v.to_owned())))
+  );
+}
+
 pub mod api;
 pub mod literal;
 pub mod arithmetic;
+pub mod complex;
+pub mod cast;
 pub mod logical;
+pub mod math;
+pub mod optimize;
+pub mod random;
 pub mod reference;
+pub mod string;
@@ -0,0 +1,442 @@
+// Copyright 2017 sadikovi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Optimizer rules and rewrites for expressions.
+
+use expr::api::Expression;
+use expr::literal::{Literal, lit};
+use expr::reference::Reference;
+use expr::logical::{and, eq, ge, gt, in_list, is_not_null, is_null, le, lt, not, or};
+use rules::Rule;
+use trees::TreeNode;
+
+/// Removes casts whose child already has the target data type, e.g. `cast(a as int)`
+/// where `a` is an integer column is replaced with `a`.
+///
+/// Returns `None` if expression does not contain redundant casts, so it can be used
+/// in rules similar to `Expression::transform_down_opt`.
+pub fn remove_redundant_casts(exp: &Expression) -> Option<Expression> {
+  exp.transform_down_opt(&mut |node| {
+    if !is_redundant_cast(node) {
+      return None;
+    }
+    let mut child = &node.children()[0];
+    while is_redundant_cast(child) {
+      child = &child.children()[0];
+    }
+    Some(child.clone())
+  })
+}
+
+/// Internal method to check if expression is a cast to the data type of its child.
+fn is_redundant_cast(exp: &Expression) -> bool {
+  exp.node_name() == "CAST" && exp.resolved() &&
+    exp.children()[0].data_type() == exp.target_type().unwrap()
+}
+
+/// Returns subexpressions that occur more than once across the expressions and can be
+/// evaluated once and reused, in order of the first occurrence. Subexpressions are
+/// compared in canonical form, e.g. `a * b` and `b * a` are the same subexpression.
+///
+/// Only expressions that pass `Expression::is_cse_eligible` are considered; children of
+/// already seen subexpression are not collected again.
+pub fn common_subexpressions(exps: &[Expression]) -> Vec<Expression> {
+  let mut seen: Vec<(Expression, Expression, usize)> = Vec::new();
+  for exp in exps {
+    add_subexpressions(exp, &mut seen);
+  }
+  seen.into_iter().filter(|entry| entry.2 > 1).map(|entry| entry.0).collect()
+}
+
+/// Internal method to count eligible subexpressions of the expression tree.
+/// Each entry contains first occurrence, its canonical form, and number of occurrences.
+fn add_subexpressions(exp: &Expression, seen: &mut Vec<(Expression, Expression, usize)>) {
+  if exp.is_cse_eligible() {
+    let canonical = exp.canonicalize();
+    let existing = seen.iter_mut().find(|entry| entry.1.semantic_equals(&canonical));
+    if let Some(entry) = existing {
+      entry.2 += 1;
+      return;
+    }
+    seen.push((exp.clone(), canonical, 1));
+  }
+  for child in exp.children() {
+    add_subexpressions(child, seen);
+  }
+}
+
+/// Simplifies `in` expressions: duplicate literals are removed from the list, list with
+/// a single element is replaced with equality, and empty list is replaced with `false`
+/// when value is not nullable, since `null IN ()` is `null`.
+pub struct SimplifyIn;
+
+impl Rule for SimplifyIn {
+  type Plan = Expression;
+
+  fn name(&self) -> String {
+    "SimplifyIn".to_owned()
+  }
+
+  fn apply(&self, exp: &Expression) -> Option<Expression> {
+    exp.transform_down_opt(&mut |node| {
+      if node.node_name() != "IN" {
+        return None;
+      }
+      let mut list: Vec<Expression> = Vec::new();
+      for child in &node.children()[1..] {
+        let duplicate = child.literal().is_some() &&
+          list.iter().any(|item| item.semantic_equals(child));
+        if !duplicate {
+          list.push(child.clone());
+        }
+      }
+      let value = node.children()[0].clone();
+      match list.len() {
+        0 if value.resolved() && !value.nullable() => {
+          Some(lit(Literal::Boolean(Some(false))))
+        },
+        0 => None,
+        1 => Some(eq(value, list.pop().unwrap())),
+        len if len < node.num_children() - 1 => Some(in_list(value, list)),
+        _ => None
+      }
+    })
+  }
+}
+
+/// Pushes negations down to the leaves of boolean expressions using De Morgan's laws,
+/// e.g. `!(a && b)` becomes `(!a || !b)`, negated comparisons are inverted, e.g.
+/// `!(a > b)` becomes `a <= b`, and double negations are removed.
+pub struct PushDownNot;
+
+impl PushDownNot {
+  /// Internal method to push negation one level down, returns `None` if expression is
+  /// not a negation or negation cannot be pushed through its child.
+  fn push_down(exp: &Expression) -> Option<Expression> {
+    if exp.node_name() != "NOT" {
+      return None;
+    }
+    let child = &exp.children()[0];
+    let children = child.children();
+    match child.node_name().as_str() {
+      "NOT" => Some(children[0].clone()),
+      "AND" => Some(or(not(children[0].clone()), not(children[1].clone()))),
+      "OR" => Some(and(not(children[0].clone()), not(children[1].clone()))),
+      "GREATER_THAN" => Some(le(children[0].clone(), children[1].clone())),
+      "GREATER_OR_EQUAL" => Some(lt(children[0].clone(), children[1].clone())),
+      "LESS_THAN" => Some(ge(children[0].clone(), children[1].clone())),
+      "LESS_OR_EQUAL" => Some(gt(children[0].clone(), children[1].clone())),
+      "IS_NULL" => Some(is_not_null(children[0].clone())),
+      "IS_NOT_NULL" => Some(is_null(children[0].clone())),
+      _ => None
+    }
+  }
+}
+
+impl Rule for PushDownNot {
+  type Plan = Expression;
+
+  fn name(&self) -> String {
+    "PushDownNot".to_owned()
+  }
+
+  fn apply(&self, exp: &Expression) -> Option<Expression> {
+    exp.transform_down_opt(&mut |node| {
+      // result can be a negation again, e.g. for `!!!a`
+      let mut current = PushDownNot::push_down(node)?;
+      while let Some(next) = PushDownNot::push_down(&current) {
+        current = next;
+      }
+      Some(current)
+    })
+  }
+}
+
+/// Propagates constants within a conjunction: columns that are compared for equality
+/// with a literal, e.g. `a = 5`, are replaced with that literal in other conjuncts, so
+/// `a = 5 && a > 3` becomes `a = 5 && 5 > 3`, which can be folded afterwards.
+///
+/// Equality predicates themselves are not rewritten, literal is only propagated if it
+/// has the same data type as the column.
+pub struct ConstantPropagation;
+
+impl ConstantPropagation {
+  /// Internal method to extract column and literal from `column = literal` or
+  /// `literal = column` predicate.
+  fn equality_constant(exp: &Expression) -> Option<(Reference, Literal)> {
+    if exp.node_name() != "EQUAL" {
+      return None;
+    }
+    let (left, right) = (&exp.children()[0], &exp.children()[1]);
+    let (column, value) = match (left.reference(), right.literal()) {
+      (Some(column), Some(value)) => (column, value),
+      _ => match (right.reference(), left.literal()) {
+        (Some(column), Some(value)) => (column, value),
+        _ => return None
+      }
+    };
+    let same_type = column.data_type().map_or(true, |tpe| tpe == value.data_type());
+    if value.is_null() || !same_type {
+      return None;
+    }
+    Some((column.clone(), value.clone()))
+  }
+
+  /// Internal method to propagate constants into conjuncts of the conjunction, returns
+  /// `None` if none of the conjuncts changed.
+  fn propagate(exp: &Expression) -> Option<Expression> {
+    if exp.node_name() != "AND" {
+      return None;
+    }
+    let conjuncts = exp.split_conjunctive_predicates();
+    let mut constants: Vec<(Reference, Literal)> = Vec::new();
+    for conjunct in &conjuncts {
+      if let Some((column, value)) = ConstantPropagation::equality_constant(conjunct) {
+        if !constants.iter().any(|(c, _)| c == &column) {
+          constants.push((column, value));
+        }
+      }
+    }
+    if constants.is_empty() {
+      return None;
+    }
+
+    let mut changed = false;
+    let updated: Vec<Expression> = conjuncts.iter().map(|conjunct| {
+      if ConstantPropagation::equality_constant(conjunct).is_some() {
+        return conjunct.clone();
+      }
+      let result = conjunct.transform_down_opt(&mut |node| {
+        let column = node.reference()?;
+        constants.iter().find(|(c, _)| c == column).map(|(_, value)| lit(value.clone()))
+      });
+      match result {
+        Some(result) => {
+          changed = true;
+          result
+        },
+        None => conjunct.clone()
+      }
+    }).collect();
+
+    if changed {
+      let mut iter = updated.into_iter();
+      let first = iter.next().unwrap();
+      Some(iter.fold(first, and))
+    } else {
+      None
+    }
+  }
+}
+
+impl Rule for ConstantPropagation {
+  type Plan = Expression;
+
+  fn name(&self) -> String {
+    "ConstantPropagation".to_owned()
+  }
+
+  fn apply(&self, exp: &Expression) -> Option<Expression> {
+    exp.transform_down_opt(&mut ConstantPropagation::propagate)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use expr::api::Row;
+  use expr::arithmetic::{add, mul};
+  use expr::cast::cast;
+  use expr::random::rand;
+  use expr::reference::{bound_reference, reference};
+  use types::DataType;
+
+  #[test]
+  fn test_common_subexpressions() {
+    let a = bound_reference("a", DataType::IntegerType, true);
+    let b = bound_reference("b", DataType::IntegerType, true);
+    let exps = vec![
+      add(mul(a.clone(), b.clone()), lit(Literal::int(1))),
+      gt(mul(b.clone(), a.clone()), lit(Literal::int(2))),
+      add(rand(0), a.clone()),
+      add(rand(0), a.clone()),
+      lit(Literal::int(1))
+    ];
+    let res: Vec<String> =
+      common_subexpressions(&exps).iter().map(|exp| exp.pretty_string()).collect();
+    assert_eq!(res, vec!["(a * b)"]);
+    assert!(common_subexpressions(&[]).is_empty());
+  }
+
+  #[test]
+  fn test_remove_redundant_casts() {
+    let exp = cast(lit(Literal::int(1)), DataType::IntegerType);
+    assert!(remove_redundant_casts(&exp).unwrap() == lit(Literal::int(1)));
+
+    let col = bound_reference("a", DataType::IntegerType, true);
+    let exp = add(
+      cast(cast(col.clone(), DataType::IntegerType), DataType::IntegerType),
+      cast(lit(Literal::long(2)), DataType::IntegerType)
+    );
+    let res = remove_redundant_casts(&exp).unwrap();
+    assert_eq!(res.pretty_string(), "(a + cast(2 as int))");
+  }
+
+  #[test]
+  fn test_remove_redundant_casts_no_change() {
+    let exp = cast(lit(Literal::long(1)), DataType::IntegerType);
+    assert!(remove_redundant_casts(&exp).is_none());
+    let exp = cast(bound_reference("a", DataType::IntegerType, true), DataType::LongType);
+    assert!(remove_redundant_casts(&exp).is_none());
+  }
+
+  #[test]
+  fn test_simplify_in() {
+    let exp = in_list(reference("a"), vec![lit(Literal::int(1))]);
+    let res = SimplifyIn.apply(&exp).unwrap();
+    assert!(res == eq(reference("a"), lit(Literal::int(1))));
+
+    let exp = in_list(bound_reference("a", DataType::IntegerType, false), vec![]);
+    let res = SimplifyIn.apply(&exp).unwrap();
+    assert!(res == lit(Literal::Boolean(Some(false))));
+
+    let exp = in_list(reference("a"), vec![
+      lit(Literal::int(1)), lit(Literal::int(2)),
+      lit(Literal::int(1)), lit(Literal::int(2))
+    ]);
+    let res = SimplifyIn.apply(&exp).unwrap();
+    assert_eq!(res.pretty_string(), "('a IN (1, 2))");
+
+    let exp = in_list(reference("a"), vec![lit(Literal::int(3)), lit(Literal::int(3))]);
+    let res = SimplifyIn.apply(&exp).unwrap();
+    assert_eq!(res.pretty_string(), "('a == 3)");
+  }
+
+  #[test]
+  fn test_simplify_in_no_change() {
+    let exp = in_list(reference("a"), vec![lit(Literal::int(1)), lit(Literal::int(2))]);
+    assert!(SimplifyIn.apply(&exp).is_none());
+    let exp = in_list(reference("a"), vec![reference("b"), reference("b")]);
+    assert!(SimplifyIn.apply(&exp).is_none());
+    assert!(SimplifyIn.apply(&reference("a")).is_none());
+
+    // null value is not in empty list, but the result is null
+    let exp = in_list(lit(Literal::null_int()), vec![]);
+    assert_eq!(exp.eval(&Row::new()), Literal::Boolean(None));
+    assert!(SimplifyIn.apply(&exp).is_none());
+    let exp = in_list(bound_reference("a", DataType::IntegerType, true), vec![]);
+    assert!(SimplifyIn.apply(&exp).is_none());
+    assert!(SimplifyIn.apply(&in_list(reference("a"), vec![])).is_none());
+  }
+
+  fn bool_col(name: &str) -> Expression {
+    bound_reference(name, DataType::BooleanType, true)
+  }
+
+  fn int_col(name: &str) -> Expression {
+    bound_reference(name, DataType::IntegerType, true)
+  }
+
+  #[test]
+  fn test_push_down_not_de_morgan() {
+    let exp = not(and(bool_col("a"), bool_col("b")));
+    let res = PushDownNot.apply(&exp).unwrap();
+    assert!(res == or(not(bool_col("a")), not(bool_col("b"))));
+
+    let exp = not(or(bool_col("a"), bool_col("b")));
+    let res = PushDownNot.apply(&exp).unwrap();
+    assert!(res == and(not(bool_col("a")), not(bool_col("b"))));
+
+    let exp = not(and(bool_col("a"), or(bool_col("b"), not(bool_col("c")))));
+    let res = PushDownNot.apply(&exp).unwrap();
+    assert_eq!(res.pretty_string(), "((!a) || ((!b) && c))");
+  }
+
+  #[test]
+  fn test_push_down_not_double_negation() {
+    let exp = not(not(bool_col("a")));
+    assert!(PushDownNot.apply(&exp).unwrap() == bool_col("a"));
+
+    let exp = not(not(not(and(bool_col("a"), bool_col("b")))));
+    let res = PushDownNot.apply(&exp).unwrap();
+    assert!(res == or(not(bool_col("a")), not(bool_col("b"))));
+  }
+
+  #[test]
+  fn test_push_down_not_comparison() {
+    let a = int_col("a");
+    let b = int_col("b");
+    let res = PushDownNot.apply(&not(gt(a.clone(), b.clone()))).unwrap();
+    assert!(res == le(a.clone(), b.clone()));
+    let res = PushDownNot.apply(&not(ge(a.clone(), b.clone()))).unwrap();
+    assert!(res == lt(a.clone(), b.clone()));
+    let res = PushDownNot.apply(&not(lt(a.clone(), b.clone()))).unwrap();
+    assert!(res == ge(a.clone(), b.clone()));
+    let res = PushDownNot.apply(&not(le(a.clone(), b.clone()))).unwrap();
+    assert!(res == gt(a.clone(), b.clone()));
+    let res = PushDownNot.apply(&not(is_null(a.clone()))).unwrap();
+    assert!(res == is_not_null(a.clone()));
+    let res = PushDownNot.apply(&not(is_not_null(a.clone()))).unwrap();
+    assert!(res == is_null(a.clone()));
+
+    let exp = not(or(gt(a.clone(), b.clone()), lt(a.clone(), lit(Literal::int(1)))));
+    let res = PushDownNot.apply(&exp).unwrap();
+    assert_eq!(res.pretty_string(), "((a <= b) && (a >= 1))");
+  }
+
+  #[test]
+  fn test_push_down_not_no_change() {
+    assert!(PushDownNot.apply(&not(bool_col("a"))).is_none());
+    assert!(PushDownNot.apply(&and(not(bool_col("a")), bool_col("b"))).is_none());
+    assert!(PushDownNot.apply(&not(eq(int_col("a"), int_col("b")))).is_none());
+  }
+
+  #[test]
+  fn test_constant_propagation() {
+    let exp = and(
+      eq(reference("a"), lit(Literal::int(5))),
+      gt(reference("a"), lit(Literal::int(3)))
+    );
+    let res = ConstantPropagation.apply(&exp).unwrap();
+    assert!(res == and(
+      eq(reference("a"), lit(Literal::int(5))),
+      gt(lit(Literal::int(5)), lit(Literal::int(3)))
+    ));
+    assert!(res.split_conjunctive_predicates()[1].partial_eval() ==
+      lit(Literal::Boolean(Some(true))));
+
+    let exp = and(
+      gt(add(int_col("a"), int_col("b")), lit(Literal::int(10))),
+      and(eq(lit(Literal::int(1)), int_col("a")), eq(int_col("b"), lit(Literal::int(2))))
+    );
+    let res = ConstantPropagation.apply(&exp).unwrap();
+    assert_eq!(res.pretty_string(), "((((1 + 2) > 10) && (1 == a)) && (b == 2))");
+    assert!(res.split_conjunctive_predicates()[0].partial_eval() ==
+      lit(Literal::Boolean(Some(false))));
+  }
+
+  #[test]
+  fn test_constant_propagation_no_change() {
+    let exp = and(eq(int_col("a"), lit(Literal::int(5))), gt(int_col("b"), int_col("c")));
+    assert!(ConstantPropagation.apply(&exp).is_none());
+    // propagation is limited to conjunction
+    let exp = or(eq(int_col("a"), lit(Literal::int(5))), gt(int_col("a"), int_col("c")));
+    assert!(ConstantPropagation.apply(&exp).is_none());
+    // literal of a different type is not propagated
+    let exp = and(eq(int_col("a"), lit(Literal::long(5))),
+      gt(int_col("a"), int_col("c")));
+    assert!(ConstantPropagation.apply(&exp).is_none());
+    assert!(ConstantPropagation.apply(&eq(int_col("a"), lit(Literal::int(5)))).is_none());
+  }
+}
@@ -0,0 +1,69 @@
+// Copyright 2017 sadikovi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Non-deterministic random expressions.
+
+use expr::api::{Expression, ExpressionBuilder};
+use trees::TreeNode;
+use types::DataType;
+
+/// Random double value in range `[0.0, 1.0)` generated with provided seed, e.g.
+/// `rand(0)`. Expression is not deterministic and not foldable.
+pub fn rand(seed: i64) -> Expression {
+  ExpressionBuilder::new("RAND".to_owned())
+    .seed(seed)
+    .display(Box::new(|exp, _| {
+      format!("rand({})", exp.seed().unwrap())
+    }))
+    .foldable(Box::new(|_| false))
+    .deterministic(Box::new(|_| false))
+    .nullable(Box::new(|_| false))
+    .resolved(Box::new(|_| true))
+    .datatype(Box::new(|_| &DataType::DoubleType))
+    .clone(Box::new(|exp| {
+      rand(exp.seed().unwrap())
+    }))
+    .eq(Box::new(|a, b| {
+      a.node_name() == b.node_name() && a.seed() == b.seed()
+    }))
+    .build()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_rand() {
+    let exp = rand(42);
+    assert_eq!(exp.pretty_string(), "rand(42)");
+    assert_eq!(exp.seed(), Some(42));
+    assert!(!exp.deterministic());
+    assert!(!exp.foldable());
+    assert!(!exp.nullable());
+    assert!(exp.resolved());
+    assert_eq!(exp.data_type(), &DataType::DoubleType);
+    assert!(exp == exp.clone());
+    assert!(exp == exp.rebuild(vec![]));
+    assert!(exp != rand(1));
+  }
+
+  #[test]
+  fn test_rand_seed() {
+    assert!(rand(1).semantic_equals(&rand(1)));
+    assert!(!rand(1).semantic_equals(&rand(2)));
+    assert_eq!(rand(1).tree_hash(), rand(1).tree_hash());
+    assert!(rand(1).tree_hash() != rand(2).tree_hash());
+  }
+}
@@ -12,12 +12,9 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-//! Contains bound references representing columns.
-/*
-use std::fmt;
-use std::any;
+//! Contains references representing columns.
 
-use expr::api::*;
+use expr::api::{Expression, ExpressionBuilder};
 use types::DataType;
 
 /// Column reference, which can be either bound or unbound depending on the provided
@@ -30,6 +27,7 @@ pub struct Reference {
 }
 
 impl Reference {
+  /// Creates new column reference.
   pub fn new(name: String, data_type: Option<DataType>, nullable: bool) -> Self {
     Self {
       name: name,
@@ -37,69 +35,116 @@ impl Reference {
       nullable: nullable
     }
   }
-}
 
-impl fmt::Display for Reference {
-  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-    match self.data_type {
-      Some(_) => write!(f, "{}#", self.name),
-      None => write!(f, "'{}", self.name)
-    }
+  /// Returns name of the column.
+  pub fn name(&self) -> &str {
+    &self.name
   }
-}
 
-impl OutputDataType for Reference {
-  fn output_datatype(&self) -> &DataType {
-    match self.data_type {
-      Some(ref dt) => dt,
-      None => panic!("Cannot extract data type from unresolved reference, \
-        resolve attributes first")
-    }
+  /// Returns data type of the column, or `None` if reference is not resolved.
+  pub fn data_type(&self) -> Option<&DataType> {
+    self.data_type.as_ref()
   }
-}
 
-impl ResolveExpression for Reference {
-  fn resolve(&self) -> bool {
-    self.data_type.is_some()
+  /// Returns `true` if column is nullable.
+  pub fn is_nullable(&self) -> bool {
+    self.nullable
   }
 }
 
-impl Expression for Reference {
-  fn foldable(&self) -> bool {
-    false
-  }
+/// Unresolved column reference.
+pub fn reference(name: &str) -> Expression {
+  column(Reference::new(name.to_owned(), None, true))
+}
 
-  fn deterministic(&self) -> bool {
-    // Reference is assumed to be deterministic, since it comes from the relation.
-    true
-  }
+/// Column reference resolved to a data type.
+pub fn bound_reference(name: &str, data_type: DataType, nullable: bool) -> Expression {
+  column(Reference::new(name.to_owned(), Some(data_type), nullable))
+}
 
-  fn nullable(&self) -> bool {
-    self.nullable
-  }
+/// Quotes identifier with backticks for SQL, e.g. `a b` becomes ``a b``, so names with
+/// special characters or reserved keywords are valid. Backticks in the name are escaped
+/// by doubling them.
+pub(crate) fn quote_identifier(name: &str) -> String {
+  format!("`{}`", name.replace("`", "``"))
+}
 
-  /// Returns a user-facing string representation of this expression's name.
-  fn pretty_name(&self) -> String {
-    format!("{:?}", self)
-  }
+/// Returns expression for column reference.
+fn column(value: Reference) -> Expression {
+  ExpressionBuilder::new("REFERENCE".to_owned())
+    .reference(value)
+    .display(Box::new(|exp, _| {
+      let value = exp.reference().unwrap();
+      match value.data_type() {
+        Some(_) => value.name().to_string(),
+        None => format!("'{}", value.name())
+      }
+    }))
+    .sql(Box::new(|exp, _| {
+      quote_identifier(exp.reference().unwrap().name())
+    }))
+    .foldable(Box::new(|_| false))
+    .deterministic(Box::new(|_| {
+      // reference is assumed to be deterministic, since it comes from the relation
+      true
+    }))
+    .nullable(Box::new(|exp| {
+      exp.reference().unwrap().is_nullable()
+    }))
+    .resolved(Box::new(|exp| {
+      exp.reference().unwrap().data_type().is_some()
+    }))
+    .datatype(Box::new(|exp| {
+      match exp.reference().unwrap().data_type() {
+        Some(dt) => dt,
+        None => panic!("Cannot extract data type from unresolved reference, \
+          resolve attributes first")
+      }
+    }))
+    .eval(Box::new(|exp, _| {
+      panic!("Cannot evaluate unbound reference {}", exp.reference().unwrap().name())
+    }))
+    .clone(Box::new(|exp| {
+      column(exp.reference().unwrap().clone())
+    }))
+    .eq(Box::new(|a, b| {
+      a.reference().is_some() && a.reference() == b.reference()
+    }))
+    .build()
+}
 
-  fn clone_as_expr(&self) -> Box<Expression> {
-    Box::new(self.clone())
-  }
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use trees::TreeNode;
 
-  fn eq_as_expr(&self, other: &Box<Expression>) -> bool {
-    match Box::new(other.as_any_ref()).downcast_ref::<Self>() {
-      Some(literal) => self.eq(literal),
-      None => false
-    }
+  #[test]
+  fn test_reference() {
+    let exp = reference("a");
+    assert_eq!(exp.node_name(), "REFERENCE");
+    assert_eq!(exp.pretty_string(), "'a");
+    assert!(!exp.resolved());
+    assert!(!exp.foldable());
+    assert!(exp.deterministic());
+    assert!(exp.nullable());
+    assert!(exp.clone() == exp);
   }
 
-  fn as_tree(&self) -> ExpressionTreeNode {
-    ExpressionTreeNode::new(self.clone_as_expr(), vec![])
+  #[test]
+  fn test_bound_reference() {
+    let exp = bound_reference("a", DataType::IntegerType, false);
+    assert_eq!(exp.pretty_string(), "a");
+    assert_eq!(exp.data_type(), &DataType::IntegerType);
+    assert!(exp.resolved());
+    assert!(!exp.nullable());
+    assert!(exp.clone() == exp);
+    assert!(exp != reference("a"));
+    assert!(exp != bound_reference("b", DataType::IntegerType, false));
   }
 
-  fn as_any_ref(&self) -> &any::Any {
-    self
+  #[test]
+  #[should_panic(expected = "Cannot extract data type from unresolved reference")]
+  fn test_reference_unresolved_data_type() {
+    reference("a").data_type();
   }
 }
-*/
@@ -0,0 +1,202 @@
+// Copyright 2017 sadikovi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! String predicates.
+
+use expr::api::{Expression, ExpressionBuilder, Row, binary};
+use expr::literal::Literal;
+use types::DataType;
+
+/// Returns builder for binary string predicate, resolved only if both children are
+/// strings.
+fn string_predicate(
+  name: &str,
+  symbol: &str,
+  left: Expression,
+  right: Expression,
+  func: fn(&str, &str) -> bool
+) -> ExpressionBuilder
+{
+  binary(name.to_owned(), symbol.to_owned(), left, right)
+    .null_intolerant(true)
+    .resolved(Box::new(|exp| {
+      exp.children().iter().all(|child| {
+        child.resolved() && child.data_type() == &DataType::StringType
+      })
+    }))
+    .datatype(Box::new(|_| &DataType::BooleanType))
+    .eval(Box::new(move |exp, row| {
+      match eval_strings(exp, row) {
+        (Some(left), Some(right)) => Literal::Boolean(Some(func(&left, &right))),
+        _ => Literal::Boolean(None)
+      }
+    }))
+}
+
+/// Internal method to evaluate children into nullable string values.
+fn eval_strings(exp: &Expression, row: &Row) -> (Option<String>, Option<String>) {
+  let mut values = exp.children().iter().map(|child| {
+    match child.eval(row) {
+      Literal::String(value) => value,
+      other => panic!("Expected string value, found {}", other)
+    }
+  });
+  (values.next().unwrap(), values.next().unwrap())
+}
+
+/// Value matches SQL pattern, e.g. `a LIKE 'ab%'`, where `%` matches any sequence of
+/// characters, `_` matches any single character, and `\` escapes the next character.
+pub fn like(value: Expression, pattern: Expression) -> Expression {
+  string_predicate("LIKE", "LIKE", value, pattern, matches_pattern)
+    .clone(Box::new(|exp| {
+      like(exp.children()[0].clone(), exp.children()[1].clone())
+    }))
+    .build()
+}
+
+/// Value starts with prefix, e.g. `startswith(a, 'ab')`.
+pub fn starts_with(value: Expression, prefix: Expression) -> Expression {
+  string_predicate("STARTS_WITH", "", value, prefix, |value, prefix| {
+    value.starts_with(prefix)
+  })
+    .display(Box::new(|_, children| {
+      format!("startswith({}, {})", children[0], children[1])
+    }))
+    .clone(Box::new(|exp| {
+      starts_with(exp.children()[0].clone(), exp.children()[1].clone())
+    }))
+    .build()
+}
+
+/// Internal method to parse pattern into list of tokens, `None` matches any sequence of
+/// characters, `Some(None)` matches any single character, and `Some(Some(ch))` matches
+/// the character.
+fn parse_pattern(pattern: &str) -> Vec<Option<Option<char>>> {
+  let mut tokens = Vec::new();
+  let mut chars = pattern.chars();
+  while let Some(ch) = chars.next() {
+    match ch {
+      '%' => tokens.push(None),
+      '_' => tokens.push(Some(None)),
+      '\\' => tokens.push(Some(Some(chars.next().unwrap_or('\\')))),
+      _ => tokens.push(Some(Some(ch)))
+    }
+  }
+  tokens
+}
+
+/// Internal method to check if value matches SQL pattern.
+fn matches_pattern(value: &str, pattern: &str) -> bool {
+  let tokens = parse_pattern(pattern);
+  let chars: Vec<char> = value.chars().collect();
+  // matched[i] is true if the first `i` characters match the tokens processed so far
+  let mut matched = vec![false; chars.len() + 1];
+  matched[0] = true;
+  for token in &tokens {
+    let mut next = vec![false; chars.len() + 1];
+    match token {
+      None => {
+        let mut any = false;
+        for i in 0..next.len() {
+          any = any || matched[i];
+          next[i] = any;
+        }
+      },
+      Some(expected) => {
+        for i in 1..next.len() {
+          next[i] = matched[i - 1] && expected.map_or(true, |ch| ch == chars[i - 1]);
+        }
+      }
+    }
+    matched = next;
+  }
+  matched[chars.len()]
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use expr::literal::lit;
+  use expr::reference::reference;
+  use trees::TreeNode;
+
+  fn string(value: Option<&str>) -> Expression {
+    lit(Literal::String(value.map(|v| v.to_owned())))
+  }
+
+  fn eval(exp: Expression) -> Literal {
+    exp.eval(&Row::new())
+  }
+
+  #[test]
+  fn test_like() {
+    let exp = like(string(Some("abc")), string(Some("a%")));
+    assert_eq!(exp.pretty_string(), "(\"abc\" LIKE \"a%\")");
+    assert_eq!(exp.node_name(), "LIKE");
+    assert_eq!(exp.data_type(), &DataType::BooleanType);
+    assert!(exp.resolved());
+    assert!(exp.foldable());
+    assert!(!exp.nullable());
+    assert!(exp == exp.clone());
+    assert_eq!(eval(exp), Literal::Boolean(Some(true)));
+
+    let exp = like(string(None), string(Some("a%")));
+    assert!(exp.nullable());
+    assert_eq!(eval(exp), Literal::Boolean(None));
+  }
+
+  #[test]
+  fn test_like_patterns() {
+    assert!(matches_pattern("abc", "abc"));
+    assert!(!matches_pattern("abc", "ab"));
+    assert!(matches_pattern("abc", "%"));
+    assert!(matches_pattern("", "%"));
+    assert!(!matches_pattern("", "_"));
+    assert!(matches_pattern("abc", "a_c"));
+    assert!(matches_pattern("abc", "%c"));
+    assert!(matches_pattern("abcbc", "a%bc"));
+    assert!(!matches_pattern("abcb", "a%bc"));
+    assert!(matches_pattern("a%c", "a\\%c"));
+    assert!(!matches_pattern("abc", "a\\%c"));
+    assert!(matches_pattern("a_", "%\\_"));
+  }
+
+  #[test]
+  fn test_starts_with() {
+    let exp = starts_with(string(Some("abc")), string(Some("ab")));
+    assert_eq!(exp.pretty_string(), "startswith(\"abc\", \"ab\")");
+    assert_eq!(exp.node_name(), "STARTS_WITH");
+    assert_eq!(exp.data_type(), &DataType::BooleanType);
+    assert!(exp.resolved());
+    assert!(!exp.nullable());
+    assert!(exp == exp.clone());
+    assert_eq!(eval(exp), Literal::Boolean(Some(true)));
+    assert_eq!(eval(starts_with(string(Some("abc")), string(Some("b")))),
+      Literal::Boolean(Some(false)));
+
+    let exp = starts_with(string(Some("abc")), string(None));
+    assert!(exp.nullable());
+    assert_eq!(eval(exp), Literal::Boolean(None));
+  }
+
+  #[test]
+  fn test_string_predicates_not_string() {
+    let int = lit(Literal::Integer(Some(1)));
+    assert!(!like(int.clone(), string(Some("1"))).resolved());
+    assert!(!like(string(Some("1")), int.clone()).resolved());
+    assert!(!starts_with(int.clone(), string(Some("1"))).resolved());
+    assert!(!starts_with(reference("a"), string(Some("1"))).resolved());
+    assert_eq!(starts_with(int, string(Some("1"))).data_type(), &DataType::BooleanType);
+  }
+}
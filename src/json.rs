@@ -0,0 +1,452 @@
+// Copyright 2017 sadikovi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Minimal JSON value used for serialization of schemas and trees.
+
+use std::iter::Peekable;
+use std::str::Chars;
+
+/// JSON value, object keeps keys in insertion order.
+#[derive(Clone, Debug, PartialEq)]
+pub enum JsonValue {
+  Null,
+  Bool(bool),
+  Number(String),
+  String(String),
+  Array(Vec<JsonValue>),
+  Object(Vec<(String, JsonValue)>)
+}
+
+impl JsonValue {
+  /// Parses JSON string into value.
+  /// Returns error message if string is not a valid JSON.
+  pub fn parse(json: &str) -> Result<JsonValue, String> {
+    let mut chars = json.chars().peekable();
+    let value = parse_value(&mut chars)?;
+    skip_whitespace(&mut chars);
+    match chars.next() {
+      Some(ch) => Err(format!("Unexpected character '{}' after JSON value", ch)),
+      None => Ok(value)
+    }
+  }
+
+  /// Returns value for the key if this value is an object, `None` otherwise.
+  pub fn get(&self, key: &str) -> Option<&JsonValue> {
+    match self {
+      JsonValue::Object(entries) => {
+        entries.iter().find(|(name, _)| name == key).map(|(_, value)| value)
+      },
+      _ => None
+    }
+  }
+
+  /// Returns compact JSON string without any whitespace.
+  pub fn to_compact_string(&self) -> String {
+    let mut buf = String::new();
+    self.write_compact(&mut buf);
+    buf
+  }
+
+  /// Returns pretty JSON string, each nested level is indented with `indent` spaces.
+  pub fn to_pretty_string(&self, indent: usize) -> String {
+    let mut buf = String::new();
+    self.write_pretty(indent, 0, &mut buf);
+    buf
+  }
+
+  /// Internal method to write compact JSON.
+  fn write_compact(&self, buf: &mut String) {
+    match self {
+      JsonValue::Array(values) => {
+        buf.push('[');
+        for (idx, value) in values.iter().enumerate() {
+          if idx > 0 {
+            buf.push(',');
+          }
+          value.write_compact(buf);
+        }
+        buf.push(']');
+      },
+      JsonValue::Object(entries) => {
+        buf.push('{');
+        for (idx, (key, value)) in entries.iter().enumerate() {
+          if idx > 0 {
+            buf.push(',');
+          }
+          write_string(key, buf);
+          buf.push(':');
+          value.write_compact(buf);
+        }
+        buf.push('}');
+      },
+      _ => self.write_scalar(buf)
+    }
+  }
+
+  /// Internal method to write pretty JSON at the nesting level `depth`.
+  fn write_pretty(&self, indent: usize, depth: usize, buf: &mut String) {
+    let inner = " ".repeat(indent * (depth + 1));
+    let outer = " ".repeat(indent * depth);
+    match self {
+      JsonValue::Array(ref values) if !values.is_empty() => {
+        buf.push_str("[\n");
+        for (idx, value) in values.iter().enumerate() {
+          if idx > 0 {
+            buf.push_str(",\n");
+          }
+          buf.push_str(&inner);
+          value.write_pretty(indent, depth + 1, buf);
+        }
+        buf.push('\n');
+        buf.push_str(&outer);
+        buf.push(']');
+      },
+      JsonValue::Object(ref entries) if !entries.is_empty() => {
+        buf.push_str("{\n");
+        for (idx, (key, value)) in entries.iter().enumerate() {
+          if idx > 0 {
+            buf.push_str(",\n");
+          }
+          buf.push_str(&inner);
+          write_string(key, buf);
+          buf.push_str(": ");
+          value.write_pretty(indent, depth + 1, buf);
+        }
+        buf.push('\n');
+        buf.push_str(&outer);
+        buf.push('}');
+      },
+      _ => self.write_compact(buf)
+    }
+  }
+
+  /// Internal method to write scalar value.
+  fn write_scalar(&self, buf: &mut String) {
+    match self {
+      JsonValue::Null => buf.push_str("null"),
+      JsonValue::Bool(value) => buf.push_str(if *value { "true" } else { "false" }),
+      JsonValue::Number(value) => buf.push_str(value),
+      JsonValue::String(value) => write_string(value, buf),
+      _ => unreachable!()
+    }
+  }
+}
+
+/// Internal method to skip whitespace characters.
+fn skip_whitespace(chars: &mut Peekable<Chars>) {
+  while let Some(&ch) = chars.peek() {
+    if !ch.is_whitespace() {
+      break;
+    }
+    chars.next();
+  }
+}
+
+/// Internal method to consume expected literal, e.g. `true`.
+fn expect_literal(chars: &mut Peekable<Chars>, literal: &str) -> Result<(), String> {
+  for expected in literal.chars() {
+    match chars.next() {
+      Some(ch) if ch == expected => { },
+      _ => return Err(format!("Invalid literal, expected '{}'", literal))
+    }
+  }
+  Ok(())
+}
+
+/// Internal method to parse JSON value.
+fn parse_value(chars: &mut Peekable<Chars>) -> Result<JsonValue, String> {
+  skip_whitespace(chars);
+  match chars.peek().cloned() {
+    Some('{') => parse_object(chars),
+    Some('[') => parse_array(chars),
+    Some('"') => parse_string(chars).map(JsonValue::String),
+    Some('t') => expect_literal(chars, "true").map(|_| JsonValue::Bool(true)),
+    Some('f') => expect_literal(chars, "false").map(|_| JsonValue::Bool(false)),
+    Some('n') => expect_literal(chars, "null").map(|_| JsonValue::Null),
+    Some(ch) if ch == '-' || ch.is_ascii_digit() => parse_number(chars),
+    Some(ch) => Err(format!("Unexpected character '{}'", ch)),
+    None => Err("Unexpected end of JSON".to_owned())
+  }
+}
+
+/// Internal method to parse JSON object.
+fn parse_object(chars: &mut Peekable<Chars>) -> Result<JsonValue, String> {
+  chars.next(); // consume '{'
+  let mut entries = Vec::new();
+  skip_whitespace(chars);
+  if chars.peek() == Some(&'}') {
+    chars.next();
+    return Ok(JsonValue::Object(entries));
+  }
+  loop {
+    skip_whitespace(chars);
+    if chars.peek() != Some(&'"') {
+      return Err("Expected string key in JSON object".to_owned());
+    }
+    let key = parse_string(chars)?;
+    skip_whitespace(chars);
+    if chars.next() != Some(':') {
+      return Err(format!("Expected ':' after key \"{}\"", key));
+    }
+    let value = parse_value(chars)?;
+    entries.push((key, value));
+    skip_whitespace(chars);
+    match chars.next() {
+      Some(',') => { },
+      Some('}') => return Ok(JsonValue::Object(entries)),
+      _ => return Err("Expected ',' or '}' in JSON object".to_owned())
+    }
+  }
+}
+
+/// Internal method to parse JSON array.
+fn parse_array(chars: &mut Peekable<Chars>) -> Result<JsonValue, String> {
+  chars.next(); // consume '['
+  let mut values = Vec::new();
+  skip_whitespace(chars);
+  if chars.peek() == Some(&']') {
+    chars.next();
+    return Ok(JsonValue::Array(values));
+  }
+  loop {
+    values.push(parse_value(chars)?);
+    skip_whitespace(chars);
+    match chars.next() {
+      Some(',') => { },
+      Some(']') => return Ok(JsonValue::Array(values)),
+      _ => return Err("Expected ',' or ']' in JSON array".to_owned())
+    }
+  }
+}
+
+/// Internal method to parse quoted JSON string.
+fn parse_string(chars: &mut Peekable<Chars>) -> Result<String, String> {
+  chars.next(); // consume opening quote
+  let mut buf = String::new();
+  loop {
+    match chars.next() {
+      Some('"') => return Ok(buf),
+      Some('\\') => match chars.next() {
+        Some('"') => buf.push('"'),
+        Some('\\') => buf.push('\\'),
+        Some('/') => buf.push('/'),
+        Some('b') => buf.push('\u{8}'),
+        Some('f') => buf.push('\u{c}'),
+        Some('n') => buf.push('\n'),
+        Some('r') => buf.push('\r'),
+        Some('t') => buf.push('\t'),
+        Some('u') => buf.push(parse_unicode_escape(chars)?),
+        _ => return Err("Invalid escape sequence in JSON string".to_owned())
+      },
+      Some(ch) => buf.push(ch),
+      None => return Err("Unterminated JSON string".to_owned())
+    }
+  }
+}
+
+/// Internal method to parse code unit of unicode escape, i.e. 4 hex digits after `\u`.
+fn parse_code_unit(chars: &mut Peekable<Chars>) -> Result<u32, String> {
+  let code: String = chars.by_ref().take(4).collect();
+  match u32::from_str_radix(&code, 16) {
+    Ok(value) if code.len() == 4 => Ok(value),
+    _ => Err(format!("Invalid unicode escape \\u{}", code))
+  }
+}
+
+/// Internal method to parse unicode escape after `\u`, characters outside of the basic
+/// multilingual plane are encoded as surrogate pair, e.g. `\uD83D\uDE00`.
+fn parse_unicode_escape(chars: &mut Peekable<Chars>) -> Result<char, String> {
+  let high = parse_code_unit(chars)?;
+  let code = if (0xD800..=0xDBFF).contains(&high) {
+    if chars.next() != Some('\\') || chars.next() != Some('u') {
+      return Err(format!("Expected low surrogate after \\u{:04X}", high));
+    }
+    let low = parse_code_unit(chars)?;
+    if !(0xDC00..=0xDFFF).contains(&low) {
+      return Err(format!("Invalid low surrogate \\u{:04X}", low));
+    }
+    0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+  } else {
+    high
+  };
+  ::std::char::from_u32(code)
+    .ok_or_else(|| format!("Invalid unicode escape \\u{:04X}", code))
+}
+
+/// Internal method to check that number follows JSON grammar, i.e. optional minus sign,
+/// integer part without leading zeros, optional fraction and exponent.
+fn is_valid_number(value: &str) -> bool {
+  let bytes = value.as_bytes();
+  let mut pos = if bytes.first() == Some(&b'-') { 1 } else { 0 };
+  let digits = |pos: usize| {
+    bytes[pos..].iter().take_while(|b| b.is_ascii_digit()).count()
+  };
+  let int_len = digits(pos);
+  if int_len == 0 || (int_len > 1 && bytes[pos] == b'0') {
+    return false;
+  }
+  pos += int_len;
+  if bytes.get(pos) == Some(&b'.') {
+    let frac_len = digits(pos + 1);
+    if frac_len == 0 {
+      return false;
+    }
+    pos += 1 + frac_len;
+  }
+  if bytes.get(pos) == Some(&b'e') || bytes.get(pos) == Some(&b'E') {
+    pos += 1;
+    if bytes.get(pos) == Some(&b'+') || bytes.get(pos) == Some(&b'-') {
+      pos += 1;
+    }
+    let exp_len = digits(pos);
+    if exp_len == 0 {
+      return false;
+    }
+    pos += exp_len;
+  }
+  pos == bytes.len()
+}
+
+/// Internal method to parse JSON number, number is kept as string.
+fn parse_number(chars: &mut Peekable<Chars>) -> Result<JsonValue, String> {
+  let mut buf = String::new();
+  while let Some(&ch) = chars.peek() {
+    if ch.is_ascii_digit() || ch == '-' || ch == '+' || ch == '.' ||
+        ch == 'e' || ch == 'E' {
+      buf.push(ch);
+      chars.next();
+    } else {
+      break;
+    }
+  }
+  if is_valid_number(&buf) {
+    Ok(JsonValue::Number(buf))
+  } else {
+    Err(format!("Invalid number {}", buf))
+  }
+}
+
+/// Internal method to write quoted and escaped JSON string.
+fn write_string(value: &str, buf: &mut String) {
+  buf.push('"');
+  for ch in value.chars() {
+    match ch {
+      '"' => buf.push_str("\\\""),
+      '\\' => buf.push_str("\\\\"),
+      '\n' => buf.push_str("\\n"),
+      '\r' => buf.push_str("\\r"),
+      '\t' => buf.push_str("\\t"),
+      ch if (ch as u32) < 0x20 => buf.push_str(&format!("\\u{:04x}", ch as u32)),
+      ch => buf.push(ch)
+    }
+  }
+  buf.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn get_test_value() -> JsonValue {
+    JsonValue::Object(vec![
+      ("a".to_owned(), JsonValue::Number("1".to_owned())),
+      ("b".to_owned(), JsonValue::Array(vec![
+        JsonValue::Bool(true),
+        JsonValue::Null
+      ])),
+      ("c".to_owned(), JsonValue::String("x\"y".to_owned())),
+      ("d".to_owned(), JsonValue::Object(vec![])),
+      ("e".to_owned(), JsonValue::Array(vec![]))
+    ])
+  }
+
+  #[test]
+  fn test_json_compact_string() {
+    assert_eq!(
+      get_test_value().to_compact_string(),
+      "{\"a\":1,\"b\":[true,null],\"c\":\"x\\\"y\",\"d\":{},\"e\":[]}"
+    );
+  }
+
+  #[test]
+  fn test_json_parse() {
+    let value = get_test_value();
+    assert_eq!(JsonValue::parse(&value.to_compact_string()), Ok(value.clone()));
+    assert_eq!(JsonValue::parse(&value.to_pretty_string(4)), Ok(value.clone()));
+
+    assert_eq!(JsonValue::parse(" -1.5e3 "), Ok(JsonValue::Number("-1.5e3".to_owned())));
+    let value = JsonValue::parse("\"a\\n\\u0041\\/\"");
+    assert_eq!(value, Ok(JsonValue::String("a\nA/".to_owned())));
+    assert_eq!(JsonValue::parse("[ ]"), Ok(JsonValue::Array(vec![])));
+  }
+
+  #[test]
+  fn test_json_parse_surrogate_pair() {
+    let value = JsonValue::parse("\"\\uD83D\\uDE00\"");
+    assert_eq!(value, Ok(JsonValue::String("\u{1F600}".to_owned())));
+    let value = JsonValue::parse("\"a\\ud83d\\ude00b\"");
+    assert_eq!(value, Ok(JsonValue::String("a\u{1F600}b".to_owned())));
+    assert!(JsonValue::parse("\"\\uD83D\"").is_err());
+    assert!(JsonValue::parse("\"\\uD83Dx\"").is_err());
+    assert!(JsonValue::parse("\"\\uD83D\\u0041\"").is_err());
+    assert!(JsonValue::parse("\"\\uDE00\"").is_err());
+    assert!(JsonValue::parse("\"\\u12\"").is_err());
+  }
+
+  #[test]
+  fn test_json_parse_number() {
+    for value in &["0", "-0", "12", "-1.5", "0.25", "1e5", "1E+5", "-2.5e-3"] {
+      assert_eq!(JsonValue::parse(value), Ok(JsonValue::Number(value.to_string())));
+    }
+    for value in &["012", "-012", "00", "1.", "-", "-.5", "1e", "1e+", "1.2.3", "+1"] {
+      assert!(JsonValue::parse(value).is_err(), "Expected error for {}", value);
+    }
+  }
+
+  #[test]
+  fn test_json_parse_error() {
+    assert!(JsonValue::parse("").is_err());
+    assert!(JsonValue::parse("{").is_err());
+    assert!(JsonValue::parse("{\"a\" 1}").is_err());
+    assert!(JsonValue::parse("[1,]").is_err());
+    assert!(JsonValue::parse("\"abc").is_err());
+    assert!(JsonValue::parse("tru").is_err());
+    assert!(JsonValue::parse("1 2").is_err());
+    assert!(JsonValue::parse("--").is_err());
+  }
+
+  #[test]
+  fn test_json_get() {
+    let value = get_test_value();
+    assert_eq!(value.get("a"), Some(&JsonValue::Number("1".to_owned())));
+    assert_eq!(value.get("z"), None);
+    assert_eq!(JsonValue::Null.get("a"), None);
+  }
+
+  #[test]
+  fn test_json_pretty_string() {
+    assert_eq!(get_test_value().to_pretty_string(2), [
+      "{",
+      "  \"a\": 1,",
+      "  \"b\": [",
+      "    true,",
+      "    null",
+      "  ],",
+      "  \"c\": \"x\\\"y\",",
+      "  \"d\": {},",
+      "  \"e\": []",
+      "}"
+    ].join("\n"));
+  }
+}
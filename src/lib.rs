@@ -17,7 +17,10 @@ extern crate log;
 
 #[macro_use]
 pub mod errors;
+mod json;
 pub mod trees;
 pub mod rules;
 pub mod types;
+pub mod naming;
 pub mod expr;
+pub mod plans;
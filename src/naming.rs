@@ -0,0 +1,106 @@
+// Copyright 2017 sadikovi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Generation of unique names, e.g. for columns of unnamed expressions.
+
+use std::collections::{HashMap, HashSet};
+
+/// Generator of unique names.
+///
+/// Names are generated as `prefix_0`, `prefix_1`, etc. with a separate counter for each
+/// prefix, names that are reserved or have already been generated are skipped.
+#[derive(Default)]
+pub struct NameGenerator {
+  used: HashSet<String>,
+  counters: HashMap<String, usize>
+}
+
+impl NameGenerator {
+  /// Creates new generator without reserved names.
+  pub fn new() -> Self {
+    Self {
+      used: HashSet::new(),
+      counters: HashMap::new()
+    }
+  }
+
+  /// Creates new generator that never returns any of the `reserved` names.
+  pub fn with_reserved(reserved: &[&str]) -> Self {
+    let mut generator = Self::new();
+    for name in reserved {
+      generator.reserve(name);
+    }
+    generator
+  }
+
+  /// Marks name as used, so it is never returned by the generator.
+  pub fn reserve(&mut self, name: &str) {
+    self.used.insert(name.to_owned());
+  }
+
+  /// Returns `true` if name is either reserved or has already been generated.
+  pub fn is_used(&self, name: &str) -> bool {
+    self.used.contains(name)
+  }
+
+  /// Returns new unique name for the prefix.
+  pub fn fresh(&mut self, prefix: &str) -> String {
+    let counter = self.counters.entry(prefix.to_owned()).or_insert(0);
+    loop {
+      let name = format!("{}_{}", prefix, counter);
+      *counter += 1;
+      if !self.used.contains(&name) {
+        self.used.insert(name.clone());
+        return name;
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_name_generator_fresh() {
+    let mut generator = NameGenerator::new();
+    assert_eq!(generator.fresh("col"), "col_0");
+    assert_eq!(generator.fresh("col"), "col_1");
+    assert_eq!(generator.fresh("expr"), "expr_0");
+    assert_eq!(generator.fresh("col"), "col_2");
+    assert!(generator.is_used("col_1"));
+    assert!(!generator.is_used("col_3"));
+  }
+
+  #[test]
+  fn test_name_generator_reserved() {
+    let mut generator = NameGenerator::with_reserved(&["col_0", "col_2"]);
+    assert_eq!(generator.fresh("col"), "col_1");
+    assert_eq!(generator.fresh("col"), "col_3");
+
+    generator.reserve("col_4");
+    assert_eq!(generator.fresh("col"), "col_5");
+  }
+
+  #[test]
+  fn test_name_generator_unique() {
+    let mut generator = NameGenerator::with_reserved(&["a_1", "a_3"]);
+    let mut names = HashSet::new();
+    for _ in 0..100 {
+      assert!(names.insert(generator.fresh("a")));
+    }
+    assert!(!names.contains("a_1"));
+    assert!(!names.contains("a_3"));
+  }
+}
@@ -0,0 +1,321 @@
+// Copyright 2017 sadikovi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Logical plan operators.
+
+use errors::CatalystError;
+use expr::api::Expression;
+use trees::TreeNode;
+use types::DataType;
+
+/// Logical plan of a query.
+#[derive(Clone, PartialEq)]
+pub enum LogicalPlan {
+  /// Leaf relation with name and output schema.
+  Relation { name: String, schema: DataType },
+  /// Returns first `n` rows of the child.
+  Limit { n: u64, child: Box<LogicalPlan> },
+  /// Sorts rows of the child by list of expressions.
+  Sort { orders: Vec<Expression>, child: Box<LogicalPlan> },
+  /// Returns union of all rows of the children, columns of the children are matched by
+  /// position and must have compatible types.
+  Union { children: Vec<LogicalPlan> }
+}
+
+impl LogicalPlan {
+  /// Creates new relation.
+  pub fn relation(name: &str, schema: DataType) -> LogicalPlan {
+    LogicalPlan::Relation { name: name.to_owned(), schema }
+  }
+
+  /// Creates new limit over the child plan.
+  pub fn limit(n: u64, child: LogicalPlan) -> LogicalPlan {
+    LogicalPlan::Limit { n, child: Box::new(child) }
+  }
+
+  /// Creates new sort over the child plan.
+  pub fn sort(orders: Vec<Expression>, child: LogicalPlan) -> LogicalPlan {
+    LogicalPlan::Sort { orders, child: Box::new(child) }
+  }
+
+  /// Creates new union of the child plans.
+  pub fn union(children: Vec<LogicalPlan>) -> LogicalPlan {
+    LogicalPlan::Union { children }
+  }
+
+  /// Returns output schema of this plan.
+  ///
+  /// Returns error if schema cannot be resolved, e.g. analysis error when union children
+  /// have different number of columns or incompatible column types. Union output has
+  /// column names of the first child and column types widened with
+  /// `DataType::union_type`.
+  pub fn output(&self) -> Result<DataType, CatalystError> {
+    match self {
+      LogicalPlan::Relation { ref schema, .. } => Ok(schema.clone()),
+      LogicalPlan::Limit { ref child, .. } => child.output(),
+      LogicalPlan::Sort { ref child, .. } => child.output(),
+      LogicalPlan::Union { ref children } => {
+        if children.is_empty() {
+          return schema_err!("Union requires at least one child");
+        }
+        let mut schema = union_child_output(&children[0])?;
+        for child in &children[1..] {
+          let output = union_child_output(child)?;
+          if output.num_fields() != schema.num_fields() {
+            return analysis_err!("Union can only be performed on schemas with the same \
+              number of columns, found {} and {}", schema, output);
+          }
+          schema = match schema.union_type(&output) {
+            Some(data_type) => data_type,
+            None => return analysis_err!("Union can only be performed on schemas with \
+              compatible column types, found {} and {}", schema, output)
+          };
+        }
+        Ok(schema)
+      }
+    }
+  }
+}
+
+impl TreeNode<LogicalPlan> for LogicalPlan {
+  fn node_name(&self) -> String {
+    match self {
+      LogicalPlan::Relation { .. } => "Relation".to_owned(),
+      LogicalPlan::Limit { .. } => "Limit".to_owned(),
+      LogicalPlan::Sort { .. } => "Sort".to_owned(),
+      LogicalPlan::Union { .. } => "Union".to_owned()
+    }
+  }
+
+  fn verbose_string(&self) -> String {
+    match self {
+      LogicalPlan::Relation { ref name, ref schema } => {
+        format!("Relation {} {}", name, schema)
+      },
+      LogicalPlan::Limit { n, .. } => format!("Limit {}", n),
+      LogicalPlan::Sort { ref orders, .. } => {
+        let orders: Vec<String> = orders.iter().map(|exp| exp.pretty_string()).collect();
+        format!("Sort [{}]", orders.join(", "))
+      },
+      LogicalPlan::Union { .. } => "Union".to_owned()
+    }
+  }
+
+  fn get(&self) -> &LogicalPlan {
+    self
+  }
+
+  fn num_children(&self) -> usize {
+    match self {
+      LogicalPlan::Relation { .. } => 0,
+      LogicalPlan::Limit { .. } | LogicalPlan::Sort { .. } => 1,
+      LogicalPlan::Union { ref children } => children.len()
+    }
+  }
+
+  fn get_child(&self, pos: usize) -> Option<&LogicalPlan> {
+    match self {
+      LogicalPlan::Limit { ref child, .. } |
+      LogicalPlan::Sort { ref child, .. } if pos == 0 => Some(child),
+      LogicalPlan::Union { ref children } => children.get(pos),
+      _ => None
+    }
+  }
+
+  fn set_child(&mut self, pos: usize, plan: LogicalPlan) {
+    match self {
+      LogicalPlan::Limit { ref mut child, .. } |
+      LogicalPlan::Sort { ref mut child, .. } if pos == 0 => {
+        **child = plan;
+      },
+      LogicalPlan::Union { ref mut children } if pos < children.len() => {
+        children[pos] = plan;
+      },
+      _ => { }
+    }
+  }
+
+  fn clone_tree(&self) -> LogicalPlan {
+    self.clone()
+  }
+
+  fn equals(&self, other: &LogicalPlan) -> bool {
+    self.eq(other)
+  }
+}
+
+/// Internal method to return output of the union child, which must be a struct type.
+fn union_child_output(child: &LogicalPlan) -> Result<DataType, CatalystError> {
+  match child.output()? {
+    schema @ DataType::StructType(_) => Ok(schema),
+    other => schema_err!("Union can only be performed on struct schemas, found {}", other)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use expr::reference::bound_reference;
+
+  fn get_test_relation() -> LogicalPlan {
+    LogicalPlan::relation("t", DataType::struct_type(vec![])
+      .add_field("a", DataType::IntegerType)
+      .add_field("b", DataType::StringType))
+  }
+
+  #[test]
+  fn test_limit_sort_properties() {
+    let orders = vec![
+      bound_reference("a", DataType::IntegerType, true),
+      bound_reference("b", DataType::StringType, true)
+    ];
+    let plan = LogicalPlan::limit(10, LogicalPlan::sort(orders, get_test_relation()));
+
+    assert_eq!(plan.num_children(), 1);
+    assert_eq!(plan.verbose_string(), "Limit 10");
+    assert_eq!(plan.get_child(0).unwrap().verbose_string(), "Sort [a, b]");
+    assert!(plan.get_child(1).is_none());
+    assert_eq!(plan.output().unwrap(), get_test_relation().output().unwrap());
+    assert_eq!(get_test_relation().verbose_string(), "Relation t struct<a:int,b:string>");
+  }
+
+  #[test]
+  fn test_limit_sort_tree_string() {
+    let orders = vec![bound_reference("a", DataType::IntegerType, true)];
+    let plan = LogicalPlan::limit(10, LogicalPlan::sort(orders, get_test_relation()));
+    assert_eq!(plan.tree_string(), [
+      "Limit",
+      "+- Sort",
+      "   +- Relation"
+    ].join("\n"));
+  }
+
+  #[test]
+  fn test_union_properties() {
+    let plan = LogicalPlan::union(vec![
+      get_test_relation(),
+      LogicalPlan::limit(10, get_test_relation()),
+      get_test_relation()
+    ]);
+    assert_eq!(plan.num_children(), 3);
+    assert_eq!(plan.verbose_string(), "Union");
+    assert_eq!(plan.get_child(1).unwrap().verbose_string(), "Limit 10");
+    assert!(plan.get_child(3).is_none());
+    assert_eq!(plan.tree_string(), [
+      "Union",
+      ":- Relation",
+      ":- Limit",
+      ":  +- Relation",
+      "+- Relation"
+    ].join("\n"));
+  }
+
+  #[test]
+  fn test_union_output() {
+    let plan = LogicalPlan::union(vec![
+      LogicalPlan::relation("t1", DataType::struct_type(vec![])
+        .add_field_n("a", DataType::IntegerType, false)
+        .add_field_n("b", DataType::StringType, false)),
+      LogicalPlan::relation("t2", DataType::struct_type(vec![])
+        .add_field_n("a", DataType::IntegerType, true)
+        .add_field_n("b", DataType::StringType, false))
+    ]);
+    assert_eq!(plan.output().unwrap(), DataType::struct_type(vec![])
+      .add_field_n("a", DataType::IntegerType, true)
+      .add_field_n("b", DataType::StringType, false));
+  }
+
+  #[test]
+  fn test_union_output_by_position() {
+    let plan = LogicalPlan::union(vec![
+      LogicalPlan::relation("t1", DataType::struct_type(vec![])
+        .add_field_n("a", DataType::IntegerType, false)
+        .add_field_n("b", DataType::IntegerType, false)),
+      LogicalPlan::relation("t2", DataType::struct_type(vec![])
+        .add_field_n("b", DataType::IntegerType, false)
+        .add_field_n("a", DataType::LongType, true)),
+      LogicalPlan::relation("t3", DataType::struct_type(vec![])
+        .add_field_n("x", DataType::ShortType, false)
+        .add_field_n("y", DataType::IntegerType, false))
+    ]);
+    assert_eq!(plan.output().unwrap(), DataType::struct_type(vec![])
+      .add_field_n("a", DataType::IntegerType, false)
+      .add_field_n("b", DataType::LongType, true));
+  }
+
+  #[test]
+  fn test_union_output_error() {
+    let mismatched_type = LogicalPlan::union(vec![
+      get_test_relation(),
+      LogicalPlan::relation("t1", DataType::struct_type(vec![])
+        .add_field("a", DataType::StringType)
+        .add_field("b", DataType::StringType))
+    ]);
+    match mismatched_type.output() {
+      Err(CatalystError::Analysis(msg)) => {
+        assert_eq!(msg, "Union can only be performed on schemas with compatible column \
+          types, found struct<a:int,b:string> and struct<a:string,b:string>")
+      },
+      _ => panic!("Expected analysis error")
+    }
+
+    let mismatched_columns = LogicalPlan::union(vec![
+      get_test_relation(),
+      LogicalPlan::relation("t1", DataType::struct_type(vec![])
+        .add_field("a", DataType::IntegerType))
+    ]);
+    match mismatched_columns.output() {
+      Err(CatalystError::Analysis(msg)) => {
+        assert_eq!(msg, "Union can only be performed on schemas with the same number \
+          of columns, found struct<a:int,b:string> and struct<a:int>")
+      },
+      _ => panic!("Expected analysis error")
+    }
+
+    let reordered = LogicalPlan::union(vec![
+      LogicalPlan::relation("t1", DataType::struct_type(vec![])
+        .add_field("a", DataType::IntegerType)
+        .add_field("b", DataType::StringType)),
+      LogicalPlan::relation("t2", DataType::struct_type(vec![])
+        .add_field("b", DataType::StringType)
+        .add_field("a", DataType::IntegerType))
+    ]);
+    assert!(reordered.output().is_err());
+
+    let non_struct = LogicalPlan::union(vec![
+      get_test_relation(),
+      LogicalPlan::relation("t1", DataType::IntegerType)
+    ]);
+    match non_struct.output() {
+      Err(CatalystError::Schema(msg)) => {
+        assert_eq!(msg, "Union can only be performed on struct schemas, found int")
+      },
+      _ => panic!("Expected schema error")
+    }
+    let non_struct = LogicalPlan::union(vec![
+      LogicalPlan::relation("t1", DataType::IntegerType),
+      get_test_relation()
+    ]);
+    assert!(non_struct.output().is_err());
+
+    assert!(LogicalPlan::union(vec![]).output().is_err());
+  }
+
+  #[test]
+  fn test_set_child() {
+    let mut plan = LogicalPlan::limit(10, get_test_relation());
+    plan.set_child(0, LogicalPlan::limit(5, get_test_relation()));
+    assert!(plan == LogicalPlan::limit(10, LogicalPlan::limit(5, get_test_relation())));
+  }
+}
@@ -0,0 +1,140 @@
+// Copyright 2017 sadikovi
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Optimizer rules for logical plans.
+
+use std::cmp;
+
+use plans::logical::LogicalPlan;
+use rules::Rule;
+use trees::TreeNode;
+
+/// Combines two adjacent limits into one limit with the minimum of both values.
+pub struct CombineLimits;
+
+impl Rule for CombineLimits {
+  type Plan = LogicalPlan;
+
+  fn name(&self) -> String {
+    "CombineLimits".to_owned()
+  }
+
+  fn apply(&self, plan: &LogicalPlan) -> Option<LogicalPlan> {
+    let updated_plan = plan.transform_up(&mut |node| {
+      match node {
+        LogicalPlan::Limit { n, ref child } => match **child {
+          LogicalPlan::Limit { n: m, child: ref grand_child } => {
+            Some(LogicalPlan::limit(cmp::min(*n, m), (**grand_child).clone()))
+          },
+          _ => None
+        },
+        _ => None
+      }
+    });
+    if &updated_plan != plan { Some(updated_plan) } else { None }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::rc::Rc;
+  use expr::reference::bound_reference;
+  use rules::{Batch, RuleBatch, RuleExecutor, Strategy};
+  use types::DataType;
+
+  fn get_test_relation() -> LogicalPlan {
+    let schema = DataType::struct_type(vec![]).add_field("a", DataType::IntegerType);
+    LogicalPlan::relation("t", schema)
+  }
+
+  #[test]
+  fn test_combine_limits() {
+    let plan = LogicalPlan::limit(10, LogicalPlan::limit(5, get_test_relation()));
+    let res = CombineLimits.apply(&plan).unwrap();
+    assert!(res == LogicalPlan::limit(5, get_test_relation()));
+
+    let plan = LogicalPlan::limit(3, LogicalPlan::limit(5, get_test_relation()));
+    let res = CombineLimits.apply(&plan).unwrap();
+    assert!(res == LogicalPlan::limit(3, get_test_relation()));
+
+    let plan = LogicalPlan::limit(7,
+      LogicalPlan::limit(10, LogicalPlan::limit(8, get_test_relation())));
+    let res = CombineLimits.apply(&plan).unwrap();
+    assert!(res == LogicalPlan::limit(7, get_test_relation()));
+  }
+
+  #[test]
+  fn test_combine_limits_no_change() {
+    let plan = LogicalPlan::limit(10, LogicalPlan::sort(vec![],
+      LogicalPlan::limit(5, get_test_relation())));
+    assert!(CombineLimits.apply(&plan).is_none());
+    assert!(CombineLimits.apply(&get_test_relation()).is_none());
+  }
+
+  fn limit_batches() -> Vec<Box<Batch<Plan=LogicalPlan>>> {
+    vec![
+      Box::new(RuleBatch::new("limits".to_owned(), Strategy::FixedPoint(10),
+        vec![Rc::new(CombineLimits)])),
+      Box::new(RuleBatch::new("limits once".to_owned(), Strategy::Once,
+        vec![Rc::new(CombineLimits)]))
+    ]
+  }
+
+  struct SerialLimitOptimizer;
+
+  impl RuleExecutor for SerialLimitOptimizer {
+    type Plan = LogicalPlan;
+
+    fn batches() -> Vec<Box<Batch<Plan=LogicalPlan>>> { limit_batches() }
+
+    fn is_plan_integral(plan: &LogicalPlan) -> bool { plan.output().is_ok() }
+  }
+
+  struct ParallelLimitOptimizer;
+
+  impl RuleExecutor for ParallelLimitOptimizer {
+    type Plan = LogicalPlan;
+
+    fn batches() -> Vec<Box<Batch<Plan=LogicalPlan>>> { limit_batches() }
+
+    fn is_plan_integral(plan: &LogicalPlan) -> bool { plan.output().is_ok() }
+
+    fn parallelize_independent_subtrees() -> bool { true }
+  }
+
+  #[test]
+  fn test_execute_parallel_logical_plan() {
+    let orders = vec![bound_reference("a", DataType::IntegerType, true)];
+    let plan = LogicalPlan::union(vec![
+      LogicalPlan::limit(10, LogicalPlan::limit(5, get_test_relation())),
+      LogicalPlan::sort(orders.clone(),
+        LogicalPlan::limit(3, LogicalPlan::limit(7, get_test_relation()))),
+      get_test_relation()
+    ]);
+    let expected = LogicalPlan::union(vec![
+      LogicalPlan::limit(5, get_test_relation()),
+      LogicalPlan::sort(orders, LogicalPlan::limit(3, get_test_relation())),
+      get_test_relation()
+    ]);
+    let serial = SerialLimitOptimizer::execute(&plan).unwrap();
+    assert!(serial == expected);
+    assert!(ParallelLimitOptimizer::execute(&plan).unwrap() == serial);
+
+    // plan with a single child is executed serially
+    let plan = LogicalPlan::limit(4, LogicalPlan::limit(2, plan));
+    let expected = LogicalPlan::limit(2, expected);
+    assert!(ParallelLimitOptimizer::execute(&plan).unwrap() == expected);
+  }
+}
@@ -16,11 +16,20 @@
 //!
 //! Also provides batches of rules that can be run once or until a fixed point.
 
+use std::collections::HashMap;
+use std::fmt;
+use std::panic;
+use std::rc::Rc;
+use std::thread;
+
 use errors::CatalystError;
+use json::JsonValue;
+use trees::TreeNode;
 
 /// An execution strategy for rules that indicates the maximum number of executions.
 /// If the execution reaches fix point (i.e. converge) before max iterations,
 /// it will stop.
+#[derive(Clone, Debug, PartialEq)]
 pub enum Strategy {
   Once,
   FixedPoint(u16),
@@ -34,6 +43,23 @@ impl Strategy {
       Strategy::FixedPoint(iterations) => iterations,
     }
   }
+
+  /// Returns `true` if strategy runs until fixed point, `false` otherwise.
+  pub fn is_fixed_point(&self) -> bool {
+    match *self {
+      Strategy::Once => false,
+      Strategy::FixedPoint(_) => true,
+    }
+  }
+}
+
+impl fmt::Display for Strategy {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match *self {
+      Strategy::Once => write!(f, "Once"),
+      Strategy::FixedPoint(iterations) => write!(f, "FixedPoint({})", iterations),
+    }
+  }
 }
 
 /// Execution rule.
@@ -48,19 +74,263 @@ pub trait Rule {
   fn apply(&self, plan: &Self::Plan) -> Option<Self::Plan>;
 }
 
+/// Batch of rules executed together with the same strategy.
 pub trait Batch {
   type Plan;
 
+  /// Batch name.
   fn name(&self) -> String;
 
+  /// Execution strategy for this batch.
   fn strategy(&self) -> &Strategy;
 
-  fn rules(&self) -> &Vec<Box<Rule<Plan=Self::Plan>>>;
+  /// Rules of this batch in the order of execution.
+  ///
+  /// Rules are reference counted, so the same rule instance can be shared between
+  /// several batches without being rebuilt.
+  fn rules(&self) -> &Vec<Rc<Rule<Plan=Self::Plan>>>;
+
+  /// Returns `true` if this batch should be run for the plan, `false` to skip the batch
+  /// entirely, e.g. aggregate rules when plan does not contain aggregates.
+  fn should_run(&self, _plan: &Self::Plan) -> bool {
+    true
+  }
+}
+
+/// Batch of rules with name and strategy, e.g. assembled by `build_batches`.
+pub struct RuleBatch<P> {
+  name: String,
+  strategy: Strategy,
+  rules: Vec<Rc<Rule<Plan=P>>>
+}
+
+impl<P> RuleBatch<P> {
+  /// Creates new batch of rules.
+  pub fn new(name: String, strategy: Strategy, rules: Vec<Rc<Rule<Plan=P>>>) -> Self {
+    Self { name, strategy, rules }
+  }
+}
+
+impl<P> Batch for RuleBatch<P> {
+  type Plan = P;
+
+  fn name(&self) -> String {
+    self.name.clone()
+  }
+
+  fn strategy(&self) -> &Strategy {
+    &self.strategy
+  }
+
+  fn rules(&self) -> &Vec<Rc<Rule<Plan=P>>> {
+    &self.rules
+  }
+}
+
+// Creates new instance of the rule.
+type RuleConstructor<P> = Fn() -> Rc<Rule<Plan=P>>;
+
+/// Registry of rule constructors by rule name, used to assemble batches from
+/// configuration.
+pub struct RuleRegistry<P> {
+  constructors: HashMap<String, Box<RuleConstructor<P>>>
+}
+
+impl<P> Default for RuleRegistry<P> {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl<P> RuleRegistry<P> {
+  /// Creates new empty registry.
+  pub fn new() -> Self {
+    Self { constructors: HashMap::new() }
+  }
+
+  /// Registers rule constructor with the name, replaces existing constructor with the
+  /// same name.
+  pub fn register(&mut self, name: &str, constructor: Box<RuleConstructor<P>>) {
+    self.constructors.insert(name.to_owned(), constructor);
+  }
+
+  /// Returns `true` if rule with the name is registered.
+  pub fn contains(&self, name: &str) -> bool {
+    self.constructors.contains_key(name)
+  }
+
+  /// Creates new rule for the name, or returns `None` if rule is not registered.
+  pub fn create(&self, name: &str) -> Option<Rc<Rule<Plan=P>>> {
+    self.constructors.get(name).map(|constructor| constructor())
+  }
+}
+
+/// Assembles batches from configuration of batch name, strategy, and rule names, rules
+/// are created using the registry.
+///
+/// Returns error if any of the rule names is not registered.
+pub fn build_batches<P: 'static>(
+  config: &[(String, Strategy, Vec<String>)],
+  registry: &RuleRegistry<P>
+) -> Result<Vec<Box<Batch<Plan=P>>>, CatalystError>
+{
+  let mut batches: Vec<Box<Batch<Plan=P>>> = Vec::with_capacity(config.len());
+  for (name, strategy, rule_names) in config {
+    let mut rules = Vec::with_capacity(rule_names.len());
+    for rule_name in rule_names {
+      match registry.create(rule_name) {
+        Some(rule) => rules.push(rule),
+        None => return rule_err!("Unknown rule {} in batch {}", rule_name, name)
+      }
+    }
+    batches.push(Box::new(RuleBatch::new(name.clone(), strategy.clone(), rules)));
+  }
+  Ok(batches)
+}
+
+/// Execution report of a single batch.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BatchReport {
+  name: String,
+  iterations: u16,
+  fixed_point: bool,
+  effective_rules: Vec<String>,
+  // rules that were applied, with flag whether or not rule ever returned updated plan
+  applied_rules: Vec<(String, bool)>
+}
+
+impl BatchReport {
+  /// Creates new empty report for the batch.
+  pub fn new(name: String) -> Self {
+    Self {
+      name,
+      iterations: 0,
+      fixed_point: false,
+      effective_rules: vec![],
+      applied_rules: vec![]
+    }
+  }
+
+  /// Returns name of the batch.
+  pub fn name(&self) -> &str {
+    &self.name
+  }
+
+  /// Returns number of iterations the batch was run for.
+  pub fn iterations(&self) -> u16 {
+    self.iterations
+  }
+
+  /// Returns `true` if batch reached fixed point, i.e. last iteration did not change
+  /// the plan.
+  pub fn fixed_point(&self) -> bool {
+    self.fixed_point
+  }
+
+  /// Returns names of the rules that changed the plan, in order of first change.
+  pub fn effective_rules(&self) -> &[String] {
+    &self.effective_rules
+  }
+
+  /// Returns names of the rules that were applied, but never returned updated plan, in
+  /// order of first application.
+  pub fn unused_rules(&self) -> Vec<String> {
+    self.applied_rules.iter().filter(|rule| !rule.1).map(|rule| rule.0.clone()).collect()
+  }
+
+  /// Internal method to record application of the rule and whether or not rule
+  /// returned updated plan.
+  fn add_applied_rule(&mut self, name: String, matched: bool) {
+    match self.applied_rules.iter().position(|rule| rule.0 == name) {
+      Some(pos) => self.applied_rules[pos].1 |= matched,
+      None => self.applied_rules.push((name, matched))
+    }
+  }
+
+  /// Internal method to record rule that changed the plan.
+  fn add_effective_rule(&mut self, name: String) {
+    if !self.effective_rules.contains(&name) {
+      self.effective_rules.push(name);
+    }
+  }
+
+  /// Internal method to convert report into JSON value.
+  fn json_value(&self) -> JsonValue {
+    JsonValue::Object(vec![
+      ("name".to_owned(), JsonValue::String(self.name.clone())),
+      ("iterations".to_owned(), JsonValue::Number(self.iterations.to_string())),
+      ("fixedPoint".to_owned(), JsonValue::Bool(self.fixed_point)),
+      ("effectiveRules".to_owned(), JsonValue::Array(
+        self.effective_rules.iter().map(|name| JsonValue::String(name.clone())).collect()
+      ))
+    ])
+  }
+}
+
+/// Execution report of the rule executor, contains report for each batch in the order
+/// of execution.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct OptimizationReport {
+  batches: Vec<BatchReport>
+}
+
+impl OptimizationReport {
+  /// Creates new empty report.
+  pub fn new() -> Self {
+    Self { batches: vec![] }
+  }
+
+  /// Returns list of batch reports.
+  pub fn batches(&self) -> &[BatchReport] {
+    &self.batches
+  }
+
+  /// Returns names of the rules that were applied in any batch, but never returned
+  /// updated plan in any of the batches.
+  pub fn unused_rules(&self) -> Vec<String> {
+    let mut unused: Vec<String> = Vec::new();
+    for batch in &self.batches {
+      for rule in &batch.applied_rules {
+        let matched = self.batches.iter()
+          .any(|other| other.applied_rules.iter().any(|r| r.0 == rule.0 && r.1));
+        if !matched && !unused.contains(&rule.0) {
+          unused.push(rule.0.clone());
+        }
+      }
+    }
+    unused
+  }
+
+  /// Returns compact JSON representation of the report.
+  pub fn to_json(&self) -> String {
+    JsonValue::Object(vec![
+      ("batches".to_owned(), JsonValue::Array(
+        self.batches.iter().map(|batch| batch.json_value()).collect()
+      ))
+    ]).to_compact_string()
+  }
+}
+
+impl fmt::Display for OptimizationReport {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    for (idx, batch) in self.batches.iter().enumerate() {
+      if idx > 0 {
+        writeln!(f)?;
+      }
+      write!(f, "Batch {}: iterations = {}, fixed point = {}, effective rules = [{}]",
+        batch.name, batch.iterations, batch.fixed_point,
+        batch.effective_rules.join(", "))?;
+    }
+    Ok(())
+  }
 }
 
 /// Abstract rule executor for batches of rules.
+///
+/// Plan is `Send + Sync`, so child subtrees can be optimized in separate threads, see
+/// `parallelize_independent_subtrees`.
 pub trait RuleExecutor {
-  type Plan: Clone + PartialEq;
+  type Plan: Clone + PartialEq + TreeNode<Self::Plan> + Send + Sync;
 
   /// Sequence of rule batches.
   fn batches() -> Vec<Box<Batch<Plan=Self::Plan>>>;
@@ -73,31 +343,262 @@ pub trait RuleExecutor {
   /// returns `false` if the given plan doesn't pass the structural integrity check.
   fn is_plan_integral(plan: &Self::Plan) -> bool;
 
+  /// Returns hash of the plan tree used to speed up fixed point detection, or `None` if
+  /// hashing is not supported (default).
+  ///
+  /// When hashes of two plans differ, plans are considered different without running
+  /// full equality check; equal hashes are always confirmed with `==` to guard against
+  /// collisions.
+  fn plan_hash(_plan: &Self::Plan) -> Option<u64> {
+    None
+  }
+
+  /// Returns names of the rules that are allowed to run, or `None` (default) if all
+  /// rules are enabled. When set, rules with other names are skipped in all batches,
+  /// e.g. to test individual rules.
+  fn enabled_rules() -> Option<Vec<String>> {
+    None
+  }
+
+  /// Prepares the plan before any batch runs, e.g. to normalize the plan or assign
+  /// expression ids. Default implementation returns the plan unchanged.
+  ///
+  /// Error aborts the execution and is returned as the result of `execute`.
+  fn preprocess(plan: Self::Plan) -> Result<Self::Plan, CatalystError> {
+    Ok(plan)
+  }
+
+  /// Internal method to check if plan has not changed since the last iteration, uses
+  /// hashes of the plans if available.
+  fn is_same_plan(
+    current_plan: &Self::Plan,
+    current_hash: Option<u64>,
+    last_plan: &Self::Plan,
+    last_hash: Option<u64>
+  ) -> bool
+  {
+    match (current_hash, last_hash) {
+      (Some(current), Some(last)) if current != last => false,
+      _ => current_plan == last_plan
+    }
+  }
+
   /// Executes the batches of rules defined by the subclass.
   ///
   /// The batches are executed serially using the defined execution strategy.
-  /// Within each batch, rules are also executed serially.
+  /// Within each batch, rules are also executed serially. Batches that should not run
+  /// for the current plan are skipped.
+  ///
+  /// When `parallelize_independent_subtrees` is enabled, each child subtree of the
+  /// preprocessed root is optimized in a separate thread, then batches are run for the
+  /// root node only.
   fn execute(plan: &Self::Plan) -> Result<Self::Plan, CatalystError> {
+    if Self::parallelize_independent_subtrees() {
+      return Self::internal_execute_subtrees(plan);
+    }
+    Self::execute_reporting(plan).map(|(plan, _)| plan)
+  }
+
+  /// Returns `true` if `execute` should optimize child subtrees of the plan
+  /// concurrently, `false` (default) otherwise.
+  ///
+  /// Subtrees are expected to be independent, i.e. rules change a node based on the node
+  /// and its children only. Each batch runs once per node: while optimizing the root,
+  /// children of the updated root are replaced with the optimized children, unless rule
+  /// changes the number of children. `should_run` and `is_plan_integral` are always
+  /// called on the full plan, for child subtrees the plan is the root with the current
+  /// subtree. Other execute methods, e.g. `execute_reporting`, always run serially.
+  fn parallelize_independent_subtrees() -> bool {
+    false
+  }
+
+  /// Executes the batches of rules, same as `execute`, and returns the optimized plan
+  /// together with the report of the execution.
+  fn execute_reporting(
+    plan: &Self::Plan
+  ) -> Result<(Self::Plan, OptimizationReport), CatalystError>
+  {
+    let (plan, report, status) = Self::internal_execute(plan);
+    status.map(|_| (plan, report))
+  }
+
+  /// Executes the batches of rules, same as `execute`, and returns the optimized plan
+  /// together with names of the rules that were applied, but never returned updated
+  /// plan during the whole execution. Rules of skipped batches are not included.
+  fn execute_with_unused(
+    plan: &Self::Plan
+  ) -> Result<(Self::Plan, Vec<String>), CatalystError>
+  {
+    Self::execute_reporting(plan).map(|(plan, report)| (plan, report.unused_rules()))
+  }
+
+  /// Executes the batches of rules and then runs all batches once more on the optimized
+  /// plan to validate order of the rules, intended for debugging and tests. Validation
+  /// only runs in debug builds, in release builds the method returns `true` without
+  /// executing the plan.
+  ///
+  /// Returns `false` and logs a warning if the second pass changes the plan, i.e. the
+  /// first pass did not reach a global fixed point, which often means that a rule
+  /// depends on another rule that runs after it.
+  fn validate_rule_order(plan: &Self::Plan) -> Result<bool, CatalystError> {
+    if !cfg!(debug_assertions) {
+      return Ok(true);
+    }
+    let first_pass = Self::execute(plan)?;
+    let second_pass = Self::execute(&first_pass)?;
+    let first_hash = Self::plan_hash(&first_pass);
+    let second_hash = Self::plan_hash(&second_pass);
+    if Self::is_same_plan(&second_pass, second_hash, &first_pass, first_hash) {
+      Ok(true)
+    } else {
+      warn!("Plan changed after the second pass of all batches, rules may be misordered");
+      Ok(false)
+    }
+  }
+
+  /// Executes the batches of rules, same as `execute`, but always returns the plan: the
+  /// optimized plan on success, or the last plan that passed the integrity check before
+  /// the failing rule, together with the error.
+  fn execute_keep_partial(plan: &Self::Plan) -> (Self::Plan, Result<(), CatalystError>) {
+    let (plan, _, status) = Self::internal_execute(plan);
+    (plan, status)
+  }
+
+  /// Internal method to execute the batches of rules, returns the last integral plan,
+  /// report of the execution, and status of the execution.
+  fn internal_execute(
+    plan: &Self::Plan
+  ) -> (Self::Plan, OptimizationReport, Result<(), CatalystError>)
+  {
+    match Self::preprocess(plan.clone()) {
+      Ok(preprocessed_plan) => {
+        Self::internal_execute_batches(preprocessed_plan, &|_| None, &|plan| plan)
+      },
+      Err(err) => (plan.clone(), OptimizationReport::new(), Err(err))
+    }
+  }
+
+  /// Internal method to execute the batches of rules with child subtrees of the
+  /// preprocessed root optimized in separate threads, see
+  /// `parallelize_independent_subtrees`.
+  ///
+  /// Children are recombined by position and the first error in child order is
+  /// returned, so the result does not depend on thread scheduling.
+  fn internal_execute_subtrees(plan: &Self::Plan) -> Result<Self::Plan, CatalystError> {
+    let root = Self::preprocess(plan.clone())?;
+    if root.num_children() < 2 {
+      let (plan, _, status) =
+        Self::internal_execute_batches(root, &|_| None, &|plan| plan);
+      return status.map(|_| plan);
+    }
+    let results: Vec<Result<Self::Plan, CatalystError>> = thread::scope(|scope| {
+      let root = &root;
+      let handles: Vec<_> = (0..root.num_children()).map(|idx| {
+        let child = root.get_child(idx).unwrap().clone();
+        scope.spawn(move || {
+          let full_plan = |subtree: &Self::Plan| {
+            let mut full = root.clone();
+            full.set_child(idx, subtree.clone());
+            Some(full)
+          };
+          let (child, _, status) =
+            Self::internal_execute_batches(child, &full_plan, &|plan| plan);
+          status.map(|_| child)
+        })
+      }).collect();
+      handles.into_iter().map(|handle| {
+        handle.join().unwrap_or_else(|err| panic::resume_unwind(err))
+      }).collect()
+    });
+    let mut children = Vec::with_capacity(results.len());
+    for result in results {
+      children.push(result?);
+    }
+    let keep_children = |mut plan: Self::Plan| {
+      if plan.num_children() == children.len() {
+        for (idx, child) in children.iter().enumerate() {
+          plan.set_child(idx, child.clone());
+        }
+      }
+      plan
+    };
+    let updated_plan = keep_children(root.clone());
+    let (plan, _, status) =
+      Self::internal_execute_batches(updated_plan, &|_| None, &keep_children);
+    status.map(|_| plan)
+  }
+
+  /// Internal method to execute the batches of rules on the preprocessed plan.
+  ///
+  /// `full_plan` returns the full plan that contains the current plan to run
+  /// `should_run` and `is_plan_integral` on, or `None` if current plan is the full plan;
+  /// `update` is applied to each plan returned by a rule.
+  fn internal_execute_batches<F, U>(
+    plan: Self::Plan,
+    full_plan: &F,
+    update: &U
+  ) -> (Self::Plan, OptimizationReport, Result<(), CatalystError>)
+    where F: Fn(&Self::Plan) -> Option<Self::Plan>, U: Fn(Self::Plan) -> Self::Plan
+  {
+    let mut report = OptimizationReport::new();
     // current plan for update
-    let mut current_plan = plan.clone();
+    let mut current_plan = plan;
+
+    let enabled_rules = Self::enabled_rules();
+    let check_integral = |plan: &Self::Plan| match full_plan(plan) {
+      Some(full) => Self::is_plan_integral(&full),
+      None => Self::is_plan_integral(plan)
+    };
 
     for batch in Self::batches() {
+      let should_run = match full_plan(&current_plan) {
+        Some(full) => batch.should_run(&full),
+        None => batch.should_run(&current_plan)
+      };
+      if !should_run {
+        debug!("Skipping batch {}", batch.name());
+        continue;
+      }
+      debug!("Running batch {} with strategy {}", batch.name(), batch.strategy());
+      let mut batch_report = BatchReport::new(batch.name());
       let mut iteration = 1;
       let mut do_continue = true;
       // initial batch plan
       let batch_start_plan = current_plan.clone();
       // last plan after applying batch
       let mut last_plan = current_plan.clone();
+      let mut last_hash = Self::plan_hash(&last_plan);
 
       while do_continue {
         for rule in batch.rules() {
-          if let Some(updated_plan) = rule.apply(&current_plan) {
-            current_plan = updated_plan;
+          if let Some(ref names) = enabled_rules {
+            if !names.contains(&rule.name()) {
+              continue;
+            }
           }
+          let result = rule.apply(&current_plan);
+          batch_report.add_applied_rule(rule.name(), result.is_some());
+          let is_integral = match result {
+            Some(updated_plan) => {
+              let updated_plan = update(updated_plan);
+              let is_integral = check_integral(&updated_plan);
+              if is_integral {
+                if updated_plan != current_plan {
+                  batch_report.add_effective_rule(rule.name());
+                }
+                current_plan = updated_plan;
+              }
+              is_integral
+            },
+            None => check_integral(&current_plan)
+          };
 
-          if !Self::is_plan_integral(&current_plan) {
-            return tree_err!("After applying rule {} in batch {}, the structural \
+          if !is_integral {
+            batch_report.iterations = iteration;
+            report.batches.push(batch_report);
+            let err = tree_err!("After applying rule {} in batch {}, the structural \
               integrity of the plan is broken", rule.name(), batch.name());
+            return (current_plan, report, err);
           }
         }
         iteration += 1;
@@ -110,16 +611,22 @@ pub trait RuleExecutor {
           do_continue = false;
         }
 
-        if current_plan == last_plan {
+        let current_hash = Self::plan_hash(&current_plan);
+        if Self::is_same_plan(&current_plan, current_hash, &last_plan, last_hash) {
           // if current plan does not change anymore for fixed point
           debug!("Fixed point reached for batch {} after {} iterations",
             batch.name(), iteration - 1);
+          batch_report.fixed_point = true;
           do_continue = false;
         } else {
           last_plan = current_plan.clone();
+          last_hash = current_hash;
         }
       }
 
+      batch_report.iterations = iteration - 1;
+      report.batches.push(batch_report);
+
       if batch_start_plan != current_plan {
         // TODO: improve log message to show plan difference
         debug!("Batch {} updated current plan", batch.name());
@@ -127,6 +634,768 @@ pub trait RuleExecutor {
         debug!("Batch {} has no effect", batch.name());
       }
     }
-    Ok(current_plan)
+    (current_plan, report, Ok(()))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::sync::atomic::{AtomicUsize, Ordering};
+
+  // == Test rules and batches ==
+  // Plan is a simple integer, rules modify the value.
+
+  impl TreeNode<i32> for i32 {
+    fn node_name(&self) -> String { self.to_string() }
+
+    fn verbose_string(&self) -> String { self.to_string() }
+
+    fn get(&self) -> &i32 { self }
+
+    fn num_children(&self) -> usize { 0 }
+
+    fn get_child(&self, _idx: usize) -> Option<&i32> { None }
+
+    fn set_child(&mut self, _idx: usize, _child: i32) { }
+
+    fn clone_tree(&self) -> i32 { *self }
+
+    fn equals(&self, other: &i32) -> bool { self == other }
+  }
+
+  struct IncrementRule;
+
+  impl Rule for IncrementRule {
+    type Plan = i32;
+
+    fn name(&self) -> String { "IncrementRule".to_owned() }
+
+    fn apply(&self, plan: &i32) -> Option<i32> { Some(plan + 1) }
+  }
+
+  // Increments plan until it reaches the limit.
+  struct LimitedIncrementRule(i32);
+
+  impl Rule for LimitedIncrementRule {
+    type Plan = i32;
+
+    fn name(&self) -> String { "LimitedIncrementRule".to_owned() }
+
+    fn apply(&self, plan: &i32) -> Option<i32> {
+      if *plan < self.0 { Some(plan + 1) } else { None }
+    }
+  }
+
+  // Always returns the same plan.
+  struct NoopRule;
+
+  impl Rule for NoopRule {
+    type Plan = i32;
+
+    fn name(&self) -> String { "NoopRule".to_owned() }
+
+    fn apply(&self, plan: &i32) -> Option<i32> { Some(*plan) }
+  }
+
+  // Never matches the plan.
+  struct NeverRule;
+
+  impl Rule for NeverRule {
+    type Plan = i32;
+
+    fn name(&self) -> String { "NeverRule".to_owned() }
+
+    fn apply(&self, _plan: &i32) -> Option<i32> { None }
+  }
+
+  // Replaces plan `from` with plan `to`.
+  struct ReplaceRule(i32, i32);
+
+  impl Rule for ReplaceRule {
+    type Plan = i32;
+
+    fn name(&self) -> String { format!("ReplaceRule({}, {})", self.0, self.1) }
+
+    fn apply(&self, plan: &i32) -> Option<i32> {
+      if *plan == self.0 { Some(self.1) } else { None }
+    }
+  }
+
+  struct TestBatch {
+    name: String,
+    strategy: Strategy,
+    rules: Vec<Rc<Rule<Plan=i32>>>
+  }
+
+  impl TestBatch {
+    fn new(name: &str, strategy: Strategy, rules: Vec<Rc<Rule<Plan=i32>>>) -> Self {
+      Self { name: name.to_owned(), strategy, rules }
+    }
+  }
+
+  impl Batch for TestBatch {
+    type Plan = i32;
+
+    fn name(&self) -> String { self.name.clone() }
+
+    fn strategy(&self) -> &Strategy { &self.strategy }
+
+    fn rules(&self) -> &Vec<Rc<Rule<Plan=i32>>> { &self.rules }
+  }
+
+  // Batch that is only run when plan is at least `min_plan`.
+  struct ConditionalBatch {
+    batch: TestBatch,
+    min_plan: i32
+  }
+
+  impl Batch for ConditionalBatch {
+    type Plan = i32;
+
+    fn name(&self) -> String { self.batch.name() }
+
+    fn strategy(&self) -> &Strategy { self.batch.strategy() }
+
+    fn rules(&self) -> &Vec<Rc<Rule<Plan=i32>>> { self.batch.rules() }
+
+    fn should_run(&self, plan: &i32) -> bool { *plan >= self.min_plan }
+  }
+
+  struct ConditionalExecutor;
+
+  impl RuleExecutor for ConditionalExecutor {
+    type Plan = i32;
+
+    fn batches() -> Vec<Box<Batch<Plan=i32>>> {
+      vec![
+        Box::new(ConditionalBatch {
+          batch: TestBatch::new("batch1", Strategy::Once, vec![Rc::new(IncrementRule)]),
+          min_plan: 10
+        }),
+        Box::new(TestBatch::new("batch2", Strategy::Once, vec![Rc::new(NoopRule)]))
+      ]
+    }
+
+    fn is_plan_integral(plan: &i32) -> bool { *plan >= 0 }
+  }
+
+  // Executor with controlled plan hash that always collides.
+  struct CollidingHashExecutor;
+
+  impl RuleExecutor for CollidingHashExecutor {
+    type Plan = i32;
+
+    fn batches() -> Vec<Box<Batch<Plan=i32>>> {
+      vec![
+        Box::new(TestBatch::new("batch", Strategy::FixedPoint(10), vec![
+          Rc::new(LimitedIncrementRule(3))
+        ]))
+      ]
+    }
+
+    fn is_plan_integral(plan: &i32) -> bool { *plan >= 0 }
+
+    fn plan_hash(_plan: &i32) -> Option<u64> { Some(0) }
+  }
+
+  // Executor with plan hash that is unique for each plan.
+  struct HashExecutor;
+
+  impl RuleExecutor for HashExecutor {
+    type Plan = i32;
+
+    fn batches() -> Vec<Box<Batch<Plan=i32>>> { CollidingHashExecutor::batches() }
+
+    fn is_plan_integral(plan: &i32) -> bool { *plan >= 0 }
+
+    fn plan_hash(plan: &i32) -> Option<u64> { Some(*plan as u64) }
+  }
+
+  fn get_test_registry() -> RuleRegistry<i32> {
+    let mut registry = RuleRegistry::new();
+    registry.register("increment", Box::new(|| Rc::new(IncrementRule)));
+    registry.register("limited", Box::new(|| Rc::new(LimitedIncrementRule(5))));
+    registry
+  }
+
+  fn get_test_config(rule: &str) -> Vec<(String, Strategy, Vec<String>)> {
+    vec![
+      ("batch1".to_owned(), Strategy::FixedPoint(10), vec!["limited".to_owned()]),
+      ("batch2".to_owned(), Strategy::Once, vec![rule.to_owned(), rule.to_owned()])
+    ]
+  }
+
+  struct RegistryExecutor;
+
+  impl RuleExecutor for RegistryExecutor {
+    type Plan = i32;
+
+    fn batches() -> Vec<Box<Batch<Plan=i32>>> {
+      build_batches(&get_test_config("increment"), &get_test_registry()).unwrap()
+    }
+
+    fn is_plan_integral(plan: &i32) -> bool { *plan >= 0 }
+  }
+
+  // Breaks integrity of the plan by making it negative.
+  struct NegateRule;
+
+  impl Rule for NegateRule {
+    type Plan = i32;
+
+    fn name(&self) -> String { "NegateRule".to_owned() }
+
+    fn apply(&self, plan: &i32) -> Option<i32> { Some(-plan) }
+  }
+
+  struct BrokenExecutor;
+
+  impl RuleExecutor for BrokenExecutor {
+    type Plan = i32;
+
+    fn batches() -> Vec<Box<Batch<Plan=i32>>> {
+      vec![
+        Box::new(TestBatch::new("batch1", Strategy::Once, vec![Rc::new(IncrementRule)])),
+        Box::new(TestBatch::new("batch2", Strategy::Once, vec![
+          Rc::new(IncrementRule),
+          Rc::new(NegateRule),
+          Rc::new(IncrementRule)
+        ]))
+      ]
+    }
+
+    fn is_plan_integral(plan: &i32) -> bool { *plan >= 0 }
+  }
+
+  struct SharedRuleExecutor;
+
+  impl RuleExecutor for SharedRuleExecutor {
+    type Plan = i32;
+
+    fn batches() -> Vec<Box<Batch<Plan=i32>>> {
+      let rule: Rc<Rule<Plan=i32>> = Rc::new(IncrementRule);
+      vec![
+        Box::new(TestBatch::new("batch1", Strategy::Once, vec![rule.clone()])),
+        Box::new(TestBatch::new("batch2", Strategy::Once, vec![rule]))
+      ]
+    }
+
+    fn is_plan_integral(plan: &i32) -> bool { *plan >= 0 }
+  }
+
+  struct ReportingExecutor;
+
+  impl RuleExecutor for ReportingExecutor {
+    type Plan = i32;
+
+    fn batches() -> Vec<Box<Batch<Plan=i32>>> {
+      vec![
+        Box::new(TestBatch::new("batch1", Strategy::FixedPoint(10), vec![
+          Rc::new(NoopRule),
+          Rc::new(LimitedIncrementRule(1))
+        ])),
+        Box::new(TestBatch::new("batch2", Strategy::Once, vec![Rc::new(IncrementRule)]))
+      ]
+    }
+
+    fn is_plan_integral(plan: &i32) -> bool { *plan >= 0 }
+  }
+
+  struct UnusedRulesExecutor;
+
+  impl RuleExecutor for UnusedRulesExecutor {
+    type Plan = i32;
+
+    fn batches() -> Vec<Box<Batch<Plan=i32>>> {
+      vec![
+        Box::new(TestBatch::new("batch1", Strategy::FixedPoint(10), vec![
+          Rc::new(LimitedIncrementRule(3)),
+          Rc::new(NeverRule)
+        ])),
+        Box::new(TestBatch::new("batch2", Strategy::Once, vec![
+          Rc::new(NeverRule),
+          Rc::new(NoopRule)
+        ])),
+        Box::new(ConditionalBatch {
+          batch: TestBatch::new("batch3", Strategy::Once, vec![Rc::new(NeverRule)]),
+          min_plan: 100
+        })
+      ]
+    }
+
+    fn is_plan_integral(plan: &i32) -> bool { *plan >= 0 }
+  }
+
+  // Rule that replaces 1 with 2 depends on the rule that replaces 0 with 1, but runs
+  // first.
+  struct MisorderedExecutor;
+
+  impl RuleExecutor for MisorderedExecutor {
+    type Plan = i32;
+
+    fn batches() -> Vec<Box<Batch<Plan=i32>>> {
+      vec![
+        Box::new(TestBatch::new("batch1", Strategy::Once, vec![
+          Rc::new(ReplaceRule(1, 2)),
+          Rc::new(ReplaceRule(0, 1))
+        ]))
+      ]
+    }
+
+    fn is_plan_integral(plan: &i32) -> bool { *plan >= 0 }
+  }
+
+  struct OrderedExecutor;
+
+  impl RuleExecutor for OrderedExecutor {
+    type Plan = i32;
+
+    fn batches() -> Vec<Box<Batch<Plan=i32>>> {
+      vec![
+        Box::new(TestBatch::new("batch1", Strategy::Once, vec![
+          Rc::new(ReplaceRule(0, 1)),
+          Rc::new(ReplaceRule(1, 2))
+        ]))
+      ]
+    }
+
+    fn is_plan_integral(plan: &i32) -> bool { *plan >= 0 }
+  }
+
+  // Only limited increment rule is enabled.
+  struct EnabledRulesExecutor;
+
+  impl RuleExecutor for EnabledRulesExecutor {
+    type Plan = i32;
+
+    fn batches() -> Vec<Box<Batch<Plan=i32>>> {
+      vec![
+        Box::new(TestBatch::new("batch1", Strategy::FixedPoint(10), vec![
+          Rc::new(IncrementRule),
+          Rc::new(LimitedIncrementRule(5)),
+          Rc::new(NegateRule)
+        ]))
+      ]
+    }
+
+    fn is_plan_integral(plan: &i32) -> bool { *plan >= 0 }
+
+    fn enabled_rules() -> Option<Vec<String>> {
+      Some(vec!["LimitedIncrementRule".to_owned()])
+    }
+  }
+
+  // Tags plan by multiplying it by 10 before batches run, rejects negative plans.
+  struct PreprocessExecutor;
+
+  impl RuleExecutor for PreprocessExecutor {
+    type Plan = i32;
+
+    fn batches() -> Vec<Box<Batch<Plan=i32>>> {
+      vec![
+        Box::new(TestBatch::new("batch1", Strategy::Once, vec![Rc::new(IncrementRule)]))
+      ]
+    }
+
+    fn is_plan_integral(plan: &i32) -> bool { *plan >= 0 }
+
+    fn preprocess(plan: i32) -> Result<i32, CatalystError> {
+      if plan < 0 { tree_err!("Negative plan {}", plan) } else { Ok(plan * 10) }
+    }
+  }
+
+  #[test]
+  fn test_execute_preprocess() {
+    assert_eq!(PreprocessExecutor::execute(&3).unwrap(), 31);
+    let (plan, report) = PreprocessExecutor::execute_reporting(&0).unwrap();
+    assert_eq!(plan, 1);
+    assert_eq!(report.batches().len(), 1);
+
+    let (plan, status) = PreprocessExecutor::execute_keep_partial(&-2);
+    assert_eq!(plan, -2);
+    match status {
+      Err(CatalystError::Tree(msg)) => assert_eq!(msg, "Negative plan -2"),
+      _ => panic!("Expected tree error")
+    }
+    // default preprocess keeps the plan
+    assert_eq!(ReportingExecutor::execute(&0).unwrap(), 2);
+  }
+
+  // Tree plan, where each node has a value.
+  #[derive(Clone, Debug, PartialEq)]
+  struct TreePlan {
+    value: i32,
+    children: Vec<TreePlan>
+  }
+
+  impl TreePlan {
+    fn new(value: i32, children: Vec<TreePlan>) -> Self {
+      Self { value, children }
+    }
+  }
+
+  impl TreeNode<TreePlan> for TreePlan {
+    fn node_name(&self) -> String { format!("{}", self.value) }
+
+    fn verbose_string(&self) -> String { format!("({})", self.value) }
+
+    fn get(&self) -> &TreePlan { self }
+
+    fn num_children(&self) -> usize { self.children.len() }
+
+    fn get_child(&self, idx: usize) -> Option<&TreePlan> { self.children.get(idx) }
+
+    fn set_child(&mut self, idx: usize, child: TreePlan) { self.children[idx] = child; }
+
+    fn clone_tree(&self) -> TreePlan { self.clone() }
+
+    fn equals(&self, other: &TreePlan) -> bool { self.eq(other) }
+  }
+
+  // Increments leaf values until they reach 5, sets value of each inner node to the sum
+  // of values of its children.
+  struct SumTreeRule;
+
+  impl Rule for SumTreeRule {
+    type Plan = TreePlan;
+
+    fn name(&self) -> String { "SumTreeRule".to_owned() }
+
+    fn apply(&self, plan: &TreePlan) -> Option<TreePlan> {
+      Some(plan.transform_up(&mut |node| {
+        let value = if node.is_leaf() {
+          if node.value < 5 { node.value + 1 } else { node.value }
+        } else {
+          node.children.iter().map(|child| child.value).sum()
+        };
+        Some(TreePlan::new(value, node.children.clone()))
+      }))
+    }
+  }
+
+  struct TreeBatch {
+    rules: Vec<Rc<Rule<Plan=TreePlan>>>,
+    strategy: Strategy
+  }
+
+  impl Batch for TreeBatch {
+    type Plan = TreePlan;
+
+    fn name(&self) -> String { "tree".to_owned() }
+
+    fn strategy(&self) -> &Strategy { &self.strategy }
+
+    fn rules(&self) -> &Vec<Rc<Rule<Plan=TreePlan>>> { &self.rules }
+  }
+
+  fn tree_batches() -> Vec<Box<Batch<Plan=TreePlan>>> {
+    vec![Box::new(TreeBatch {
+      rules: vec![Rc::new(SumTreeRule)],
+      strategy: Strategy::FixedPoint(100)
+    })]
+  }
+
+  // Plan is integral if all values in the tree are non-negative.
+  fn is_tree_integral(plan: &TreePlan) -> bool {
+    plan.find(&mut |node| node.value < 0).is_none()
+  }
+
+  struct SerialTreeExecutor;
+
+  impl RuleExecutor for SerialTreeExecutor {
+    type Plan = TreePlan;
+
+    fn batches() -> Vec<Box<Batch<Plan=TreePlan>>> { tree_batches() }
+
+    fn is_plan_integral(plan: &TreePlan) -> bool { is_tree_integral(plan) }
+  }
+
+  struct ParallelTreeExecutor;
+
+  impl RuleExecutor for ParallelTreeExecutor {
+    type Plan = TreePlan;
+
+    fn batches() -> Vec<Box<Batch<Plan=TreePlan>>> { tree_batches() }
+
+    fn is_plan_integral(plan: &TreePlan) -> bool { is_tree_integral(plan) }
+
+    fn parallelize_independent_subtrees() -> bool { true }
+  }
+
+  static INCREMENT_TREE_APPLIED: AtomicUsize = AtomicUsize::new(0);
+
+  // Increments value of each node, the rule is not idempotent.
+  struct IncrementTreeRule;
+
+  impl Rule for IncrementTreeRule {
+    type Plan = TreePlan;
+
+    fn name(&self) -> String { "IncrementTreeRule".to_owned() }
+
+    fn apply(&self, plan: &TreePlan) -> Option<TreePlan> {
+      INCREMENT_TREE_APPLIED.fetch_add(1, Ordering::SeqCst);
+      Some(plan.transform_up(&mut |node| {
+        Some(TreePlan::new(node.value + 1, node.children.clone()))
+      }))
+    }
+  }
+
+  fn increment_tree_batches() -> Vec<Box<Batch<Plan=TreePlan>>> {
+    vec![Box::new(TreeBatch {
+      rules: vec![Rc::new(IncrementTreeRule)],
+      strategy: Strategy::Once
+    })]
+  }
+
+  struct SerialIncrementTreeExecutor;
+
+  impl RuleExecutor for SerialIncrementTreeExecutor {
+    type Plan = TreePlan;
+
+    fn batches() -> Vec<Box<Batch<Plan=TreePlan>>> { increment_tree_batches() }
+
+    fn is_plan_integral(plan: &TreePlan) -> bool { is_tree_integral(plan) }
+  }
+
+  struct ParallelIncrementTreeExecutor;
+
+  impl RuleExecutor for ParallelIncrementTreeExecutor {
+    type Plan = TreePlan;
+
+    fn batches() -> Vec<Box<Batch<Plan=TreePlan>>> { increment_tree_batches() }
+
+    fn is_plan_integral(plan: &TreePlan) -> bool { is_tree_integral(plan) }
+
+    fn parallelize_independent_subtrees() -> bool { true }
+  }
+
+  #[test]
+  fn test_execute_parallel() {
+    let plan = TreePlan::new(0, vec![
+      TreePlan::new(0, vec![TreePlan::new(1, vec![]), TreePlan::new(3, vec![])]),
+      TreePlan::new(0, vec![TreePlan::new(0, vec![TreePlan::new(2, vec![])])])
+    ]);
+    let serial = SerialTreeExecutor::execute(&plan).unwrap();
+    assert_eq!(serial.value, 15);
+    assert_eq!(ParallelTreeExecutor::execute(&plan).unwrap(), serial);
+
+    // error in one of the children is returned
+    let plan = TreePlan::new(0, vec![
+      TreePlan::new(1, vec![]),
+      TreePlan::new(-10, vec![])
+    ]);
+    match ParallelTreeExecutor::execute(&plan) {
+      Err(CatalystError::Tree(msg)) => assert_eq!(msg, "After applying rule SumTreeRule \
+        in batch tree, the structural integrity of the plan is broken"),
+      _ => panic!("Expected tree error")
+    }
+  }
+
+  #[test]
+  fn test_execute_parallel_once_batch() {
+    let plan = TreePlan::new(0, vec![
+      TreePlan::new(1, vec![TreePlan::new(2, vec![])]),
+      TreePlan::new(3, vec![])
+    ]);
+    let expected = TreePlan::new(1, vec![
+      TreePlan::new(2, vec![TreePlan::new(3, vec![])]),
+      TreePlan::new(4, vec![])
+    ]);
+    let serial = SerialIncrementTreeExecutor::execute(&plan).unwrap();
+    assert_eq!(serial, expected);
+    INCREMENT_TREE_APPLIED.store(0, Ordering::SeqCst);
+    assert_eq!(ParallelIncrementTreeExecutor::execute(&plan).unwrap(), serial);
+    // batch runs once for each child subtree and once for the root
+    assert_eq!(INCREMENT_TREE_APPLIED.load(Ordering::SeqCst), 3);
+  }
+
+  #[test]
+  fn test_execute_reporting() {
+    let (plan, report) = ReportingExecutor::execute_reporting(&0).unwrap();
+    assert_eq!(plan, 2);
+    assert_eq!(report.batches().len(), 2);
+
+    let batch = &report.batches()[0];
+    assert_eq!(batch.name(), "batch1");
+    assert_eq!(batch.iterations(), 2);
+    assert!(batch.fixed_point());
+    assert_eq!(batch.effective_rules(), &["LimitedIncrementRule".to_owned()]);
+
+    let batch = &report.batches()[1];
+    assert_eq!(batch.name(), "batch2");
+    assert_eq!(batch.iterations(), 1);
+    assert!(!batch.fixed_point());
+    assert_eq!(batch.effective_rules(), &["IncrementRule".to_owned()]);
+
+    assert_eq!(ReportingExecutor::execute(&0).unwrap(), plan);
+  }
+
+  #[test]
+  fn test_execute_enabled_rules() {
+    let (plan, report) = EnabledRulesExecutor::execute_reporting(&0).unwrap();
+    assert_eq!(plan, 5);
+    let batch = &report.batches()[0];
+    assert_eq!(batch.effective_rules(), &["LimitedIncrementRule".to_owned()]);
+    assert!(batch.unused_rules().is_empty());
+    assert!(batch.fixed_point());
+    assert_eq!(ReportingExecutor::enabled_rules(), None);
+  }
+
+  #[test]
+  fn test_validate_rule_order() {
+    assert_eq!(MisorderedExecutor::execute(&0).unwrap(), 1);
+    assert_eq!(OrderedExecutor::execute(&0).unwrap(), 2);
+
+    if cfg!(debug_assertions) {
+      assert!(!MisorderedExecutor::validate_rule_order(&0).unwrap());
+      assert!(MisorderedExecutor::validate_rule_order(&1).unwrap());
+      assert!(OrderedExecutor::validate_rule_order(&0).unwrap());
+      assert!(BrokenExecutor::validate_rule_order(&1).is_err());
+    } else {
+      assert!(MisorderedExecutor::validate_rule_order(&0).unwrap());
+      assert!(BrokenExecutor::validate_rule_order(&1).unwrap());
+    }
+  }
+
+  #[test]
+  fn test_execute_with_unused() {
+    let (plan, unused) = UnusedRulesExecutor::execute_with_unused(&0).unwrap();
+    assert_eq!(plan, 3);
+    assert_eq!(unused, vec!["NeverRule".to_owned()]);
+
+    let (_, report) = UnusedRulesExecutor::execute_reporting(&0).unwrap();
+    assert_eq!(report.batches()[0].unused_rules(), vec!["NeverRule".to_owned()]);
+    assert_eq!(report.batches()[1].unused_rules(), vec!["NeverRule".to_owned()]);
+
+    // rule that returned updated plan in one batch is not reported as unused
+    let (_, unused) = ReportingExecutor::execute_with_unused(&0).unwrap();
+    assert_eq!(unused, Vec::<String>::new());
+  }
+
+  #[test]
+  fn test_execute_skip_batch() {
+    let (plan, report) = ConditionalExecutor::execute_reporting(&1).unwrap();
+    assert_eq!(plan, 1);
+    assert_eq!(report.batches().len(), 1);
+    assert_eq!(report.batches()[0].name(), "batch2");
+
+    let (plan, report) = ConditionalExecutor::execute_reporting(&10).unwrap();
+    assert_eq!(plan, 11);
+    assert_eq!(report.batches().len(), 2);
+    assert_eq!(report.batches()[0].effective_rules(), &["IncrementRule".to_owned()]);
+  }
+
+  #[test]
+  fn test_execute_plan_hash() {
+    // equal hashes must not be treated as fixed point when plans differ
+    let (plan, report) = CollidingHashExecutor::execute_reporting(&0).unwrap();
+    assert_eq!(plan, 3);
+    assert_eq!(report.batches()[0].iterations(), 4);
+    assert!(report.batches()[0].fixed_point());
+
+    let (plan, report) = HashExecutor::execute_reporting(&0).unwrap();
+    assert_eq!(plan, 3);
+    assert_eq!(report.batches()[0].iterations(), 4);
+    assert!(report.batches()[0].fixed_point());
+  }
+
+  #[test]
+  fn test_is_same_plan() {
+    assert!(HashExecutor::is_same_plan(&1, Some(1), &1, Some(1)));
+    assert!(HashExecutor::is_same_plan(&1, None, &1, None));
+    assert!(!HashExecutor::is_same_plan(&1, None, &2, None));
+    assert!(!HashExecutor::is_same_plan(&1, Some(0), &2, Some(0)));
+    // different hashes short-circuit equality check
+    assert!(!HashExecutor::is_same_plan(&1, Some(1), &1, Some(2)));
+  }
+
+  #[test]
+  fn test_rule_registry() {
+    let registry = get_test_registry();
+    assert!(registry.contains("increment"));
+    assert!(!registry.contains("unknown"));
+    assert_eq!(registry.create("limited").unwrap().name(), "LimitedIncrementRule");
+    assert!(registry.create("unknown").is_none());
+  }
+
+  #[test]
+  fn test_build_batches() {
+    let batches = RegistryExecutor::batches();
+    assert_eq!(batches.len(), 2);
+    assert_eq!(batches[0].name(), "batch1");
+    assert_eq!(batches[0].strategy(), &Strategy::FixedPoint(10));
+    assert_eq!(batches[0].rules().len(), 1);
+    assert_eq!(batches[1].name(), "batch2");
+    assert_eq!(batches[1].strategy(), &Strategy::Once);
+    assert_eq!(batches[1].rules().len(), 2);
+
+    assert_eq!(RegistryExecutor::execute(&0).unwrap(), 7);
+  }
+
+  #[test]
+  fn test_build_batches_unknown_rule() {
+    match build_batches(&get_test_config("unknown"), &get_test_registry()) {
+      Err(CatalystError::Rule(msg)) => {
+        assert_eq!(msg, "Unknown rule unknown in batch batch2")
+      },
+      _ => panic!("Expected rule error")
+    }
+  }
+
+  #[test]
+  fn test_execute_keep_partial() {
+    let (plan, status) = BrokenExecutor::execute_keep_partial(&1);
+    assert_eq!(plan, 3);
+    match status {
+      Err(CatalystError::Tree(msg)) => assert_eq!(msg, "After applying rule NegateRule \
+        in batch batch2, the structural integrity of the plan is broken"),
+      _ => panic!("Expected tree error")
+    }
+    assert!(BrokenExecutor::execute(&1).is_err());
+
+    let (plan, status) = ReportingExecutor::execute_keep_partial(&0);
+    assert_eq!(plan, 2);
+    assert!(status.is_ok());
+  }
+
+  #[test]
+  fn test_optimization_report_format() {
+    let (_, report) = ReportingExecutor::execute_reporting(&0).unwrap();
+    assert_eq!(report.to_json(), "{\"batches\":[\
+      {\"name\":\"batch1\",\"iterations\":2,\"fixedPoint\":true,\
+        \"effectiveRules\":[\"LimitedIncrementRule\"]},\
+      {\"name\":\"batch2\",\"iterations\":1,\"fixedPoint\":false,\
+        \"effectiveRules\":[\"IncrementRule\"]}]}");
+    assert_eq!(report.to_string(), [
+      "Batch batch1: iterations = 2, fixed point = true, \
+        effective rules = [LimitedIncrementRule]",
+      "Batch batch2: iterations = 1, fixed point = false, \
+        effective rules = [IncrementRule]"
+    ].join("\n"));
+  }
+
+  #[test]
+  fn test_strategy_display() {
+    assert_eq!(Strategy::Once.to_string(), "Once");
+    assert_eq!(Strategy::FixedPoint(3).to_string(), "FixedPoint(3)");
+  }
+
+  #[test]
+  fn test_strategy_is_fixed_point() {
+    assert!(!Strategy::Once.is_fixed_point());
+    assert!(Strategy::FixedPoint(3).is_fixed_point());
+    assert_eq!(Strategy::Once.num_iterations(), 1);
+    assert_eq!(Strategy::FixedPoint(3).num_iterations(), 3);
+  }
+
+  #[test]
+  fn test_batches_share_rule() {
+    let batches = SharedRuleExecutor::batches();
+    assert!(Rc::ptr_eq(&batches[0].rules()[0], &batches[1].rules()[0]));
+    assert_eq!(Rc::strong_count(&batches[0].rules()[0]), 2);
+
+    // shared rule is applied once in each batch
+    assert_eq!(SharedRuleExecutor::execute(&0).unwrap(), 2);
   }
 }
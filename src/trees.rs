@@ -26,6 +26,52 @@
 //!   or vice versa.
 //! - debugging support - pretty printing, tree structure display, etc.
 
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use json::JsonValue;
+
+/// Single difference between two trees reported by `TreeNode::diff`.
+///
+/// Path is a list of child indices from the root to the node, root has empty path.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TreeEdit {
+  /// Node at the path has a different name.
+  Changed { path: Vec<usize>, old_name: String, new_name: String },
+  /// Node at the path has a different number of children, its subtree is not compared.
+  ChildCountChanged { path: Vec<usize>, old: usize, new: usize }
+}
+
+/// Returns `true` if node at `ancestor` path is a strict ancestor of node at
+/// `descendant` path, i.e. `ancestor` is a proper prefix of `descendant`.
+///
+/// Paths are lists of child indices from the root, same as in `TreeEdit`; node is not
+/// considered its own ancestor.
+pub fn is_ancestor_path(ancestor: &[usize], descendant: &[usize]) -> bool {
+  ancestor.len() < descendant.len() && descendant.starts_with(ancestor)
+}
+
+/// Internal method to convert node and its children into JSON object with `name`,
+/// attributes returned by `attrs`, and `children`.
+fn json_value_with<A, F>(node: &A, attrs: &mut F) -> JsonValue
+  where A: TreeNode<A>, F: FnMut(&A) -> Vec<(String, String)>
+{
+  let mut entries = vec![("name".to_owned(), JsonValue::String(node.node_name()))];
+  for (key, value) in attrs(node.get()) {
+    entries.push((key, JsonValue::String(value)));
+  }
+  let mut children = Vec::with_capacity(node.num_children());
+  let mut idx = 0;
+  while let Some(child) = node.get_child(idx) {
+    children.push(json_value_with(child, attrs));
+    idx += 1;
+  }
+  entries.push(("children".to_owned(), JsonValue::Array(children)));
+  JsonValue::Object(entries)
+}
+
 /// Generic `TreeNode` to provide traversal and transform.
 pub trait TreeNode<A: TreeNode<A>> {
   /// Returns string label for this node.
@@ -82,6 +128,97 @@ pub trait TreeNode<A: TreeNode<A>> {
     None
   }
 
+  /// Internal method to find path to the first node that matches predicate function in
+  /// pre-order, returns `true` if node is found.
+  fn internal_find_path<F>(
+    &self,
+    predicate: &mut F,
+    path: &mut Vec<usize>
+  ) -> bool where F: FnMut(&A) -> bool
+  {
+    if predicate(self.get()) {
+      return true;
+    }
+    for idx in 0..self.num_children() {
+      path.push(idx);
+      if self.get_child(idx).unwrap().internal_find_path(predicate, path) {
+        return true;
+      }
+      path.pop();
+    }
+    false
+  }
+
+  /// Returns path of child indices from this node to the lowest common ancestor of the
+  /// first nodes (pre-order) that match predicates `a` and `b`, or `None` if either node
+  /// is not found. Node is considered an ancestor of itself.
+  fn common_ancestor<P1, P2>(
+    &self,
+    a: &mut P1,
+    b: &mut P2
+  ) -> Option<Vec<usize>> where P1: FnMut(&A) -> bool, P2: FnMut(&A) -> bool
+  {
+    let mut path_a = vec![];
+    let mut path_b = vec![];
+    if !self.internal_find_path(a, &mut path_a) {
+      return None;
+    }
+    if !self.internal_find_path(b, &mut path_b) {
+      return None;
+    }
+    Some(path_a.iter().zip(path_b.iter()).take_while(|&(x, y)| x == y).map(|(x, _)| *x)
+      .collect())
+  }
+
+  /// Returns number of nodes in this tree that match predicate function.
+  fn count_matching<F>(&self, predicate: &mut F) -> usize where F: FnMut(&A) -> bool {
+    let mut count = 0;
+    self.foreach(&mut |node| if predicate(node) { count += 1; });
+    count
+  }
+
+  /// Returns number of nodes in this tree for each node name.
+  fn count_by_name(&self) -> BTreeMap<String, usize> {
+    let mut counts = BTreeMap::new();
+    self.foreach(&mut |node| *counts.entry(node.node_name()).or_insert(0) += 1);
+    counts
+  }
+
+  /// Returns clones of all nodes of this tree in pre-order.
+  ///
+  /// Each clone is a full subtree, i.e. it carries its own children.
+  fn flatten_nodes(&self) -> Vec<A> {
+    self.map(&mut |node| node.clone_tree())
+  }
+
+  /// Returns edges of this tree as pairs of parent id and child id, where ids are
+  /// assigned to nodes in pre-order starting with 0 for this node.
+  ///
+  /// Use together with `node_labels` to export tree into external graph tools.
+  fn edge_list(&self) -> Vec<(usize, usize)> {
+    let mut edges = Vec::new();
+    let mut next_id = 0;
+    self.internal_edge_list(&mut next_id, &mut edges);
+    edges
+  }
+
+  /// Internal method to assign id to this node and collect edges to its children.
+  fn internal_edge_list(&self, next_id: &mut usize, edges: &mut Vec<(usize, usize)>) {
+    let id = *next_id;
+    *next_id += 1;
+    let mut idx = 0;
+    while let Some(child) = self.get_child(idx) {
+      edges.push((id, *next_id));
+      child.internal_edge_list(next_id, edges);
+      idx += 1;
+    }
+  }
+
+  /// Returns node names of this tree indexed by ids used in `edge_list`.
+  fn node_labels(&self) -> Vec<String> {
+    self.map(&mut |node| node.node_name())
+  }
+
   /// Runs the given function recursively on this node and then on children.
   fn foreach<F>(&self, func: &mut F) where F: FnMut(&A) {
     func(self.get());
@@ -176,6 +313,135 @@ pub trait TreeNode<A: TreeNode<A>> {
     }
   }
 
+  /// Same as `transform_down`, but `replace` is applied only to nodes that match
+  /// `predicate`, other nodes are left unchanged.
+  fn transform_down_where<P, R>(
+    &self,
+    predicate: &mut P,
+    replace: &mut R
+  ) -> A where P: FnMut(&A) -> bool, R: FnMut(&A) -> A
+  {
+    self.transform_down(&mut |node| {
+      if predicate(node) { Some(replace(node)) } else { None }
+    })
+  }
+
+  /// Same as `transform_down_where`, but also returns the number of nodes that were
+  /// replaced.
+  fn replace_all<F, R>(&self, predicate: &mut F, replace: &mut R) -> (A, usize)
+    where F: FnMut(&A) -> bool, R: FnMut(&A) -> A
+  {
+    let mut count = 0;
+    let res = self.transform_down_with_state(&mut count, &mut |count, node| {
+      if predicate(node) {
+        *count += 1;
+        Some(replace(node))
+      } else {
+        None
+      }
+    });
+    (res, count)
+  }
+
+  /// Same as `transform_down`, but threads mutable `state` through each invocation of
+  /// `rule`, e.g. to generate unique ids while rewriting the tree.
+  fn transform_down_with_state<S, F>(
+    &self,
+    state: &mut S,
+    rule: &mut F
+  ) -> A where F: FnMut(&mut S, &A) -> Option<A>
+  {
+    match rule(state, self.get()) {
+      Some(after_rule) => after_rule.map_children(&mut |node| {
+        node.transform_down_with_state(state, rule)
+      }),
+      None => self.map_children(&mut |node| node.transform_down_with_state(state, rule)),
+    }
+  }
+
+  /// Same as `transform_down`, but `rule` can fail. Traversal stops at the first error,
+  /// which is returned, remaining nodes are not visited.
+  fn transform_down_try<F, E>(&self, rule: &mut F) -> Result<A, E>
+    where F: FnMut(&A) -> Result<Option<A>, E>
+  {
+    let mut node = match rule(self.get())? {
+      Some(after_rule) => after_rule,
+      None => self.get().clone_tree()
+    };
+    let mut idx = 0;
+    while let Some(child) = node.get_child(idx) {
+      let updated_child = child.transform_down_try(rule)?;
+      node.set_child(idx, updated_child);
+      idx += 1;
+    }
+    Ok(node)
+  }
+
+  /// Same as `transform_down`, but results are cached by subtree, so identical subtrees,
+  /// e.g. after macro expansion, are transformed once and `rule` is invoked once per
+  /// distinct subtree. `rule` must return the same result for equal nodes.
+  fn transform_down_memoized<F>(&self, rule: &mut F) -> A
+    where F: FnMut(&A) -> Option<A>, A: Hash
+  {
+    let mut cache = HashMap::new();
+    self.internal_transform_down_memoized(rule, &mut cache)
+  }
+
+  /// Internal method to apply `rule` in pre-order using cache of transformed subtrees,
+  /// cache maps hash of the subtree to the list of subtrees and their results.
+  fn internal_transform_down_memoized<F>(
+    &self,
+    rule: &mut F,
+    cache: &mut HashMap<u64, Vec<(A, A)>>
+  ) -> A where F: FnMut(&A) -> Option<A>, A: Hash
+  {
+    let mut hasher = DefaultHasher::new();
+    self.get().hash(&mut hasher);
+    let key = hasher.finish();
+    if let Some(entries) = cache.get(&key) {
+      if let Some(entry) = entries.iter().find(|entry| entry.0.equals(self.get())) {
+        return entry.1.clone_tree();
+      }
+    }
+    let result = match rule(self.get()) {
+      Some(after_rule) => after_rule.map_children(&mut |node| {
+        node.internal_transform_down_memoized(rule, cache)
+      }),
+      None => self.map_children(&mut |node| {
+        node.internal_transform_down_memoized(rule, cache)
+      })
+    };
+    let entry = (self.get().clone_tree(), result.clone_tree());
+    cache.entry(key).or_default().push(entry);
+    result
+  }
+
+  /// Internal method to recursively apply `rule` with parent of the current node.
+  fn internal_transform_down_with_parent<F>(
+    &self,
+    parent: Option<&A>,
+    rule: &mut F
+  ) -> A where F: FnMut(Option<&A>, &A) -> Option<A>
+  {
+    let node = match rule(parent, self.get()) {
+      Some(after_rule) => after_rule,
+      None => self.get().clone_tree(),
+    };
+    node.map_children(&mut |child| {
+      child.internal_transform_down_with_parent(Some(&node), rule)
+    })
+  }
+
+  /// Same as `transform_down`, but `rule` also receives parent of the node (`None` for
+  /// the root). Parent is passed after `rule` has been applied to it.
+  fn transform_down_with_parent<F>(
+    &self,
+    rule: &mut F
+  ) -> A where F: FnMut(Option<&A>, &A) -> Option<A>
+  {
+    self.internal_transform_down_with_parent(None, rule)
+  }
+
   /// Return a copy of this node where `rule` has been recursively applied first to all
   /// of its children and then itself (post-order). When `rule` does not apply to a given
   /// node, it is left unchanged.
@@ -187,6 +453,64 @@ pub trait TreeNode<A: TreeNode<A>> {
     }
   }
 
+  /// Same as `transform_up`, but result of `rule` that `equals` to the node is ignored
+  /// and the node is kept unchanged, so rules that return equivalent nodes do not cause
+  /// churn in the tree.
+  fn transform_up_stable<F>(&self, rule: &mut F) -> A where F: FnMut(&A) -> Option<A> {
+    let updated_node = self.map_children(&mut |node| node.transform_up_stable(rule));
+    match rule(&updated_node) {
+      Some(ref after_rule) if after_rule.equals(&updated_node) => updated_node,
+      Some(after_rule) => after_rule,
+      None => updated_node,
+    }
+  }
+
+  /// Internal method to recursively compare this node with other node at `path`.
+  fn internal_diff(&self, other: &A, path: &mut Vec<usize>, edits: &mut Vec<TreeEdit>) {
+    if self.node_name() != other.node_name() {
+      edits.push(TreeEdit::Changed {
+        path: path.clone(),
+        old_name: self.node_name(),
+        new_name: other.node_name()
+      });
+    }
+    if self.num_children() != other.num_children() {
+      edits.push(TreeEdit::ChildCountChanged {
+        path: path.clone(),
+        old: self.num_children(),
+        new: other.num_children()
+      });
+      return;
+    }
+    let mut idx = 0;
+    while let Some(child) = self.get_child(idx) {
+      path.push(idx);
+      child.internal_diff(other.get_child(idx).unwrap(), path, edits);
+      path.pop();
+      idx += 1;
+    }
+  }
+
+  /// Returns list of differences between this tree and `other` tree.
+  ///
+  /// Both trees are traversed in pre-order in lockstep. When number of children
+  /// differs, divergence is reported and subtrees are not compared further.
+  /// Returns empty list if both trees have the same shape and node names.
+  fn diff(&self, other: &A) -> Vec<TreeEdit> {
+    let mut edits = Vec::new();
+    self.internal_diff(other, &mut Vec::new(), &mut edits);
+    edits
+  }
+
+  /// Returns indented JSON representation of this tree, where each node is an object
+  /// with `name`, key-value attributes returned by `attrs` for the node, and `children`,
+  /// e.g. `{"name": "a", "dataType": "int", "children": []}`.
+  fn to_json_with<F>(&self, attrs: &mut F) -> String
+    where F: FnMut(&A) -> Vec<(String, String)>
+  {
+    json_value_with(self.get(), attrs).to_pretty_string(2)
+  }
+
   /// Internal method to generate tree string.
   fn recur_gen_tree(
     &self,
@@ -248,7 +572,7 @@ mod tests {
   use super::*;
 
   // == Test node ==
-  #[derive(Clone, Debug, PartialEq)]
+  #[derive(Clone, Debug, Hash, PartialEq)]
   struct TestNode {
     label: String,
     children: Vec<TestNode>
@@ -340,6 +664,90 @@ mod tests {
     assert!(!tree1.clone_tree().equals(&tree2.clone_tree()));
   }
 
+  #[test]
+  fn test_count_matching() {
+    let tree = get_small_test_tree_1();
+    assert_eq!(tree.count_matching(&mut |node| node.is_leaf()), 4);
+    assert_eq!(tree.count_matching(&mut |node| node.num_children() >= 2), 2);
+    assert_eq!(tree.count_matching(&mut |node| node.num_children() >= 3), 1);
+    assert_eq!(tree.count_matching(&mut |node| node.node_name() == "<unknown>"), 0);
+  }
+
+  #[test]
+  fn test_count_by_name() {
+    let tree = TestNode::new("a".to_owned(), vec![
+      TestNode::new("b".to_owned(), vec![]),
+      TestNode::new("a".to_owned(), vec![TestNode::new("b".to_owned(), vec![])]),
+      TestNode::new("c".to_owned(), vec![])
+    ]);
+    let counts = tree.count_by_name();
+    assert_eq!(counts.len(), 3);
+    assert_eq!(counts["a"], 2);
+    assert_eq!(counts["b"], 2);
+    assert_eq!(counts["c"], 1);
+  }
+
+  #[test]
+  fn test_flatten_nodes() {
+    let tree = get_small_test_tree_1();
+    let nodes = tree.flatten_nodes();
+    assert_eq!(nodes.len(), 7);
+    assert_eq!(nodes[0], tree);
+    assert_eq!(nodes[1], tree.get_child(0).unwrap().clone());
+    let labels: Vec<String> = nodes.iter().map(|node| node.node_name()).collect();
+    assert_eq!(labels, vec!["a1", "b1", "c1", "c2", "b2", "c3", "b3"]);
+  }
+
+  #[test]
+  fn test_edge_list() {
+    let tree = get_small_test_tree_2();
+    assert_eq!(tree.edge_list(), vec![(0, 1), (1, 2), (2, 3)]);
+    assert_eq!(tree.node_labels(), vec!["a", "b", "c", "d"]);
+
+    let tree = get_small_test_tree_1();
+    assert_eq!(tree.edge_list(), vec![(0, 1), (1, 2), (1, 3), (0, 4), (4, 5), (0, 6)]);
+    assert_eq!(tree.node_labels(), vec!["a1", "b1", "c1", "c2", "b2", "c3", "b3"]);
+  }
+
+  #[test]
+  fn test_common_ancestor() {
+    let tree = get_small_test_tree_1();
+    let path = tree.common_ancestor(
+      &mut |node| node.node_name() == "c1",
+      &mut |node| node.node_name() == "c2"
+    );
+    assert_eq!(path, Some(vec![0]));
+
+    let path = tree.common_ancestor(
+      &mut |node| node.node_name() == "c1",
+      &mut |node| node.node_name() == "c3"
+    );
+    assert_eq!(path, Some(vec![]));
+
+    let path = tree.common_ancestor(
+      &mut |node| node.node_name() == "b2",
+      &mut |node| node.node_name() == "c3"
+    );
+    assert_eq!(path, Some(vec![1]));
+
+    let path = tree.common_ancestor(
+      &mut |node| node.node_name() == "c1",
+      &mut |node| node.node_name() == "<unknown>"
+    );
+    assert_eq!(path, None);
+  }
+
+  #[test]
+  fn test_is_ancestor_path() {
+    assert!(is_ancestor_path(&[1], &[1, 0]));
+    assert!(is_ancestor_path(&[], &[2]));
+    assert!(is_ancestor_path(&[0, 1], &[0, 1, 3, 2]));
+    assert!(!is_ancestor_path(&[1], &[2, 0]));
+    assert!(!is_ancestor_path(&[1, 0], &[1]));
+    assert!(!is_ancestor_path(&[1, 0], &[1, 0]));
+    assert!(!is_ancestor_path(&[], &[]));
+  }
+
   #[test]
   fn test_foreach() {
     let tree = get_small_test_tree_1();
@@ -456,6 +864,154 @@ mod tests {
     assert_eq!(tree, get_small_test_tree_1());
   }
 
+  #[test]
+  fn test_transform_down_where() {
+    let tree = get_small_test_tree_1();
+    let res = tree.transform_down_where(
+      &mut |node| node.is_leaf(),
+      &mut |node| TestNode::new(format!("{}-#", node.node_name()), vec![])
+    );
+    let expected = TestNode::new(String::from("a1"), vec![
+      TestNode::new(String::from("b1"), vec![
+        TestNode::new(String::from("c1-#"), vec![]),
+        TestNode::new(String::from("c2-#"), vec![])
+      ]),
+      TestNode::new(String::from("b2"), vec![
+        TestNode::new(String::from("c3-#"), vec![])
+      ]),
+      TestNode::new(String::from("b3-#"), vec![])
+    ]);
+    assert_eq!(res, expected);
+    // should not modify original tree
+    assert_eq!(tree, get_small_test_tree_1());
+  }
+
+  #[test]
+  fn test_replace_all() {
+    let tree = get_small_test_tree_1();
+    let (res, count) = tree.replace_all(
+      &mut |node| node.is_leaf(),
+      &mut |node| TestNode::new(format!("{}-#", node.node_name()), vec![])
+    );
+    let expected = TestNode::new(String::from("a1"), vec![
+      TestNode::new(String::from("b1"), vec![
+        TestNode::new(String::from("c1-#"), vec![]),
+        TestNode::new(String::from("c2-#"), vec![])
+      ]),
+      TestNode::new(String::from("b2"), vec![
+        TestNode::new(String::from("c3-#"), vec![])
+      ]),
+      TestNode::new(String::from("b3-#"), vec![])
+    ]);
+    assert_eq!(res, expected);
+    assert_eq!(count, 4);
+    assert_eq!(res.edge_list(), tree.edge_list());
+
+    let (res, count) = tree.replace_all(&mut |_| false, &mut |node| node.clone());
+    assert_eq!(res, tree);
+    assert_eq!(count, 0);
+  }
+
+  #[test]
+  fn test_transform_down_with_state() {
+    let tree = get_small_test_tree_1();
+    let mut counter = 0;
+    let res = tree.transform_down_with_state(&mut counter, &mut |id, node| {
+      let mut cloned = node.clone();
+      cloned.label = format!("{}-{}", node.label, id);
+      *id += 1;
+      Some(cloned)
+    });
+    let mut labels = Vec::new();
+    res.foreach(&mut |node| labels.push(node.node_name()));
+    assert_eq!(labels, vec!["a1-0", "b1-1", "c1-2", "c2-3", "b2-4", "c3-5", "b3-6"]);
+    assert_eq!(counter, 7);
+  }
+
+  #[test]
+  fn test_transform_down_memoized() {
+    let branch = TestNode::new(String::from("b"), vec![
+      TestNode::new(String::from("c"), vec![]),
+      TestNode::new(String::from("d"), vec![])
+    ]);
+    let tree = TestNode::new(String::from("a"), vec![branch.clone(), branch]);
+    let mut rule = |node: &TestNode| {
+      if node.node_name() == "c" {
+        Some(TestNode::new(String::from("C"), vec![]))
+      } else {
+        None
+      }
+    };
+
+    let mut invocations = 0;
+    let res = tree.transform_down_memoized(&mut |node| {
+      invocations += 1;
+      rule(node)
+    });
+    // invoked once for each of the distinct subtrees "a", "b", "c", and "d"
+    assert_eq!(invocations, 4);
+    assert_eq!(res, tree.transform_down(&mut rule));
+    let mut labels = Vec::new();
+    res.foreach(&mut |node| labels.push(node.node_name()));
+    assert_eq!(labels, vec!["a", "b", "C", "d", "b", "C", "d"]);
+  }
+
+  #[test]
+  fn test_transform_down_try() {
+    let tree = get_small_test_tree_1();
+    let mut visited = Vec::new();
+    let res: Result<TestNode, String> = tree.transform_down_try(&mut |node| {
+      visited.push(node.node_name());
+      if visited.len() == 3 {
+        Err(format!("Failed at {}", node.node_name()))
+      } else {
+        Ok(None)
+      }
+    });
+    assert_eq!(res, Err("Failed at c1".to_owned()));
+    assert_eq!(visited, vec!["a1", "b1", "c1"]);
+
+    let res: Result<TestNode, String> = tree.transform_down_try(&mut |node| {
+      if node.is_leaf() {
+        Ok(Some(TestNode::new(format!("{}-#", node.node_name()), vec![])))
+      } else {
+        Ok(None)
+      }
+    });
+    let mut labels = Vec::new();
+    res.unwrap().foreach(&mut |node| labels.push(node.node_name()));
+    assert_eq!(labels, vec!["a1", "b1", "c1-#", "c2-#", "b2", "c3-#", "b3-#"]);
+  }
+
+  #[test]
+  fn test_transform_down_with_parent() {
+    let tree = get_small_test_tree_1();
+    let mut parents = Vec::new();
+    let res = tree.transform_down_with_parent(&mut |parent, node| {
+      parents.push(parent.map(|p| p.node_name()));
+      match parent {
+        Some(p) if p.node_name() == "b2" && node.is_leaf() => {
+          Some(TestNode::new(format!("{}-#", node.node_name()), vec![]))
+        },
+        _ => None
+      }
+    });
+
+    let mut expected = get_small_test_tree_1();
+    expected.children[1].children[0].label = String::from("c3-#");
+    assert_eq!(res, expected);
+
+    assert_eq!(parents, vec![
+      None,
+      Some(String::from("a1")),
+      Some(String::from("b1")),
+      Some(String::from("b1")),
+      Some(String::from("a1")),
+      Some(String::from("b2")),
+      Some(String::from("a1"))
+    ]);
+  }
+
   #[test]
   fn test_transform_up() {
     let tree = get_small_test_tree_1();
@@ -476,6 +1032,106 @@ mod tests {
     assert_eq!(tree, get_small_test_tree_1());
   }
 
+  #[test]
+  fn test_transform_up_stable() {
+    let tree = get_small_test_tree_1();
+    let mut applied = 0;
+    let res = tree.transform_up_stable(&mut |node| {
+      applied += 1;
+      Some(node.clone())
+    });
+    assert_eq!(applied, 7);
+    assert_eq!(res, tree);
+    assert_eq!(tree.diff(&res), vec![]);
+
+    let res = tree.transform_up_stable(&mut |node| {
+      if node.node_name() == "c3" {
+        Some(TestNode::new(String::from("c3-#"), vec![]))
+      } else {
+        Some(node.clone())
+      }
+    });
+    assert_eq!(tree.diff(&res), vec![TreeEdit::Changed {
+      path: vec![1, 0],
+      old_name: String::from("c3"),
+      new_name: String::from("c3-#")
+    }]);
+  }
+
+  #[test]
+  fn test_diff() {
+    let tree = get_small_test_tree_1();
+    assert_eq!(tree.diff(&get_small_test_tree_1()), vec![]);
+
+    let mut other = get_small_test_tree_1();
+    other.children[1].children[0].label = String::from("c3-#");
+    assert_eq!(tree.diff(&other), vec![
+      TreeEdit::Changed {
+        path: vec![1, 0],
+        old_name: String::from("c3"),
+        new_name: String::from("c3-#")
+      }
+    ]);
+
+    let mut other = get_small_test_tree_1();
+    other.label = String::from("a1-#");
+    other.children[0].children.pop();
+    other.children[2].label = String::from("b3-#");
+    assert_eq!(tree.diff(&other), vec![
+      TreeEdit::Changed {
+        path: vec![],
+        old_name: String::from("a1"),
+        new_name: String::from("a1-#")
+      },
+      TreeEdit::ChildCountChanged { path: vec![0], old: 2, new: 1 },
+      TreeEdit::Changed {
+        path: vec![2],
+        old_name: String::from("b3"),
+        new_name: String::from("b3-#")
+      }
+    ]);
+
+    // divergent shapes are not compared below the divergence
+    let tree = get_small_test_tree_2();
+    assert_eq!(tree.diff(&get_small_test_tree_1()), vec![
+      TreeEdit::Changed {
+        path: vec![],
+        old_name: String::from("a"),
+        new_name: String::from("a1")
+      },
+      TreeEdit::ChildCountChanged { path: vec![], old: 1, new: 3 }
+    ]);
+  }
+
+  #[test]
+  fn test_to_json_with() {
+    let tree = get_small_test_tree_2();
+    let res = tree.to_json_with(&mut |node| {
+      vec![("label".to_owned(), node.label.to_uppercase())]
+    });
+    assert_eq!(res.lines().next(), Some("{"));
+    assert_eq!(res.lines().nth(1), Some("  \"name\": \"a\","));
+
+    let mut value = JsonValue::parse(&res).unwrap();
+    for name in ["a", "b", "c", "d"] {
+      assert_eq!(value.get("name"), Some(&JsonValue::String(name.to_owned())));
+      assert_eq!(value.get("label"), Some(&JsonValue::String(name.to_uppercase())));
+      value = match value.get("children") {
+        Some(JsonValue::Array(ref children)) if name == "d" => {
+          assert!(children.is_empty());
+          break;
+        },
+        Some(JsonValue::Array(ref children)) => children[0].clone(),
+        other => panic!("Unexpected children {:?}", other)
+      };
+    }
+
+    let res = tree.to_json_with(&mut |_| vec![]);
+    let value = JsonValue::parse(&res).unwrap();
+    assert_eq!(value.get("label"), None);
+    assert!(value.get("children").is_some());
+  }
+
   #[test]
   fn test_tree_string() {
     let tree = get_small_test_tree_1();
@@ -17,6 +17,9 @@
 
 use std::fmt;
 
+use errors::CatalystError;
+use json::JsonValue;
+
 /// The collection of all data types supported by the optimizer.
 #[derive(Clone, Debug, PartialEq)]
 pub enum DataType {
@@ -28,7 +31,22 @@ pub enum DataType {
   FloatType,
   DoubleType,
   StringType,
-  StructType(Vec<StructField>)
+  // Fixed-length string with length, e.g. `char(10)`.
+  CharType(u16),
+  // Variable-length string with maximum length, e.g. `varchar(255)`.
+  VarcharType(u16),
+  BinaryType,
+  DateType,
+  TimestampType,
+  // Fixed-point decimal with precision and scale.
+  DecimalType(u8, u8),
+  // Array of element type, flag indicates if array can contain null values.
+  ArrayType(Box<DataType>, bool),
+  // Map of key type and value type, flag indicates if values can be null.
+  MapType(Box<DataType>, Box<DataType>, bool),
+  StructType(Vec<StructField>),
+  // User-defined type with the name, backed by the underlying SQL type.
+  UserDefined { name: String, sql_type: Box<DataType> }
 }
 
 impl DataType {
@@ -37,6 +55,41 @@ impl DataType {
     DataType::StructType(fields)
   }
 
+  /// Creates new `ArrayType` with element type and flag if elements can be null.
+  pub fn array_type(element_type: DataType, contains_null: bool) -> DataType {
+    DataType::ArrayType(Box::new(element_type), contains_null)
+  }
+
+  /// Creates new `MapType` with key type, value type, and flag if values can be null.
+  pub fn map_type(
+    key_type: DataType,
+    value_type: DataType,
+    value_contains_null: bool
+  ) -> DataType {
+    DataType::MapType(Box::new(key_type), Box::new(value_type), value_contains_null)
+  }
+
+  /// Maximum precision of `DecimalType`.
+  pub const MAX_DECIMAL_PRECISION: u8 = 38;
+
+  /// Creates new `DecimalType` with precision and scale.
+  /// Panics if precision is not within `[1, 38]` or scale is greater than precision.
+  pub fn decimal_type(precision: u8, scale: u8) -> DataType {
+    if precision == 0 || precision > DataType::MAX_DECIMAL_PRECISION {
+      panic!("Decimal precision {} is out of range [1, {}]",
+        precision, DataType::MAX_DECIMAL_PRECISION);
+    }
+    if scale > precision {
+      panic!("Decimal scale {} is greater than precision {}", scale, precision);
+    }
+    DataType::DecimalType(precision, scale)
+  }
+
+  /// Creates new user-defined type with name and underlying SQL type.
+  pub fn user_defined(name: &str, sql_type: DataType) -> DataType {
+    DataType::UserDefined { name: name.to_owned(), sql_type: Box::new(sql_type) }
+  }
+
   /// Adds a `StructField` to this type.
   /// Panics if current field is not `StructType`.
   pub fn add(mut self, field: StructField) -> DataType {
@@ -59,6 +112,12 @@ impl DataType {
 
   /// Default size in bytes of a value of this data type, used for size estimation.
   pub fn default_size(&self) -> usize {
+    self.default_size_with(20)
+  }
+
+  /// Same as `default_size`, but each string value is estimated as `string_size` bytes,
+  /// including strings nested in structs, arrays, and maps.
+  pub fn default_size_with(&self, string_size: usize) -> usize {
     match self {
       DataType::BooleanType => 1,
       DataType::ByteType => 1,
@@ -67,13 +126,68 @@ impl DataType {
       DataType::LongType => 8,
       DataType::FloatType => 4,
       DataType::DoubleType => 8,
-      DataType::StringType => 20,
+      DataType::StringType => string_size,
+      DataType::CharType(length) | DataType::VarcharType(length) => *length as usize,
+      DataType::BinaryType => 100,
+      DataType::DateType => 4,
+      DataType::TimestampType => 8,
+      DataType::DecimalType(precision, _) => if *precision <= 18 { 8 } else { 16 },
+      // assume that array has one element on average
+      DataType::ArrayType(ref element_type, _) => {
+        element_type.default_size_with(string_size)
+      },
+      // assume that map has one entry on average
+      DataType::MapType(ref key_type, ref value_type, _) => {
+        key_type.default_size_with(string_size) +
+          value_type.default_size_with(string_size)
+      },
       DataType::StructType(ref fields) => {
-        fields.iter().map(|field| field.data_type().default_size()).sum()
+        fields.iter().map(|field| field.data_type().default_size_with(string_size)).sum()
+      },
+      DataType::UserDefined { ref sql_type, .. } => {
+        sql_type.default_size_with(string_size)
       }
     }
   }
 
+  /// Returns natural alignment in bytes of a value of this data type for row layout.
+  ///
+  /// Variable-length types are aligned as 8 byte offsets, struct is aligned by its
+  /// largest field alignment.
+  pub fn alignment(&self) -> usize {
+    match self {
+      DataType::BooleanType | DataType::ByteType => 1,
+      DataType::ShortType => 2,
+      DataType::IntegerType | DataType::FloatType | DataType::DateType => 4,
+      DataType::LongType | DataType::DoubleType | DataType::TimestampType => 8,
+      DataType::DecimalType(precision, _) => if *precision <= 18 { 8 } else { 16 },
+      DataType::StringType | DataType::BinaryType => 8,
+      DataType::CharType(_) | DataType::VarcharType(_) => 8,
+      DataType::ArrayType(_, _) | DataType::MapType(_, _, _) => 8,
+      DataType::StructType(ref fields) => {
+        fields.iter().map(|field| field.data_type().alignment()).max().unwrap_or(1)
+      },
+      DataType::UserDefined { ref sql_type, .. } => sql_type.alignment()
+    }
+  }
+
+  /// Returns size in bytes of a value of this data type if all values have the same
+  /// size, or `None` for variable-length types, e.g. string, binary, or array.
+  ///
+  /// Struct has fixed width only if all of its fields have fixed width.
+  pub fn fixed_width(&self) -> Option<usize> {
+    match self {
+      DataType::StringType | DataType::BinaryType => None,
+      DataType::CharType(_) | DataType::VarcharType(_) => None,
+      DataType::ArrayType(_, _) | DataType::MapType(_, _, _) => None,
+      DataType::StructType(ref fields) => {
+        fields.iter().map(|field| field.data_type().fixed_width()).sum()
+      },
+      DataType::UserDefined { ref sql_type, .. } => sql_type.fixed_width(),
+      other => Some(other.default_size())
+    }
+  }
+
   /// Returns number of fields in this struct type.
   /// Panics if field is not `StructType`.
   pub fn num_fields(&self) -> usize {
@@ -83,6 +197,83 @@ impl DataType {
     }
   }
 
+  /// Returns field with the given name, or `None` if field does not exist.
+  /// Panics if type is not `StructType`.
+  pub fn field(&self, name: &str) -> Option<&StructField> {
+    match self {
+      DataType::StructType(ref fields) => fields.iter().find(|f| f.name() == name),
+      _ => panic!("Not a StructType")
+    }
+  }
+
+  /// Returns position of the field with the given name, or `None` if field does not
+  /// exist. Panics if type is not `StructType`.
+  pub fn field_index(&self, name: &str) -> Option<usize> {
+    match self {
+      DataType::StructType(ref fields) => fields.iter().position(|f| f.name() == name),
+      _ => panic!("Not a StructType")
+    }
+  }
+
+  /// Returns names of all fields in this struct type in order.
+  /// Panics if type is not `StructType`.
+  pub fn field_names(&self) -> Vec<&str> {
+    match self {
+      DataType::StructType(ref fields) => fields.iter().map(|f| f.name()).collect(),
+      _ => panic!("Not a StructType")
+    }
+  }
+
+  /// Returns copy of this struct type where every top-level field is nullable, types of
+  /// the fields are not changed, e.g. for the right side output of left outer join.
+  /// Panics if type is not `StructType`.
+  pub fn make_right_side_nullable(&self) -> DataType {
+    match self {
+      DataType::StructType(ref fields) => DataType::StructType(
+        fields.iter().map(|field| field.clone().with_nullable(true)).collect()
+      ),
+      _ => panic!("Not a StructType")
+    }
+  }
+
+  /// Returns copy of this struct type where only the field at dotted path, e.g.
+  /// `a.b`, is marked as nullable; all other fields, including parent fields on the
+  /// path, are not changed. Returns error if path does not exist.
+  pub fn make_nullable_at_path(&self, path: &str) -> Result<DataType, CatalystError> {
+    let parts: Vec<&str> = path.split('.').collect();
+    self.make_nullable_at_parts(&parts, path)
+  }
+
+  /// Internal method to mark field at path parts as nullable.
+  fn make_nullable_at_parts(
+    &self,
+    parts: &[&str],
+    path: &str
+  ) -> Result<DataType, CatalystError> {
+    let fields = match self {
+      DataType::StructType(ref fields) => fields,
+      _ => {
+        return schema_err!("Cannot resolve path '{}' in non-struct type {}", path, self)
+      }
+    };
+    let pos = match fields.iter().position(|f| f.name() == parts[0]) {
+      Some(pos) => pos,
+      None => {
+        return schema_err!("Field '{}' does not exist for path '{}'", parts[0], path)
+      }
+    };
+    let mut updated = fields.clone();
+    updated[pos] = if parts.len() == 1 {
+      updated[pos].clone().with_nullable(true)
+    } else {
+      let data_type = updated[pos].data_type().make_nullable_at_parts(&parts[1..], path)?;
+      let mut field = updated[pos].clone();
+      field.data_type = data_type;
+      field
+    };
+    Ok(DataType::StructType(updated))
+  }
+
   /// Returns `true` if type is `StructType`, `false` otherwise.
   pub fn is_struct(&self) -> bool {
     match self {
@@ -102,11 +293,283 @@ impl DataType {
       DataType::LongType |
       DataType::FloatType |
       DataType::DoubleType |
-      DataType::StringType => true,
+      DataType::StringType |
+      DataType::CharType(_) |
+      DataType::VarcharType(_) |
+      DataType::BinaryType |
+      DataType::DateType |
+      DataType::TimestampType |
+      DataType::DecimalType(_, _) => true,
+      DataType::UserDefined { ref sql_type, .. } => sql_type.is_atomic(),
+      _ => false
+    }
+  }
+
+  /// Returns `true` if this type is numeric, i.e. it can be widened with
+  /// `widen_numeric`.
+  ///
+  /// `DecimalType` is not numeric until literals support decimal values.
+  pub fn is_numeric(&self) -> bool {
+    self.numeric_precedence().is_some()
+  }
+
+  /// Returns common type that both this type and `other` type can be coerced to, or
+  /// `None` if types are not compatible.
+  ///
+  /// Numeric types are widened, e.g. `int` and `long` are coerced to `long`, `char` and
+  /// `varchar` of different lengths are coerced to `string`, other types can only be
+  /// coerced if they are equal.
+  pub fn coerce_to_common(&self, other: &DataType) -> Option<DataType> {
+    if self == other {
+      return Some(self.clone());
+    }
+    if self.is_string_like() && other.is_string_like() {
+      return Some(DataType::StringType);
+    }
+    widen_numeric(self, other)
+  }
+
+  /// Returns type that values of this type and `other` type are converted to in set
+  /// operations, e.g. union, or `None` if types are incompatible.
+  ///
+  /// Atomic types are coerced with `coerce_to_common`, structs are merged field-wise by
+  /// position with names of this struct, arrays and maps are merged element-wise;
+  /// nullability flags of both sides are combined with OR.
+  pub fn union_type(&self, other: &DataType) -> Option<DataType> {
+    if self == other {
+      return Some(self.clone());
+    }
+    match (self, other) {
+      (DataType::StructType(ref left), DataType::StructType(ref right)) => {
+        if left.len() != right.len() {
+          return None;
+        }
+        let mut fields = Vec::with_capacity(left.len());
+        for (l, r) in left.iter().zip(right.iter()) {
+          let mut field = l.clone();
+          field.data_type = l.data_type().union_type(r.data_type())?;
+          field.nullable = l.is_nullable() || r.is_nullable();
+          fields.push(field);
+        }
+        Some(DataType::StructType(fields))
+      },
+      (DataType::ArrayType(ref left, l_null), DataType::ArrayType(ref right, r_null)) => {
+        Some(DataType::array_type(left.union_type(right)?, *l_null || *r_null))
+      },
+      (
+        DataType::MapType(ref l_key, ref l_value, l_null),
+        DataType::MapType(ref r_key, ref r_value, r_null)
+      ) => {
+        Some(DataType::map_type(
+          l_key.union_type(r_key)?,
+          l_value.union_type(r_value)?,
+          *l_null || *r_null
+        ))
+      },
+      (left, right) => left.coerce_to_common(right)
+    }
+  }
+
+  /// Internal method to check if type is `string`, `char`, or `varchar`.
+  fn is_string_like(&self) -> bool {
+    matches!(
+      self,
+      DataType::StringType | DataType::CharType(_) | DataType::VarcharType(_)
+    )
+  }
+
+  /// Returns true if this type and `other` type are equal ignoring nullability of struct
+  /// fields, array elements, and map values, e.g. to compare output of a plan with the
+  /// expected schema.
+  ///
+  /// Struct fields are compared by name and type in order, field metadata is ignored.
+  pub fn equals_ignore_nullability(&self, other: &DataType) -> bool {
+    match (self, other) {
+      (DataType::StructType(ref left), DataType::StructType(ref right)) => {
+        left.len() == right.len() &&
+          left.iter().zip(right.iter()).all(|(l, r)| {
+            l.name() == r.name() && l.data_type().equals_ignore_nullability(r.data_type())
+          })
+      },
+      (DataType::ArrayType(ref left, _), DataType::ArrayType(ref right, _)) => {
+        left.equals_ignore_nullability(right)
+      },
+      (DataType::MapType(ref lk, ref lv, _), DataType::MapType(ref rk, ref rv, _)) => {
+        lk.equals_ignore_nullability(rk) && lv.equals_ignore_nullability(rv)
+      },
+      (
+        DataType::UserDefined { name: ref l_name, sql_type: ref l_type },
+        DataType::UserDefined { name: ref r_name, sql_type: ref r_type }
+      ) => {
+        l_name == r_name && l_type.equals_ignore_nullability(r_type)
+      },
+      (left, right) => left == right
+    }
+  }
+
+  /// Returns true if this type and `other` type can be used as join keys, i.e. they are
+  /// equal or can be coerced to a common type.
+  pub fn join_compatible(&self, other: &DataType) -> bool {
+    self.coerce_to_common(other).is_some()
+  }
+
+  /// Internal method to return precedence of numeric type for widening, `None` if type
+  /// is not numeric.
+  fn numeric_precedence(&self) -> Option<u8> {
+    match self {
+      DataType::ByteType => Some(1),
+      DataType::ShortType => Some(2),
+      DataType::IntegerType => Some(3),
+      DataType::LongType => Some(4),
+      DataType::FloatType => Some(5),
+      DataType::DoubleType => Some(6),
+      _ => None
+    }
+  }
+
+  /// Returns canonical Arrow type name for atomic types, or `None` for complex and
+  /// unsupported types.
+  pub fn arrow_type_name(&self) -> Option<&'static str> {
+    match self {
+      DataType::BooleanType => Some("bool"),
+      DataType::ByteType => Some("int8"),
+      DataType::ShortType => Some("int16"),
+      DataType::IntegerType => Some("int32"),
+      DataType::LongType => Some("int64"),
+      DataType::FloatType => Some("float32"),
+      DataType::DoubleType => Some("float64"),
+      DataType::StringType => Some("utf8"),
+      DataType::CharType(_) | DataType::VarcharType(_) => Some("utf8"),
+      DataType::BinaryType => Some("binary"),
+      DataType::DateType => Some("date32"),
+      DataType::TimestampType => Some("timestamp"),
+      DataType::DecimalType(_, _) => Some("decimal128"),
+      DataType::UserDefined { ref sql_type, .. } => sql_type.arrow_type_name(),
+      DataType::ArrayType(_, _) | DataType::MapType(_, _, _) => None,
+      DataType::StructType(_) => None
+    }
+  }
+
+  /// Returns `true` if this type or any of its nested types matches predicate.
+  pub fn contains<F>(&self, predicate: &mut F) -> bool where F: FnMut(&DataType) -> bool {
+    if predicate(self) {
+      return true;
+    }
+    match self {
+      DataType::StructType(ref fields) => {
+        fields.iter().any(|field| field.data_type().contains(predicate))
+      },
+      DataType::ArrayType(ref element_type, _) => element_type.contains(predicate),
+      DataType::MapType(ref key_type, ref value_type, _) => {
+        key_type.contains(predicate) || value_type.contains(predicate)
+      },
+      DataType::UserDefined { ref sql_type, .. } => sql_type.contains(predicate),
       _ => false
     }
   }
 
+  /// Merges this type with `other` type, e.g. to find common schema of two files.
+  ///
+  /// Struct fields are merged by name: fields with the same name must have types that
+  /// can be merged and merged field is nullable if either of the fields is nullable;
+  /// fields that only exist on one side are marked as nullable and keep their order,
+  /// with new fields of `other` placed after fields of this type. Array element types
+  /// and map key and value types are merged, merged container can contain nulls if
+  /// either of containers can. Other types can only be merged if they are equal.
+  pub fn merge(&self, other: &DataType) -> Result<DataType, CatalystError> {
+    match (self, other) {
+      (DataType::StructType(ref left), DataType::StructType(ref right)) => {
+        let mut fields = Vec::with_capacity(left.len());
+        for field in left {
+          match right.iter().find(|f| f.name() == field.name()) {
+            Some(other_field) => fields.push(field.merge(other_field)?),
+            None => fields.push(field.clone().with_nullable(true))
+          }
+        }
+        for field in right {
+          if !left.iter().any(|f| f.name() == field.name()) {
+            fields.push(field.clone().with_nullable(true));
+          }
+        }
+        Ok(DataType::StructType(fields))
+      },
+      (DataType::ArrayType(ref left, l_null), DataType::ArrayType(ref right, r_null)) => {
+        Ok(DataType::array_type(left.merge(right)?, *l_null || *r_null))
+      },
+      (DataType::MapType(ref lk, ref lv, l_null),
+          DataType::MapType(ref rk, ref rv, r_null)) => {
+        Ok(DataType::map_type(lk.merge(rk)?, lv.merge(rv)?, *l_null || *r_null))
+      },
+      _ if self == other => Ok(self.clone()),
+      _ => schema_err!("Failed to merge incompatible data types {} and {}", self, other)
+    }
+  }
+
+  /// Returns string representation of this type, same as `Display`, where struct, array,
+  /// and map types nested deeper than `max_depth` levels are replaced with `...`.
+  /// User-defined types are rendered as their underlying SQL type.
+  ///
+  /// For example, `struct<a:int,b:struct<c:int>>` with `max_depth = 1` is rendered as
+  /// `struct<a:int,b:...>`.
+  pub fn truncated_string(&self, max_depth: usize) -> String {
+    match self {
+      DataType::StructType(_) if max_depth == 0 => "...".to_owned(),
+      DataType::StructType(ref fields) => {
+        let fields: Vec<String> = fields.iter().map(|field| {
+          let data_type = field.data_type().truncated_string(max_depth - 1);
+          format!("{}:{}", field.name(), data_type)
+        }).collect();
+        format!("struct<{}>", fields.join(","))
+      },
+      DataType::ArrayType(_, _) if max_depth == 0 => "...".to_owned(),
+      DataType::ArrayType(ref element_type, _) => {
+        format!("array<{}>", element_type.truncated_string(max_depth - 1))
+      },
+      DataType::MapType(_, _, _) if max_depth == 0 => "...".to_owned(),
+      DataType::MapType(ref key_type, ref value_type, _) => {
+        format!("map<{},{}>", key_type.truncated_string(max_depth - 1),
+          value_type.truncated_string(max_depth - 1))
+      },
+      DataType::UserDefined { ref sql_type, .. } => sql_type.truncated_string(max_depth),
+      _ => self.to_string()
+    }
+  }
+
+  /// Maximum number of struct fields rendered by `simple_string`.
+  pub const MAX_SIMPLE_STRING_FIELDS: usize = 25;
+
+  /// Returns fully expanded string representation of this type, same as `Display`,
+  /// e.g. `struct<a:int,b:array<string>>`.
+  pub fn catalog_string(&self) -> String {
+    self.to_string()
+  }
+
+  /// Returns short string representation of this type, where only the first
+  /// `MAX_SIMPLE_STRING_FIELDS` fields of each struct are rendered, remaining fields are
+  /// replaced with `... N more fields`.
+  pub fn simple_string(&self) -> String {
+    match self {
+      DataType::StructType(ref fields) => {
+        let mut parts: Vec<String> = fields.iter()
+          .take(DataType::MAX_SIMPLE_STRING_FIELDS)
+          .map(|field| format!("{}:{}", field.name(), field.data_type().simple_string()))
+          .collect();
+        if fields.len() > DataType::MAX_SIMPLE_STRING_FIELDS {
+          parts.push(format!("... {} more fields",
+            fields.len() - DataType::MAX_SIMPLE_STRING_FIELDS));
+        }
+        format!("struct<{}>", parts.join(","))
+      },
+      DataType::ArrayType(ref element_type, _) => {
+        format!("array<{}>", element_type.simple_string())
+      },
+      DataType::MapType(ref key_type, ref value_type, _) => {
+        format!("map<{},{}>", key_type.simple_string(), value_type.simple_string())
+      },
+      _ => self.to_string()
+    }
+  }
+
   /// Returns string representation of schema tree.
   pub fn tree_string(&self) -> String {
     match self {
@@ -120,6 +583,207 @@ impl DataType {
     }
   }
 
+  /// Returns compact JSON representation of this type in Spark schema format, which can
+  /// be parsed back with `from_json_schema`.
+  ///
+  /// User-defined type is stored as `{"type":"udt","class":name,"sqlType":...}`.
+  pub fn to_json_schema(&self) -> String {
+    self.json_value().to_compact_string()
+  }
+
+  /// Returns JSON representation of this type in Spark schema format, where each
+  /// nested level is indented with `indent` spaces.
+  pub fn to_json_schema_pretty(&self, indent: usize) -> String {
+    self.json_value().to_pretty_string(indent)
+  }
+
+  /// Parses data type from JSON in Spark schema format.
+  ///
+  /// Returns error if JSON is invalid, contains unknown type or is missing fields.
+  pub fn from_json_schema(json: &str) -> Result<DataType, CatalystError> {
+    match JsonValue::parse(json) {
+      Ok(value) => DataType::from_json_value(&value),
+      Err(msg) => schema_err!("Invalid JSON schema: {}", msg)
+    }
+  }
+
+  /// Returns JSON representation of this type in Spark schema format, same as
+  /// `to_json_schema`, can be parsed back with `from_json`.
+  pub fn to_json(&self) -> String {
+    self.to_json_schema()
+  }
+
+  /// Parses data type from JSON in Spark schema format, same as `from_json_schema`.
+  pub fn from_json(json: &str) -> Result<DataType, CatalystError> {
+    DataType::from_json_schema(json)
+  }
+
+  /// Internal method to convert JSON value into data type.
+  fn from_json_value(value: &JsonValue) -> Result<DataType, CatalystError> {
+    match value {
+      JsonValue::String(ref name) => match name.as_str() {
+        "boolean" => Ok(DataType::BooleanType),
+        "byte" => Ok(DataType::ByteType),
+        "short" => Ok(DataType::ShortType),
+        "integer" => Ok(DataType::IntegerType),
+        "long" => Ok(DataType::LongType),
+        "float" => Ok(DataType::FloatType),
+        "double" => Ok(DataType::DoubleType),
+        "string" => Ok(DataType::StringType),
+        "binary" => Ok(DataType::BinaryType),
+        "date" => Ok(DataType::DateType),
+        "timestamp" => Ok(DataType::TimestampType),
+        _ => match DataType::parse_decimal(name).or_else(|| DataType::parse_char(name)) {
+          Some(data_type) => Ok(data_type),
+          None => schema_err!("Unknown type name {}", name)
+        }
+      },
+      JsonValue::Object(_) => match value.get("type") {
+        Some(JsonValue::String(ref name)) if name == "struct" => {
+          match value.get("fields") {
+            Some(JsonValue::Array(ref values)) => {
+              let mut fields = Vec::with_capacity(values.len());
+              for value in values {
+                fields.push(StructField::from_json_value(value)?);
+              }
+              Ok(DataType::StructType(fields))
+            },
+            _ => schema_err!("Missing struct fields in {}", value.to_compact_string())
+          }
+        },
+        Some(JsonValue::String(ref name)) if name == "array" => {
+          let element_type = match value.get("elementType") {
+            Some(element_type) => DataType::from_json_value(element_type)?,
+            None => return schema_err!("Missing array element type in {}",
+              value.to_compact_string())
+          };
+          match value.get("containsNull") {
+            Some(JsonValue::Bool(contains_null)) => {
+              Ok(DataType::array_type(element_type, *contains_null))
+            },
+            _ => schema_err!("Missing containsNull in {}", value.to_compact_string())
+          }
+        },
+        Some(JsonValue::String(ref name)) if name == "map" => {
+          let key_type = match value.get("keyType") {
+            Some(key_type) => DataType::from_json_value(key_type)?,
+            None => return schema_err!("Missing keyType in {}", value.to_compact_string())
+          };
+          let value_type = match value.get("valueType") {
+            Some(value_type) => DataType::from_json_value(value_type)?,
+            None => {
+              return schema_err!("Missing valueType in {}", value.to_compact_string())
+            }
+          };
+          match value.get("valueContainsNull") {
+            Some(JsonValue::Bool(contains_null)) => {
+              Ok(DataType::map_type(key_type, value_type, *contains_null))
+            },
+            _ => schema_err!("Missing valueContainsNull in {}", value.to_compact_string())
+          }
+        },
+        Some(JsonValue::String(ref name)) if name == "udt" => {
+          let class = match value.get("class") {
+            Some(JsonValue::String(ref class)) => class,
+            _ => return schema_err!("Missing class in {}", value.to_compact_string())
+          };
+          match value.get("sqlType") {
+            Some(sql_type) => {
+              Ok(DataType::user_defined(class, DataType::from_json_value(sql_type)?))
+            },
+            None => schema_err!("Missing sqlType in {}", value.to_compact_string())
+          }
+        },
+        Some(JsonValue::String(ref name)) => schema_err!("Unknown type name {}", name),
+        _ => schema_err!("Missing type in {}", value.to_compact_string())
+      },
+      _ => schema_err!("Invalid type {}", value.to_compact_string())
+    }
+  }
+
+  /// Internal method to parse decimal type name, e.g. `decimal(10,2)`, returns `None`
+  /// if name is not a valid decimal type.
+  fn parse_decimal(name: &str) -> Option<DataType> {
+    if !name.starts_with("decimal(") || !name.ends_with(')') {
+      return None;
+    }
+    let params: Vec<&str> = name["decimal(".len()..name.len() - 1].split(',').collect();
+    if params.len() != 2 {
+      return None;
+    }
+    match (params[0].trim().parse::<u8>(), params[1].trim().parse::<u8>()) {
+      (Ok(precision), Ok(scale)) => {
+        let max_precision = DataType::MAX_DECIMAL_PRECISION;
+        if precision == 0 || precision > max_precision || scale > precision {
+          None
+        } else {
+          Some(DataType::DecimalType(precision, scale))
+        }
+      },
+      _ => None
+    }
+  }
+
+  /// Internal method to parse `char(n)` or `varchar(n)` type name, returns `None` if
+  /// name is not a valid char or varchar type.
+  fn parse_char(name: &str) -> Option<DataType> {
+    if !name.ends_with(')') {
+      return None;
+    }
+    let (prefix, is_char) = if name.starts_with("char(") {
+      ("char(", true)
+    } else if name.starts_with("varchar(") {
+      ("varchar(", false)
+    } else {
+      return None;
+    };
+    match name[prefix.len()..name.len() - 1].trim().parse::<u16>() {
+      Ok(length) if length > 0 && is_char => Some(DataType::CharType(length)),
+      Ok(length) if length > 0 => Some(DataType::VarcharType(length)),
+      _ => None
+    }
+  }
+
+  /// Internal method to convert type into JSON value.
+  fn json_value(&self) -> JsonValue {
+    match self {
+      DataType::StructType(ref fields) => JsonValue::Object(vec![
+        ("type".to_owned(), JsonValue::String("struct".to_owned())),
+        ("fields".to_owned(), JsonValue::Array(
+          fields.iter().map(|field| field.json_value()).collect()
+        ))
+      ]),
+      DataType::ArrayType(ref element_type, contains_null) => JsonValue::Object(vec![
+        ("type".to_owned(), JsonValue::String("array".to_owned())),
+        ("elementType".to_owned(), element_type.json_value()),
+        ("containsNull".to_owned(), JsonValue::Bool(*contains_null))
+      ]),
+      DataType::MapType(ref key_type, ref value_type, contains_null) => {
+        JsonValue::Object(vec![
+          ("type".to_owned(), JsonValue::String("map".to_owned())),
+          ("keyType".to_owned(), key_type.json_value()),
+          ("valueType".to_owned(), value_type.json_value()),
+          ("valueContainsNull".to_owned(), JsonValue::Bool(*contains_null))
+        ])
+      },
+      DataType::UserDefined { ref name, ref sql_type } => JsonValue::Object(vec![
+        ("type".to_owned(), JsonValue::String("udt".to_owned())),
+        ("class".to_owned(), JsonValue::String(name.clone())),
+        ("sqlType".to_owned(), sql_type.json_value())
+      ]),
+      _ => JsonValue::String(self.json_type_name())
+    }
+  }
+
+  /// Internal method to extract type name used in JSON schema.
+  fn json_type_name(&self) -> String {
+    match self {
+      DataType::BooleanType => "boolean".to_owned(),
+      DataType::IntegerType => "integer".to_owned(),
+      _ => self.type_name()
+    }
+  }
+
   /// Internal method to print tree.
   fn print_tree(&self, prefix: &str, buf: &mut Vec<String>) {
     match self {
@@ -128,6 +792,19 @@ impl DataType {
           field.print_tree(prefix, buf);
         }
       },
+      DataType::ArrayType(ref element_type, contains_null) => {
+        buf.push(format!("{}- element: {} (containsNull = {})",
+          prefix, element_type.type_name(), contains_null));
+        element_type.print_tree(&format!("   {}", prefix), buf);
+      },
+      DataType::MapType(ref key_type, ref value_type, contains_null) => {
+        buf.push(format!("{}- key: {}", prefix, key_type.type_name()));
+        key_type.print_tree(&format!("   {}", prefix), buf);
+        buf.push(format!("{}- value: {} (valueContainsNull = {})",
+          prefix, value_type.type_name(), contains_null));
+        value_type.print_tree(&format!("   {}", prefix), buf);
+      },
+      DataType::UserDefined { ref sql_type, .. } => sql_type.print_tree(prefix, buf),
       _ => {
         // no-op operation
       }
@@ -135,17 +812,28 @@ impl DataType {
   }
 
   /// Internal method to extract short type name.
-  fn type_name(&self) -> &str {
+  fn type_name(&self) -> String {
     match self {
-      DataType::BooleanType => "bool",
-      DataType::ByteType => "byte",
-      DataType::ShortType => "short",
-      DataType::IntegerType => "int",
-      DataType::LongType => "long",
-      DataType::FloatType => "float",
-      DataType::DoubleType => "double",
-      DataType::StringType => "string",
-      DataType::StructType(_) => "struct"
+      DataType::BooleanType => "bool".to_owned(),
+      DataType::ByteType => "byte".to_owned(),
+      DataType::ShortType => "short".to_owned(),
+      DataType::IntegerType => "int".to_owned(),
+      DataType::LongType => "long".to_owned(),
+      DataType::FloatType => "float".to_owned(),
+      DataType::DoubleType => "double".to_owned(),
+      DataType::StringType => "string".to_owned(),
+      DataType::CharType(length) => format!("char({})", length),
+      DataType::VarcharType(length) => format!("varchar({})", length),
+      DataType::BinaryType => "binary".to_owned(),
+      DataType::DateType => "date".to_owned(),
+      DataType::TimestampType => "timestamp".to_owned(),
+      DataType::DecimalType(precision, scale) => {
+        format!("decimal({},{})", precision, scale)
+      },
+      DataType::ArrayType(_, _) => "array".to_owned(),
+      DataType::MapType(_, _, _) => "map".to_owned(),
+      DataType::StructType(_) => "struct".to_owned(),
+      DataType::UserDefined { ref name, .. } => name.clone()
     }
   }
 }
@@ -164,22 +852,38 @@ impl fmt::Display for DataType {
         }
         write!(f, ">")
       },
+      DataType::ArrayType(ref element_type, _) => write!(f, "array<{}>", element_type),
+      DataType::MapType(ref key_type, ref value_type, _) => {
+        write!(f, "map<{},{}>", key_type, value_type)
+      },
       _ => write!(f, "{}", self.type_name())
     }
   }
 }
 
+/// Returns the wider of two numeric types using numeric precedence
+/// `byte < short < int < long < float < double`, or `None` if either type is not
+/// numeric.
+pub fn widen_numeric(a: &DataType, b: &DataType) -> Option<DataType> {
+  match (a.numeric_precedence(), b.numeric_precedence()) {
+    (Some(left), Some(right)) => Some(if left >= right { a.clone() } else { b.clone() }),
+    _ => None
+  }
+}
+
 /// A field inside a StructType.
 ///
 /// Contains:
 /// - `name`, the name of this field.
 /// - `data_type`, the data type of this field.
 /// - `nullable`, indicates if values of this type field can be `null` values.
+/// - `metadata`, optional raw JSON object with metadata of this field.
 #[derive(Clone, Debug, PartialEq)]
 pub struct StructField {
   name: String,
   data_type: DataType,
   nullable: bool,
+  metadata: Option<String>
 }
 
 impl StructField {
@@ -189,7 +893,8 @@ impl StructField {
     Self {
       name: name,
       data_type: data_type,
-      nullable: true
+      nullable: true,
+      metadata: None
     }
   }
 
@@ -214,6 +919,77 @@ impl StructField {
     self
   }
 
+  /// Internal method to convert field into JSON value.
+  fn json_value(&self) -> JsonValue {
+    JsonValue::Object(vec![
+      ("name".to_owned(), JsonValue::String(self.name.clone())),
+      ("type".to_owned(), self.data_type.json_value()),
+      ("nullable".to_owned(), JsonValue::Bool(self.nullable)),
+      ("metadata".to_owned(), match self.metadata {
+        Some(ref metadata) => JsonValue::parse(metadata).unwrap(),
+        None => JsonValue::Object(vec![])
+      })
+    ])
+  }
+
+  /// Returns metadata of this field as JSON object string, if set.
+  pub fn metadata(&self) -> Option<&str> {
+    self.metadata.as_deref()
+  }
+
+  /// Sets metadata as JSON object string, e.g. `{"comment":"field"}`.
+  /// Empty object is treated as no metadata.
+  ///
+  /// Returns error if metadata is not a valid JSON object.
+  pub fn with_metadata(mut self, metadata: &str) -> Result<Self, CatalystError> {
+    self.metadata = match JsonValue::parse(metadata) {
+      Ok(JsonValue::Object(ref entries)) if entries.is_empty() => None,
+      Ok(value @ JsonValue::Object(_)) => Some(value.to_compact_string()),
+      _ => return schema_err!("Invalid metadata {}, expected JSON object", metadata)
+    };
+    Ok(self)
+  }
+
+  /// Internal method to merge this field with field of the same name.
+  fn merge(&self, other: &StructField) -> Result<StructField, CatalystError> {
+    match self.data_type.merge(&other.data_type) {
+      Ok(data_type) => {
+        let mut field = self.clone();
+        field.data_type = data_type;
+        field.nullable = self.nullable || other.nullable;
+        Ok(field)
+      },
+      Err(CatalystError::Schema(msg)) => {
+        schema_err!("Failed to merge fields '{}': {}", self.name, msg)
+      },
+      Err(err) => Err(err)
+    }
+  }
+
+  /// Internal method to convert JSON value into struct field.
+  fn from_json_value(value: &JsonValue) -> Result<StructField, CatalystError> {
+    let name = match value.get("name") {
+      Some(JsonValue::String(ref name)) => name.clone(),
+      _ => return schema_err!("Missing field name in {}", value.to_compact_string())
+    };
+    let data_type = match value.get("type") {
+      Some(tpe) => DataType::from_json_value(tpe)?,
+      None => return schema_err!("Missing field type in {}", value.to_compact_string())
+    };
+    let nullable = match value.get("nullable") {
+      Some(JsonValue::Bool(nullable)) => *nullable,
+      _ => return schema_err!("Missing field nullable in {}", value.to_compact_string())
+    };
+    let field = StructField::new(name, data_type).with_nullable(nullable);
+    match value.get("metadata") {
+      Some(metadata @ JsonValue::Object(_)) => {
+        field.with_metadata(&metadata.to_compact_string())
+      },
+      Some(_) => schema_err!("Invalid field metadata in {}", value.to_compact_string()),
+      None => Ok(field)
+    }
+  }
+
   /// Prints tree string.
   fn print_tree(&self, prefix: &str, buf: &mut Vec<String>) {
     buf.push(format!("{}- {}: {} (nullable = {})",
@@ -242,6 +1018,9 @@ mod tests {
     assert_eq!(DataType::FloatType.is_atomic(), true);
     assert_eq!(DataType::DoubleType.is_atomic(), true);
     assert_eq!(DataType::StringType.is_atomic(), true);
+    assert_eq!(DataType::BinaryType.is_atomic(), true);
+    assert_eq!(DataType::DateType.is_atomic(), true);
+    assert_eq!(DataType::TimestampType.is_atomic(), true);
     assert_eq!(DataType::struct_type(vec![]).is_atomic(), false);
   }
 
@@ -255,6 +1034,9 @@ mod tests {
     assert_eq!(DataType::FloatType.is_struct(), false);
     assert_eq!(DataType::DoubleType.is_struct(), false);
     assert_eq!(DataType::StringType.is_struct(), false);
+    assert_eq!(DataType::BinaryType.is_struct(), false);
+    assert_eq!(DataType::DateType.is_struct(), false);
+    assert_eq!(DataType::TimestampType.is_struct(), false);
     assert_eq!(DataType::struct_type(vec![]).is_struct(), true);
   }
 
@@ -268,7 +1050,11 @@ mod tests {
     assert_eq!(DataType::FloatType.type_name(), "float");
     assert_eq!(DataType::DoubleType.type_name(), "double");
     assert_eq!(DataType::StringType.type_name(), "string");
+    assert_eq!(DataType::BinaryType.type_name(), "binary");
+    assert_eq!(DataType::DateType.type_name(), "date");
+    assert_eq!(DataType::TimestampType.type_name(), "timestamp");
     assert_eq!(DataType::struct_type(vec![]).type_name(), "struct");
+    assert_eq!(DataType::decimal_type(10, 2).type_name(), "decimal(10,2)");
   }
 
   #[test]
@@ -299,6 +1085,643 @@ mod tests {
     assert_eq!(schema.num_fields(), 2);
   }
 
+  #[test]
+  fn test_datatype_decimal() {
+    let decimal = DataType::decimal_type(10, 2);
+    assert_eq!(decimal, DataType::DecimalType(10, 2));
+    assert!(decimal.is_atomic());
+    assert!(!decimal.is_struct());
+    assert!(!decimal.is_numeric());
+    assert_eq!(decimal.to_string(), "decimal(10,2)");
+
+    assert_eq!(DataType::decimal_type(1, 0).default_size(), 8);
+    assert_eq!(DataType::decimal_type(18, 18).default_size(), 8);
+    assert_eq!(DataType::decimal_type(19, 0).default_size(), 16);
+    assert_eq!(DataType::decimal_type(38, 10).default_size(), 16);
+
+    let schema = DataType::struct_type(vec![])
+      .add_field_n("a", DataType::decimal_type(38, 0), false)
+      .add_field("b", DataType::struct_type(vec![])
+        .add_field("c", DataType::decimal_type(5, 5)));
+    assert_eq!(schema.to_string(), "struct<a:decimal(38,0),b:struct<c:decimal(5,5)>>");
+    assert_eq!(schema.tree_string(), [
+      "root",
+      " |- a: decimal(38,0) (nullable = false)",
+      " |- b: struct (nullable = true)",
+      "    |- c: decimal(5,5) (nullable = true)"
+    ].join("\n"));
+    assert_eq!(DataType::from_json_schema(&schema.to_json_schema()).unwrap(), schema);
+    assert!(DataType::from_json_schema("\"decimal(39,0)\"").is_err());
+    assert!(DataType::from_json_schema("\"decimal(2,3)\"").is_err());
+    assert!(DataType::from_json_schema("\"decimal(2)\"").is_err());
+  }
+
+  #[test]
+  fn test_datatype_char_varchar() {
+    let char_type = DataType::CharType(10);
+    let varchar_type = DataType::VarcharType(255);
+    assert_eq!(char_type.to_string(), "char(10)");
+    assert_eq!(varchar_type.to_string(), "varchar(255)");
+    assert!(char_type.is_atomic());
+    assert!(!char_type.is_numeric());
+    assert_eq!(char_type.default_size(), 10);
+    assert_eq!(varchar_type.default_size(), 255);
+    assert_eq!(varchar_type.default_size_with(100), 255);
+    assert_eq!(char_type.fixed_width(), None);
+    assert_eq!(varchar_type.arrow_type_name(), Some("utf8"));
+
+    let schema = DataType::struct_type(vec![])
+      .add_field("a", char_type.clone())
+      .add_field("b", DataType::array_type(varchar_type.clone(), true));
+    assert_eq!(schema.to_string(), "struct<a:char(10),b:array<varchar(255)>>");
+    assert_eq!(schema.default_size(), 265);
+    assert_eq!(DataType::from_json_schema(&schema.to_json_schema()).unwrap(), schema);
+    assert!(DataType::from_json_schema("\"char(0)\"").is_err());
+    assert!(DataType::from_json_schema("\"varchar(70000)\"").is_err());
+    assert!(DataType::from_json_schema("\"varchar\"").is_err());
+  }
+
+  #[test]
+  #[should_panic(expected = "Decimal precision 39 is out of range [1, 38]")]
+  fn test_datatype_decimal_invalid_precision() {
+    DataType::decimal_type(39, 0);
+  }
+
+  #[test]
+  #[should_panic(expected = "Decimal precision 0 is out of range [1, 38]")]
+  fn test_datatype_decimal_zero_precision() {
+    DataType::decimal_type(0, 0);
+  }
+
+  #[test]
+  #[should_panic(expected = "Decimal scale 3 is greater than precision 2")]
+  fn test_datatype_decimal_invalid_scale() {
+    DataType::decimal_type(2, 3);
+  }
+
+  #[test]
+  #[should_panic(expected = "Not a StructType")]
+  fn test_datatype_decimal_num_fields() {
+    DataType::decimal_type(10, 2).num_fields();
+  }
+
+  #[test]
+  #[should_panic(expected = "Not a StructType")]
+  fn test_datatype_decimal_add() {
+    DataType::decimal_type(10, 2).add_field("a", DataType::IntegerType);
+  }
+
+  #[test]
+  fn test_datatype_array() {
+    let array = DataType::array_type(DataType::IntegerType, true);
+    assert_eq!(array, DataType::ArrayType(Box::new(DataType::IntegerType), true));
+    assert_eq!(array.type_name(), "array");
+    assert!(!array.is_atomic());
+    assert!(!array.is_struct());
+    assert_eq!(array.default_size(), 4);
+    assert_eq!(array.to_string(), "array<int>");
+    assert_eq!(array.arrow_type_name(), None);
+    assert!(array.contains(&mut |tpe| tpe == &DataType::IntegerType));
+
+    let array = DataType::array_type(DataType::struct_type(vec![])
+      .add_field("a", DataType::LongType)
+      .add_field_n("b", DataType::array_type(DataType::StringType, false), false), false);
+    assert_eq!(array.default_size(), 28);
+    assert_eq!(array.to_string(), "array<struct<a:long,b:array<string>>>");
+    assert_eq!(array.truncated_string(1), "array<...>");
+    assert_eq!(array.truncated_string(2), "array<struct<a:long,b:...>>");
+  }
+
+  #[test]
+  fn test_datatype_array_tree_string() {
+    let schema = DataType::struct_type(vec![])
+      .add_field("a", DataType::array_type(DataType::IntegerType, true))
+      .add_field_n("b", DataType::array_type(DataType::struct_type(vec![])
+        .add_field("c", DataType::StringType)
+        .add_field("d", DataType::array_type(DataType::LongType, false)), false), false);
+    assert_eq!(schema.to_string(),
+      "struct<a:array<int>,b:array<struct<c:string,d:array<long>>>>");
+    assert_eq!(schema.tree_string(), [
+      "root",
+      " |- a: array (nullable = true)",
+      "    |- element: int (containsNull = true)",
+      " |- b: array (nullable = false)",
+      "    |- element: struct (containsNull = false)",
+      "       |- c: string (nullable = true)",
+      "       |- d: array (nullable = true)",
+      "          |- element: long (containsNull = false)"
+    ].join("\n"));
+  }
+
+  #[test]
+  fn test_datatype_array_json_schema() {
+    let schema = DataType::struct_type(vec![])
+      .add_field("a", DataType::array_type(DataType::IntegerType, true));
+    assert_eq!(schema.to_json_schema(), "{\"type\":\"struct\",\"fields\":[\
+      {\"name\":\"a\",\"type\":{\"type\":\"array\",\"elementType\":\"integer\",\
+      \"containsNull\":true},\"nullable\":true,\"metadata\":{}}]}");
+    assert_eq!(DataType::from_json_schema(&schema.to_json_schema()).unwrap(), schema);
+    assert!(DataType::from_json_schema("{\"type\":\"array\",\"containsNull\":true}")
+      .is_err());
+  }
+
+  #[test]
+  fn test_datatype_array_merge() {
+    let left = DataType::array_type(DataType::struct_type(vec![])
+      .add_field_n("a", DataType::IntegerType, false), false);
+    let right = DataType::array_type(DataType::struct_type(vec![])
+      .add_field_n("a", DataType::IntegerType, true), true);
+    assert_eq!(left.merge(&right).unwrap(), right);
+    assert!(left.merge(&DataType::array_type(DataType::IntegerType, false)).is_err());
+  }
+
+  #[test]
+  fn test_datatype_map() {
+    let map = DataType::map_type(DataType::StringType, DataType::LongType, true);
+    assert_eq!(map, DataType::MapType(
+      Box::new(DataType::StringType), Box::new(DataType::LongType), true));
+    assert_eq!(map.type_name(), "map");
+    assert!(!map.is_atomic());
+    assert!(!map.is_struct());
+    assert_eq!(map.default_size(), 28);
+    assert_eq!(map.to_string(), "map<string,long>");
+    assert_eq!(map.arrow_type_name(), None);
+    assert!(map.contains(&mut |tpe| tpe == &DataType::LongType));
+
+    let map = DataType::map_type(DataType::IntegerType, DataType::struct_type(vec![])
+      .add_field("a", DataType::DoubleType)
+      .add_field("b", DataType::array_type(DataType::BooleanType, true)), false);
+    assert_eq!(map.default_size(), 13);
+    assert_eq!(map.to_string(), "map<int,struct<a:double,b:array<bool>>>");
+    assert_eq!(map.truncated_string(1), "map<int,...>");
+    assert_eq!(DataType::from_json_schema(&map.to_json_schema()).unwrap(), map);
+
+    let other = DataType::map_type(DataType::IntegerType, DataType::struct_type(vec![])
+      .add_field("c", DataType::StringType), true);
+    assert_eq!(map.merge(&other).unwrap(), DataType::map_type(DataType::IntegerType,
+      DataType::struct_type(vec![])
+        .add_field("a", DataType::DoubleType)
+        .add_field("b", DataType::array_type(DataType::BooleanType, true))
+        .add_field("c", DataType::StringType), true));
+  }
+
+  #[test]
+  fn test_datatype_map_tree_string() {
+    let schema = DataType::struct_type(vec![])
+      .add_field("a",
+        DataType::map_type(DataType::StringType, DataType::IntegerType, true))
+      .add_field_n("b",
+        DataType::map_type(DataType::LongType, DataType::struct_type(vec![])
+          .add_field("c", DataType::StringType), false), false);
+    assert_eq!(schema.to_string(),
+      "struct<a:map<string,int>,b:map<long,struct<c:string>>>");
+    assert_eq!(schema.tree_string(), [
+      "root",
+      " |- a: map (nullable = true)",
+      "    |- key: string",
+      "    |- value: int (valueContainsNull = true)",
+      " |- b: map (nullable = false)",
+      "    |- key: long",
+      "    |- value: struct (valueContainsNull = false)",
+      "       |- c: string (nullable = true)"
+    ].join("\n"));
+    assert_eq!(schema.to_json_schema(), "{\"type\":\"struct\",\"fields\":[\
+      {\"name\":\"a\",\"type\":{\"type\":\"map\",\"keyType\":\"string\",\
+      \"valueType\":\"integer\",\"valueContainsNull\":true},\"nullable\":true,\
+      \"metadata\":{}},\
+      {\"name\":\"b\",\"type\":{\"type\":\"map\",\"keyType\":\"long\",\
+      \"valueType\":{\"type\":\"struct\",\"fields\":[{\"name\":\"c\",\
+      \"type\":\"string\",\"nullable\":true,\"metadata\":{}}]},\
+      \"valueContainsNull\":false},\"nullable\":false,\"metadata\":{}}]}");
+  }
+
+  #[test]
+  fn test_datatype_user_defined() {
+    let udt = DataType::user_defined("point", DataType::IntegerType);
+    assert_eq!(udt.default_size(), 4);
+    assert!(udt.is_atomic());
+    assert!(!udt.is_struct());
+    assert_eq!(udt.to_string(), "point");
+    assert_eq!(udt.to_json_schema(),
+      "{\"type\":\"udt\",\"class\":\"point\",\"sqlType\":\"integer\"}");
+    assert!(udt.contains(&mut |tpe| tpe == &DataType::IntegerType));
+
+    let udt = DataType::user_defined("pair", DataType::struct_type(vec![])
+      .add_field("x", DataType::LongType)
+      .add_field("y", DataType::LongType));
+    assert_eq!(udt.default_size(), 16);
+    assert!(!udt.is_atomic());
+
+    let schema = DataType::struct_type(vec![])
+      .add_field_n("a", DataType::user_defined("point", DataType::IntegerType), false)
+      .add_field("b", udt);
+    assert_eq!(schema.to_string(), "struct<a:point,b:pair>");
+    assert_eq!(schema.tree_string(), [
+      "root",
+      " |- a: point (nullable = false)",
+      " |- b: pair (nullable = true)",
+      "    |- x: long (nullable = true)",
+      "    |- y: long (nullable = true)"
+    ].join("\n"));
+  }
+
+  #[test]
+  fn test_datatype_merge() {
+    assert_eq!(DataType::IntegerType.merge(&DataType::IntegerType).unwrap(),
+      DataType::IntegerType);
+    assert!(DataType::IntegerType.merge(&DataType::LongType).is_err());
+
+    let left = DataType::struct_type(vec![])
+      .add_field_n("a", DataType::IntegerType, false)
+      .add_field_n("b", DataType::struct_type(vec![])
+        .add_field_n("c", DataType::StringType, false), false);
+    let right = DataType::struct_type(vec![])
+      .add_field_n("b", DataType::struct_type(vec![])
+        .add_field_n("c", DataType::StringType, true)
+        .add_field_n("d", DataType::LongType, false), false)
+      .add_field_n("a", DataType::IntegerType, false)
+      .add_field("e", DataType::BooleanType);
+    let expected = DataType::struct_type(vec![])
+      .add_field_n("a", DataType::IntegerType, false)
+      .add_field_n("b", DataType::struct_type(vec![])
+        .add_field_n("c", DataType::StringType, true)
+        .add_field_n("d", DataType::LongType, true), false)
+      .add_field("e", DataType::BooleanType);
+    assert_eq!(left.merge(&right).unwrap(), expected);
+
+    // disjoint fields are nullable
+    let left = DataType::struct_type(vec![])
+      .add_field_n("a", DataType::IntegerType, false);
+    let right = DataType::struct_type(vec![])
+      .add_field_n("b", DataType::StringType, false);
+    assert_eq!(left.merge(&right).unwrap(), DataType::struct_type(vec![])
+      .add_field("a", DataType::IntegerType)
+      .add_field("b", DataType::StringType));
+
+    // overlapping fields with differing nullability
+    let right = DataType::struct_type(vec![]).add_field("a", DataType::IntegerType);
+    assert_eq!(left.merge(&right).unwrap(),
+      DataType::struct_type(vec![]).add_field("a", DataType::IntegerType));
+    assert_eq!(left.merge(&left).unwrap(), left);
+
+    let right = DataType::struct_type(vec![]).add_field("a", DataType::StringType);
+    assert!(DataType::struct_type(vec![]).merge(&DataType::IntegerType).is_err());
+    match left.merge(&right) {
+      Err(CatalystError::Schema(msg)) => assert_eq!(msg, "Failed to merge fields 'a': \
+        Failed to merge incompatible data types int and string"),
+      _ => panic!("Expected schema error")
+    }
+  }
+
+  #[test]
+  fn test_datatype_coerce_to_common() {
+    assert!(DataType::IntegerType.is_numeric());
+    assert!(DataType::DoubleType.is_numeric());
+    assert!(!DataType::StringType.is_numeric());
+    assert!(!DataType::BooleanType.is_numeric());
+
+    assert_eq!(DataType::IntegerType.coerce_to_common(&DataType::LongType),
+      Some(DataType::LongType));
+    assert_eq!(DataType::DoubleType.coerce_to_common(&DataType::ByteType),
+      Some(DataType::DoubleType));
+    assert_eq!(DataType::StringType.coerce_to_common(&DataType::StringType),
+      Some(DataType::StringType));
+    assert_eq!(DataType::StringType.coerce_to_common(&DataType::IntegerType), None);
+    assert_eq!(DataType::BooleanType.coerce_to_common(&DataType::IntegerType), None);
+
+    assert_eq!(DataType::CharType(10).coerce_to_common(&DataType::CharType(10)),
+      Some(DataType::CharType(10)));
+    assert_eq!(DataType::CharType(10).coerce_to_common(&DataType::StringType),
+      Some(DataType::StringType));
+    assert_eq!(DataType::StringType.coerce_to_common(&DataType::VarcharType(5)),
+      Some(DataType::StringType));
+    assert_eq!(DataType::CharType(10).coerce_to_common(&DataType::VarcharType(10)),
+      Some(DataType::StringType));
+    assert_eq!(DataType::VarcharType(5).coerce_to_common(&DataType::IntegerType), None);
+    assert!(DataType::CharType(3).join_compatible(&DataType::StringType));
+  }
+
+  #[test]
+  fn test_datatype_union_type() {
+    let left = DataType::struct_type(vec![])
+      .add_field_n("a", DataType::IntegerType, false)
+      .add_field_n("b", DataType::DoubleType, false)
+      .add_field_n("c", DataType::StringType, false);
+    let right = DataType::struct_type(vec![])
+      .add_field_n("x", DataType::LongType, false)
+      .add_field_n("y", DataType::ByteType, true)
+      .add_field_n("z", DataType::StringType, false);
+    let expected = DataType::struct_type(vec![])
+      .add_field_n("a", DataType::LongType, false)
+      .add_field_n("b", DataType::DoubleType, true)
+      .add_field_n("c", DataType::StringType, false);
+    assert_eq!(left.union_type(&right), Some(expected));
+
+    let left = DataType::array_type(DataType::IntegerType, false);
+    let right = DataType::array_type(DataType::LongType, true);
+    assert_eq!(left.union_type(&right),
+      Some(DataType::array_type(DataType::LongType, true)));
+    assert_eq!(left.union_type(&right).unwrap().to_string(), "array<long>");
+
+    let left = DataType::map_type(DataType::StringType, DataType::ShortType, false);
+    let right = DataType::map_type(DataType::StringType, DataType::FloatType, false);
+    assert_eq!(left.union_type(&right),
+      Some(DataType::map_type(DataType::StringType, DataType::FloatType, false)));
+
+    assert_eq!(DataType::CharType(1).union_type(&DataType::StringType),
+      Some(DataType::StringType));
+    assert_eq!(DataType::IntegerType.union_type(&DataType::StringType), None);
+    let left = DataType::struct_type(vec![]).add_field("a", DataType::IntegerType);
+    let right = DataType::struct_type(vec![]).add_field("a", DataType::BooleanType);
+    assert_eq!(left.union_type(&right), None);
+    let wider = left.clone().add_field("b", DataType::IntegerType);
+    assert_eq!(left.union_type(&wider), None);
+    assert_eq!(DataType::array_type(DataType::IntegerType, true)
+      .union_type(&DataType::IntegerType), None);
+  }
+
+  #[test]
+  fn test_widen_numeric() {
+    let types = [
+      DataType::ByteType,
+      DataType::ShortType,
+      DataType::IntegerType,
+      DataType::LongType,
+      DataType::FloatType,
+      DataType::DoubleType
+    ];
+    for (i, a) in types.iter().enumerate() {
+      for (j, b) in types.iter().enumerate() {
+        let expected = if i >= j { a.clone() } else { b.clone() };
+        assert_eq!(widen_numeric(a, b), Some(expected));
+      }
+    }
+    assert_eq!(widen_numeric(&DataType::IntegerType, &DataType::LongType),
+      Some(DataType::LongType));
+    assert_eq!(widen_numeric(&DataType::FloatType, &DataType::LongType),
+      Some(DataType::FloatType));
+    assert_eq!(widen_numeric(&DataType::StringType, &DataType::IntegerType), None);
+    assert_eq!(widen_numeric(&DataType::IntegerType, &DataType::BooleanType), None);
+    assert_eq!(widen_numeric(&DataType::StringType, &DataType::StringType), None);
+    let decimal = DataType::decimal_type(10, 2);
+    assert_eq!(widen_numeric(&decimal, &DataType::IntegerType), None);
+  }
+
+  #[test]
+  fn test_datatype_join_compatible() {
+    assert!(DataType::IntegerType.join_compatible(&DataType::LongType));
+    assert!(DataType::LongType.join_compatible(&DataType::IntegerType));
+    assert!(DataType::StringType.join_compatible(&DataType::StringType));
+    assert!(!DataType::StringType.join_compatible(&DataType::IntegerType));
+    assert!(!DataType::BooleanType.join_compatible(&DataType::DoubleType));
+  }
+
+  #[test]
+  fn test_datatype_default_size_with() {
+    assert_eq!(DataType::StringType.default_size(), 20);
+    assert_eq!(DataType::StringType.default_size_with(100), 100);
+    assert_eq!(DataType::IntegerType.default_size_with(100), 4);
+
+    let schema = DataType::struct_type(vec![])
+      .add_field("a", DataType::IntegerType)
+      .add_field("b", DataType::StringType)
+      .add_field("c", DataType::array_type(DataType::StringType, true))
+      .add_field("d", DataType::map_type(DataType::StringType,
+        DataType::struct_type(vec![]).add_field("e", DataType::StringType), true));
+    assert_eq!(schema.default_size(), 4 + 20 + 20 + 20 + 20);
+    assert_eq!(schema.default_size_with(20), schema.default_size());
+    assert_eq!(schema.default_size_with(50), 4 + 50 + 50 + 50 + 50);
+    assert_eq!(schema.default_size_with(0), 4);
+  }
+
+  #[test]
+  fn test_datatype_alignment() {
+    assert_eq!(DataType::BooleanType.alignment(), 1);
+    assert_eq!(DataType::ByteType.alignment(), 1);
+    assert_eq!(DataType::ShortType.alignment(), 2);
+    assert_eq!(DataType::IntegerType.alignment(), 4);
+    assert_eq!(DataType::FloatType.alignment(), 4);
+    assert_eq!(DataType::LongType.alignment(), 8);
+    assert_eq!(DataType::DoubleType.alignment(), 8);
+    assert_eq!(DataType::StringType.alignment(), 8);
+    assert_eq!(DataType::decimal_type(38, 2).alignment(), 16);
+    assert_eq!(DataType::struct_type(vec![]).alignment(), 1);
+    let schema = DataType::struct_type(vec![])
+      .add_field("a", DataType::ShortType)
+      .add_field("b", DataType::IntegerType);
+    assert_eq!(schema.alignment(), 4);
+  }
+
+  #[test]
+  fn test_datatype_fixed_width() {
+    assert_eq!(DataType::LongType.fixed_width(), Some(8));
+    assert_eq!(DataType::BooleanType.fixed_width(), Some(1));
+    assert_eq!(DataType::DateType.fixed_width(), Some(4));
+    assert_eq!(DataType::StringType.fixed_width(), None);
+    assert_eq!(DataType::BinaryType.fixed_width(), None);
+    assert_eq!(DataType::array_type(DataType::IntegerType, false).fixed_width(), None);
+    let schema = DataType::struct_type(vec![])
+      .add_field("a", DataType::ShortType)
+      .add_field("b", DataType::IntegerType);
+    assert_eq!(schema.fixed_width(), Some(6));
+    assert_eq!(schema.add_field("c", DataType::StringType).fixed_width(), None);
+  }
+
+  #[test]
+  fn test_datatype_equals_ignore_nullability() {
+    let left = DataType::struct_type(vec![])
+      .add_field_n("a", DataType::IntegerType, false)
+      .add_field("b", DataType::struct_type(vec![])
+        .add_field("c", DataType::array_type(DataType::StringType, true))
+        .add_field_n("d",
+          DataType::map_type(DataType::LongType, DataType::DoubleType, true), false));
+    let right = DataType::struct_type(vec![])
+      .add_field("a", DataType::IntegerType)
+      .add_field_n("b", DataType::struct_type(vec![])
+        .add_field_n("c", DataType::array_type(DataType::StringType, false), false)
+        .add_field("d",
+          DataType::map_type(DataType::LongType, DataType::DoubleType, false)), false);
+    assert!(left != right);
+    assert!(left.equals_ignore_nullability(&right));
+    assert!(right.equals_ignore_nullability(&left));
+
+    let other = DataType::struct_type(vec![])
+      .add_field("a", DataType::LongType)
+      .add_field("b", right.field("b").unwrap().data_type().clone());
+    assert!(!left.equals_ignore_nullability(&other));
+    let other = DataType::struct_type(vec![])
+      .add_field("x", DataType::IntegerType)
+      .add_field("b", right.field("b").unwrap().data_type().clone());
+    assert!(!left.equals_ignore_nullability(&other));
+    assert!(!left.equals_ignore_nullability(&DataType::struct_type(vec![])));
+
+    assert!(DataType::IntegerType.equals_ignore_nullability(&DataType::IntegerType));
+    assert!(!DataType::IntegerType.equals_ignore_nullability(&DataType::LongType));
+  }
+
+  #[test]
+  fn test_datatype_catalog_and_simple_string() {
+    let mut schema = DataType::struct_type(vec![]);
+    for idx in 0..30 {
+      schema = schema.add_field(&format!("c{}", idx), DataType::IntegerType);
+    }
+    let fields: Vec<String> = (0..30).map(|idx| format!("c{}:int", idx)).collect();
+    assert_eq!(schema.catalog_string(), format!("struct<{}>", fields.join(",")));
+    assert_eq!(schema.catalog_string(), schema.to_string());
+    assert_eq!(schema.simple_string(),
+      format!("struct<{},... 5 more fields>", fields[..25].join(",")));
+
+    let nested = DataType::struct_type(vec![])
+      .add_field("a", DataType::array_type(schema.clone(), true))
+      .add_field("b", DataType::StringType);
+    assert_eq!(nested.simple_string(), format!(
+      "struct<a:array<struct<{},... 5 more fields>>,b:string>", fields[..25].join(",")));
+
+    let narrow = DataType::struct_type(vec![])
+      .add_field("a", DataType::IntegerType)
+      .add_field("b", DataType::map_type(DataType::StringType, DataType::LongType, true));
+    assert_eq!(narrow.simple_string(), "struct<a:int,b:map<string,long>>");
+    assert_eq!(narrow.simple_string(), narrow.catalog_string());
+    assert_eq!(DataType::IntegerType.simple_string(), "int");
+  }
+
+  #[test]
+  fn test_datatype_arrow_type_name() {
+    assert_eq!(DataType::BooleanType.arrow_type_name(), Some("bool"));
+    assert_eq!(DataType::ByteType.arrow_type_name(), Some("int8"));
+    assert_eq!(DataType::ShortType.arrow_type_name(), Some("int16"));
+    assert_eq!(DataType::IntegerType.arrow_type_name(), Some("int32"));
+    assert_eq!(DataType::LongType.arrow_type_name(), Some("int64"));
+    assert_eq!(DataType::FloatType.arrow_type_name(), Some("float32"));
+    assert_eq!(DataType::DoubleType.arrow_type_name(), Some("float64"));
+    assert_eq!(DataType::StringType.arrow_type_name(), Some("utf8"));
+    assert_eq!(DataType::BinaryType.arrow_type_name(), Some("binary"));
+    assert_eq!(DataType::DateType.arrow_type_name(), Some("date32"));
+    assert_eq!(DataType::TimestampType.arrow_type_name(), Some("timestamp"));
+    assert_eq!(DataType::struct_type(vec![]).arrow_type_name(), None);
+    assert_eq!(DataType::user_defined("point", DataType::LongType).arrow_type_name(),
+      Some("int64"));
+  }
+
+  #[test]
+  fn test_datatype_field() {
+    let schema = DataType::struct_type(vec![])
+      .add_field("a", DataType::IntegerType)
+      .add_field_n("b", DataType::StringType, false);
+    assert_eq!(schema.field("b").map(|f| f.data_type()), Some(&DataType::StringType));
+    assert_eq!(schema.field("c"), None);
+    assert_eq!(schema.field("A"), None);
+  }
+
+  #[test]
+  fn test_datatype_field_index() {
+    let schema = DataType::struct_type(vec![])
+      .add_field("b", DataType::IntegerType)
+      .add_field("a", DataType::StringType)
+      .add_field("c", DataType::LongType);
+    assert_eq!(schema.field_index("b"), Some(0));
+    assert_eq!(schema.field_index("c"), Some(2));
+    assert_eq!(schema.field_index("d"), None);
+    assert_eq!(schema.field_index("B"), None);
+    assert_eq!(schema.field_names(), vec!["b", "a", "c"]);
+    assert_eq!(DataType::struct_type(vec![]).field_names(), Vec::<&str>::new());
+  }
+
+  #[test]
+  fn test_datatype_make_right_side_nullable() {
+    let schema = DataType::struct_type(vec![])
+      .add_field_n("a", DataType::IntegerType, false)
+      .add_field("b", DataType::StringType)
+      .add_field_n("c", DataType::struct_type(vec![])
+        .add_field_n("d", DataType::LongType, false), false);
+    let expected = DataType::struct_type(vec![])
+      .add_field("a", DataType::IntegerType)
+      .add_field("b", DataType::StringType)
+      .add_field("c", DataType::struct_type(vec![])
+        .add_field_n("d", DataType::LongType, false));
+    assert_eq!(schema.make_right_side_nullable(), expected);
+    assert_eq!(expected.make_right_side_nullable(), expected);
+  }
+
+  #[test]
+  fn test_datatype_make_nullable_at_path() {
+    let schema = DataType::struct_type(vec![])
+      .add_field_n("a", DataType::struct_type(vec![])
+        .add_field_n("b", DataType::IntegerType, false), false);
+    let expected = DataType::struct_type(vec![])
+      .add_field_n("a", DataType::struct_type(vec![])
+        .add_field_n("b", DataType::IntegerType, true), false);
+    let res = schema.make_nullable_at_path("a.b").unwrap();
+    assert_eq!(res, expected);
+    assert!(!res.field("a").unwrap().is_nullable());
+    assert!(res.field("a").unwrap().data_type().field("b").unwrap().is_nullable());
+
+    let res = schema.make_nullable_at_path("a").unwrap();
+    assert!(res.field("a").unwrap().is_nullable());
+    assert!(!res.field("a").unwrap().data_type().field("b").unwrap().is_nullable());
+  }
+
+  #[test]
+  fn test_datatype_make_nullable_at_path_missing() {
+    let schema = DataType::struct_type(vec![])
+      .add_field_n("a", DataType::struct_type(vec![])
+        .add_field_n("b", DataType::IntegerType, false), false);
+    match schema.make_nullable_at_path("a.c") {
+      Err(CatalystError::Schema(msg)) => {
+        assert_eq!(msg, "Field 'c' does not exist for path 'a.c'")
+      },
+      _ => panic!("Expected schema error")
+    }
+    match schema.make_nullable_at_path("a.b.c") {
+      Err(CatalystError::Schema(msg)) => {
+        assert_eq!(msg, "Cannot resolve path 'a.b.c' in non-struct type int")
+      },
+      _ => panic!("Expected schema error")
+    }
+    assert!(schema.make_nullable_at_path("").is_err());
+  }
+
+  #[test]
+  #[should_panic(expected = "Not a StructType")]
+  fn test_datatype_make_right_side_nullable_not_struct() {
+    DataType::IntegerType.make_right_side_nullable();
+  }
+
+  #[test]
+  #[should_panic(expected = "Not a StructType")]
+  fn test_datatype_field_not_struct() {
+    DataType::IntegerType.field("a");
+  }
+
+  #[test]
+  #[should_panic(expected = "Not a StructType")]
+  fn test_datatype_field_index_not_struct() {
+    DataType::IntegerType.field_index("a");
+  }
+
+  #[test]
+  #[should_panic(expected = "Not a StructType")]
+  fn test_datatype_field_names_not_struct() {
+    DataType::array_type(DataType::IntegerType, true).field_names();
+  }
+
+  #[test]
+  fn test_datatype_contains() {
+    let schema = DataType::struct_type(vec![])
+      .add_field("a", DataType::IntegerType)
+      .add_field("b", DataType::struct_type(vec![])
+        .add_field("c", DataType::StringType)
+      );
+
+    assert!(schema.contains(&mut |dt| dt == &DataType::IntegerType));
+    assert!(schema.contains(&mut |dt| dt == &DataType::StringType));
+    assert!(schema.contains(&mut |dt| dt.is_struct() && dt.num_fields() == 1));
+    assert!(!schema.contains(&mut |dt| dt == &DataType::LongType));
+
+    assert!(DataType::LongType.contains(&mut |dt| dt == &DataType::LongType));
+    assert!(!DataType::struct_type(vec![]).contains(&mut |dt| dt.is_atomic()));
+  }
+
   #[test]
   fn test_datatype_display() {
     let schema = DataType::struct_type(vec![])
@@ -314,6 +1737,33 @@ mod tests {
     );
   }
 
+  #[test]
+  fn test_datatype_truncated_string() {
+    let schema = DataType::struct_type(vec![])
+      .add_field("a", DataType::IntegerType)
+      .add_field("b", DataType::struct_type(vec![])
+        .add_field("c", DataType::StringType)
+        .add_field("d", DataType::struct_type(vec![])
+          .add_field("e", DataType::LongType)));
+
+    assert_eq!(schema.truncated_string(0), "...");
+    assert_eq!(schema.truncated_string(1), "struct<a:int,b:...>");
+    assert_eq!(schema.truncated_string(2), "struct<a:int,b:struct<c:string,d:...>>");
+    assert_eq!(schema.truncated_string(3), schema.to_string());
+    assert_eq!(schema.truncated_string(10), schema.to_string());
+    assert_eq!(DataType::IntegerType.truncated_string(0), "int");
+
+    let udt = DataType::user_defined("point", DataType::struct_type(vec![])
+      .add_field("x", DataType::DoubleType)
+      .add_field("y", DataType::array_type(DataType::DoubleType, false)));
+    assert_eq!(udt.truncated_string(0), "...");
+    assert_eq!(udt.truncated_string(1), "struct<x:double,y:...>");
+    assert_eq!(udt.truncated_string(2), "struct<x:double,y:array<double>>");
+
+    let schema = DataType::struct_type(vec![]).add_field("p", udt);
+    assert_eq!(schema.truncated_string(1), "struct<p:...>");
+  }
+
   #[test]
   fn test_datatype_tree_string() {
     let schema = DataType::struct_type(vec![])
@@ -347,6 +1797,202 @@ mod tests {
     )
   }
 
+  #[test]
+  fn test_datatype_temporal() {
+    assert_eq!(DataType::DateType.default_size(), 4);
+    assert_eq!(DataType::TimestampType.default_size(), 8);
+    assert!(!DataType::DateType.is_numeric());
+
+    let schema = DataType::struct_type(vec![])
+      .add_field_n("a", DataType::DateType, false)
+      .add_field("b", DataType::struct_type(vec![])
+        .add_field("c", DataType::TimestampType));
+    assert_eq!(schema.default_size(), 12);
+    assert_eq!(schema.to_string(), "struct<a:date,b:struct<c:timestamp>>");
+    assert_eq!(schema.tree_string(), [
+      "root",
+      " |- a: date (nullable = false)",
+      " |- b: struct (nullable = true)",
+      "    |- c: timestamp (nullable = true)"
+    ].join("\n"));
+    assert_eq!(DataType::from_json_schema(&schema.to_json_schema()).unwrap(), schema);
+  }
+
+  #[test]
+  fn test_datatype_to_json_schema() {
+    assert_eq!(DataType::IntegerType.to_json_schema(), "\"integer\"");
+    assert_eq!(DataType::BooleanType.to_json_schema(), "\"boolean\"");
+    assert_eq!(DataType::struct_type(vec![]).to_json_schema(),
+      "{\"type\":\"struct\",\"fields\":[]}");
+
+    let schema = DataType::struct_type(vec![])
+      .add_field("a", DataType::LongType)
+      .add_field_n("b", DataType::struct_type(vec![])
+        .add_field("c", DataType::StringType), false);
+    assert_eq!(
+      schema.to_json_schema(),
+      "{\"type\":\"struct\",\"fields\":[\
+        {\"name\":\"a\",\"type\":\"long\",\"nullable\":true,\"metadata\":{}},\
+        {\"name\":\"b\",\"type\":{\"type\":\"struct\",\"fields\":[\
+          {\"name\":\"c\",\"type\":\"string\",\"nullable\":true,\"metadata\":{}}]},\
+        \"nullable\":false,\"metadata\":{}}]}"
+    );
+  }
+
+  #[test]
+  fn test_datatype_to_json_schema_pretty() {
+    let schema = DataType::struct_type(vec![])
+      .add_field("a", DataType::IntegerType)
+      .add_field_n("b", DataType::StringType, false);
+
+    assert_eq!(schema.to_json_schema_pretty(2), vec![
+      "{",
+      "  \"type\": \"struct\",",
+      "  \"fields\": [",
+      "    {",
+      "      \"name\": \"a\",",
+      "      \"type\": \"integer\",",
+      "      \"nullable\": true,",
+      "      \"metadata\": {}",
+      "    },",
+      "    {",
+      "      \"name\": \"b\",",
+      "      \"type\": \"string\",",
+      "      \"nullable\": false,",
+      "      \"metadata\": {}",
+      "    }",
+      "  ]",
+      "}"
+    ].join("\n"));
+
+    let schema = DataType::struct_type(vec![])
+      .add_field("a", DataType::struct_type(vec![]).add_field("b", DataType::ByteType));
+
+    assert_eq!(schema.to_json_schema_pretty(4), vec![
+      "{",
+      "    \"type\": \"struct\",",
+      "    \"fields\": [",
+      "        {",
+      "            \"name\": \"a\",",
+      "            \"type\": {",
+      "                \"type\": \"struct\",",
+      "                \"fields\": [",
+      "                    {",
+      "                        \"name\": \"b\",",
+      "                        \"type\": \"byte\",",
+      "                        \"nullable\": true,",
+      "                        \"metadata\": {}",
+      "                    }",
+      "                ]",
+      "            },",
+      "            \"nullable\": true,",
+      "            \"metadata\": {}",
+      "        }",
+      "    ]",
+      "}"
+    ].join("\n"));
+    assert_eq!(DataType::LongType.to_json_schema_pretty(2), "\"long\"");
+  }
+
+  #[test]
+  fn test_datatype_from_json_schema() {
+    let schema = DataType::struct_type(vec![])
+      .add_field("a", DataType::IntegerType)
+      .add_field_n("b", DataType::struct_type(vec![])
+        .add_field_n("c", DataType::StringType, false)
+        .add_field("d", DataType::BinaryType), false)
+      .add_field("e", DataType::BooleanType);
+    assert_eq!(DataType::from_json_schema(&schema.to_json_schema()).unwrap(), schema);
+    let pretty = schema.to_json_schema_pretty(2);
+    assert_eq!(DataType::from_json_schema(&pretty).unwrap(), schema);
+    assert_eq!(DataType::from_json_schema("\"double\"").unwrap(), DataType::DoubleType);
+
+    // metadata is optional
+    let json = "{\"type\":\"struct\",\"fields\":[\
+      {\"name\":\"a\",\"type\":\"long\",\"nullable\":false}]}";
+    assert_eq!(
+      DataType::from_json_schema(json).unwrap(),
+      DataType::struct_type(vec![]).add_field_n("a", DataType::LongType, false)
+    );
+  }
+
+  #[test]
+  fn test_datatype_from_json_schema_error() {
+    assert!(DataType::from_json_schema("").is_err());
+    assert!(DataType::from_json_schema("\"unknown\"").is_err());
+    assert!(DataType::from_json_schema("{\"type\":\"unknown\"}").is_err());
+    assert!(DataType::from_json_schema("{\"type\":\"struct\"}").is_err());
+    assert!(DataType::from_json_schema("{\"type\":\"struct\",\"fields\":[{}]}").is_err());
+    assert!(DataType::from_json_schema("{\"type\":\"struct\",\"fields\":[\
+      {\"name\":\"a\",\"type\":\"long\"}]}").is_err());
+  }
+
+  #[test]
+  fn test_datatype_json_schema_roundtrip() {
+    let schema = DataType::struct_type(vec![])
+      .add_field_n("a", DataType::LongType, false)
+      .add_field("b", DataType::struct_type(vec![])
+        .add_field("c", DataType::array_type(DataType::struct_type(vec![])
+          .add_field_n("d", DataType::DoubleType, false), true))
+        .add_field_n("e", DataType::map_type(DataType::StringType,
+          DataType::decimal_type(10, 2), false), false));
+    let res = DataType::from_json(&schema.to_json()).unwrap();
+    assert_eq!(res, schema);
+    assert!(!res.field("a").unwrap().is_nullable());
+    assert!(res.field("b").unwrap().is_nullable());
+
+    assert_eq!(DataType::from_json("\"string\"").unwrap(), DataType::StringType);
+    match DataType::from_json("{\"type\":\"unknown\"}") {
+      Err(CatalystError::Schema(msg)) => assert_eq!(msg, "Unknown type name unknown"),
+      _ => panic!("Expected schema error")
+    }
+    assert!(DataType::from_json("{\"type\":\"struct\",\"fields\":[\
+      {\"type\":\"long\",\"nullable\":true}]}").is_err());
+
+    let schema = DataType::struct_type(vec![])
+      .add_field_n("a", DataType::user_defined("point", DataType::struct_type(vec![])
+        .add_field_n("x", DataType::DoubleType, false)
+        .add_field_n("y", DataType::DoubleType, false)), false)
+      .add_field("b", DataType::array_type(
+        DataType::user_defined("id", DataType::LongType), true));
+    let res = DataType::from_json(&schema.to_json()).unwrap();
+    assert_eq!(res, schema);
+    assert!(DataType::from_json("{\"type\":\"udt\",\"sqlType\":\"long\"}")
+      .is_err());
+    assert!(DataType::from_json("{\"type\":\"udt\",\"class\":\"id\"}").is_err());
+  }
+
+  #[test]
+  fn test_structfield_metadata() {
+    let field = StructField::new("a".to_owned(), DataType::IntegerType);
+    assert_eq!(field.metadata(), None);
+    assert_eq!(field.clone().with_metadata("{}").unwrap().metadata(), None);
+
+    let field = field.with_metadata("{ \"comment\" : \"hi\" }").unwrap();
+    assert_eq!(field.metadata(), Some("{\"comment\":\"hi\"}"));
+
+    let schema = DataType::struct_type(vec![field]);
+    assert_eq!(
+      schema.to_json_schema(),
+      "{\"type\":\"struct\",\"fields\":[\
+        {\"name\":\"a\",\"type\":\"integer\",\"nullable\":true,\
+        \"metadata\":{\"comment\":\"hi\"}}]}"
+    );
+    assert_eq!(DataType::from_json_schema(&schema.to_json_schema()).unwrap(), schema);
+  }
+
+  #[test]
+  fn test_structfield_invalid_metadata() {
+    let field = StructField::new("a".to_owned(), DataType::IntegerType);
+    match field.clone().with_metadata("[]") {
+      Err(CatalystError::Schema(msg)) => {
+        assert_eq!(msg, "Invalid metadata [], expected JSON object")
+      },
+      _ => panic!("Expected schema error")
+    }
+    assert!(field.with_metadata("{\"a\":").is_err());
+  }
+
   #[test]
   fn test_structfield() {
     let field = StructField::new("field_name".to_owned(), DataType::IntegerType);